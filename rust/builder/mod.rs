@@ -7,8 +7,8 @@
 use crate::{
     common::token,
     pattern::{
-        Constant, Expression, Function, Label, Negation, Predicate, RolePlayerConstraint, RuleLabel, ThingStatement,
-        ThingStatementBuilder, TypeStatement, Value,
+        Constant, Expression, Function, FunctionCall, FunctionLabel, Label, Negation, Optional, Predicate,
+        RolePlayerConstraint, RuleLabel, Struct, ThingStatement, ThingStatementBuilder, TypeStatement, Value,
     },
     query::ProjectionKeyLabel,
     variable::{ConceptVariable, TypeReference, ValueVariable},
@@ -29,6 +29,13 @@ macro_rules! typeql_insert {
     }
 }
 
+#[macro_export]
+macro_rules! typeql_put {
+    ($($thing_statement:expr),* $(,)?) => {
+        $crate::query::TypeQLPut::new(vec![$($thing_statement),*])
+    }
+}
+
 #[macro_export]
 macro_rules! typeql_define {
     ($($pattern:expr),* $(,)?) => {
@@ -95,14 +102,37 @@ macro_rules! sort_vars {
     }}
 }
 
+#[macro_export]
+macro_rules! select_vars {
+    ($($arg:expr),*) => {{
+        $crate::query::Selection::new(vec![$($crate::query::SelectVariable::from($arg), )*])
+    }}
+}
+
 pub fn not<T: Into<Pattern>>(pattern: T) -> Negation {
     Negation::new(pattern.into())
 }
 
+pub fn try_<T: Into<Pattern>>(pattern: T) -> Optional {
+    Optional::new(pattern.into())
+}
+
 pub fn rule(name: &str) -> RuleLabel {
     RuleLabel::from(name)
 }
 
+pub fn fun(name: &str) -> FunctionLabel {
+    FunctionLabel::from(name)
+}
+
+pub fn call<T: Into<Expression>>(name: &str, args: impl IntoIterator<Item = T>) -> FunctionCall {
+    FunctionCall::new(Label::from(name), args.into_iter().map(Into::into).collect())
+}
+
+pub fn struct_(name: &str) -> Struct {
+    Struct::from(name)
+}
+
 pub fn cvar(var: impl Into<ConceptVariable>) -> ConceptVariable {
     var.into()
 }
@@ -151,8 +181,8 @@ pub fn gte<T: Into<Value>>(value: T) -> Predicate {
     Predicate::new(token::Predicate::Gte, value.into())
 }
 
-pub fn contains<T: Into<String>>(value: T) -> Predicate {
-    Predicate::new(token::Predicate::Contains, Value::from(value.into()))
+pub fn contains<T: Into<Value>>(value: T) -> Predicate {
+    Predicate::new(token::Predicate::Contains, value.into())
 }
 
 pub fn like<T: Into<String>>(value: T) -> Predicate {
@@ -174,3 +204,47 @@ pub fn floor<T: Into<Expression>>(arg: T) -> Function {
 pub fn round<T: Into<Expression>>(arg: T) -> Function {
     Function { function_name: token::Function::Round, args: vec![arg.into()] }
 }
+
+pub fn length<T: Into<Expression>>(arg: T) -> Function {
+    Function { function_name: token::Function::Length, args: vec![arg.into()] }
+}
+
+pub fn lowercase<T: Into<Expression>>(arg: T) -> Function {
+    Function { function_name: token::Function::Lowercase, args: vec![arg.into()] }
+}
+
+pub fn uppercase<T: Into<Expression>>(arg: T) -> Function {
+    Function { function_name: token::Function::Uppercase, args: vec![arg.into()] }
+}
+
+pub fn substring<T: Into<Expression>, U: Into<Expression>, V: Into<Expression>>(
+    arg: T,
+    start: U,
+    length: V,
+) -> Function {
+    Function { function_name: token::Function::Substring, args: vec![arg.into(), start.into(), length.into()] }
+}
+
+pub fn concat<T: Into<Expression>>(args: impl IntoIterator<Item = T>) -> Function {
+    Function { function_name: token::Function::Concat, args: args.into_iter().map(Into::into).collect() }
+}
+
+pub fn year<T: Into<Expression>>(arg: T) -> Function {
+    Function { function_name: token::Function::Year, args: vec![arg.into()] }
+}
+
+pub fn month<T: Into<Expression>>(arg: T) -> Function {
+    Function { function_name: token::Function::Month, args: vec![arg.into()] }
+}
+
+pub fn day<T: Into<Expression>>(arg: T) -> Function {
+    Function { function_name: token::Function::Day, args: vec![arg.into()] }
+}
+
+pub fn add_duration<T: Into<Expression>, U: Into<Expression>>(arg: T, duration: U) -> Function {
+    Function { function_name: token::Function::AddDuration, args: vec![arg.into(), duration.into()] }
+}
+
+pub fn subtract_duration<T: Into<Expression>, U: Into<Expression>>(arg: T, duration: U) -> Function {
+    Function { function_name: token::Function::SubtractDuration, args: vec![arg.into(), duration.into()] }
+}
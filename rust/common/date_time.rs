@@ -4,7 +4,38 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use chrono::{NaiveDateTime, Timelike};
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Timelike};
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum DateTimePrecision {
+    #[default]
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl DateTimePrecision {
+    pub(crate) fn admits(&self, date_time: &NaiveDateTime) -> bool {
+        let unit = match self {
+            DateTimePrecision::Millis => 1_000_000,
+            DateTimePrecision::Micros => 1_000,
+            DateTimePrecision::Nanos => 1,
+        };
+        date_time.nanosecond() % unit == 0
+    }
+}
+
+impl fmt::Display for DateTimePrecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateTimePrecision::Millis => write!(f, "millisecond"),
+            DateTimePrecision::Micros => write!(f, "microsecond"),
+            DateTimePrecision::Nanos => write!(f, "nanosecond"),
+        }
+    }
+}
 
 pub(crate) fn parse(date_time_text: &str) -> Option<NaiveDateTime> {
     let has_seconds = date_time_text.matches(':').count() == 2;
@@ -24,12 +55,51 @@ pub(crate) fn parse(date_time_text: &str) -> Option<NaiveDateTime> {
     }
 }
 
+pub(crate) fn parse_with_offset(date_time_text: &str) -> Option<DateTime<FixedOffset>> {
+    let (date_time, offset) = match date_time_text.strip_suffix('Z') {
+        Some(date_time) => (date_time, FixedOffset::east_opt(0)?),
+        None => {
+            let (date_time, offset) = date_time_text.split_at(date_time_text.len().checked_sub(6)?);
+            let sign = match &offset[..1] {
+                "+" => 1,
+                "-" => -1,
+                _ => return None,
+            };
+            let (hours, minutes) = offset[1..].split_once(':')?;
+            let seconds = hours.parse::<i32>().ok()? * 3600 + minutes.parse::<i32>().ok()? * 60;
+            (date_time, FixedOffset::east_opt(sign * seconds)?)
+        }
+    };
+    offset.from_local_datetime(&parse(date_time)?).single()
+}
+
+pub(crate) fn fraction_format(date_time: &NaiveDateTime) -> &'static str {
+    if DateTimePrecision::Millis.admits(date_time) {
+        "%.3f"
+    } else if DateTimePrecision::Micros.admits(date_time) {
+        "%.6f"
+    } else {
+        "%.9f"
+    }
+}
+
 pub(crate) fn format(date_time: &NaiveDateTime) -> String {
     if date_time.time().nanosecond() > 0 {
-        date_time.format("%Y-%m-%dT%H:%M:%S.%3f").to_string()
+        date_time.format(&format!("%Y-%m-%dT%H:%M:%S{}", fraction_format(date_time))).to_string()
     } else if date_time.time().second() > 0 {
         date_time.format("%Y-%m-%dT%H:%M:%S").to_string()
     } else {
         date_time.format("%Y-%m-%dT%H:%M").to_string()
     }
 }
+
+pub(crate) fn format_with_offset(date_time: &DateTime<FixedOffset>) -> String {
+    let offset = date_time.offset().local_minus_utc();
+    if offset == 0 {
+        format!("{}Z", format(&date_time.naive_local()))
+    } else {
+        let sign = if offset < 0 { '-' } else { '+' };
+        let (hours, minutes) = (offset.abs() / 3600, offset.abs() % 3600 / 60);
+        format!("{}{sign}{hours:02}:{minutes:02}", format(&date_time.naive_local()))
+    }
+}
@@ -0,0 +1,114 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{fmt, str::FromStr};
+
+use crate::common::{error::TypeQLError, Error};
+
+const FRACTIONAL_DIGITS: usize = 19;
+const SCALE: u64 = 10_u64.pow(FRACTIONAL_DIGITS as u32);
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Decimal {
+    integer: i64,
+    fractional: u64,
+}
+
+impl Decimal {
+    pub fn as_double(&self) -> f64 {
+        self.integer as f64 + self.fractional as f64 / SCALE as f64
+    }
+
+    pub(crate) fn checked_add(self, other: Decimal) -> Option<Decimal> {
+        Decimal::from_scaled(self.scaled().checked_add(other.scaled())?)
+    }
+
+    pub(crate) fn checked_sub(self, other: Decimal) -> Option<Decimal> {
+        Decimal::from_scaled(self.scaled().checked_sub(other.scaled())?)
+    }
+
+    pub(crate) fn checked_mul(self, other: Decimal) -> Option<Decimal> {
+        let (lhs_integer, lhs_fractional) = (self.integer as i128, self.fractional as i128);
+        let (rhs_integer, rhs_fractional) = (other.integer as i128, other.fractional as i128);
+        let scaled = (lhs_integer * rhs_integer)
+            .checked_mul(SCALE as i128)?
+            .checked_add(lhs_integer * rhs_fractional)?
+            .checked_add(lhs_fractional * rhs_integer)?
+            .checked_add(lhs_fractional * rhs_fractional / SCALE as i128)?;
+        Decimal::from_scaled(scaled)
+    }
+
+    pub fn checked_div(self, divisor: i64) -> Option<Decimal> {
+        Decimal::from_scaled(self.scaled().checked_div(divisor as i128)?)
+    }
+
+    pub(crate) fn floor(self) -> i64 {
+        self.integer
+    }
+
+    pub(crate) fn ceil(self) -> Option<i64> {
+        if self.fractional > 0 {
+            self.integer.checked_add(1)
+        } else {
+            Some(self.integer)
+        }
+    }
+
+    pub(crate) fn round(self) -> Option<i64> {
+        if self.fractional >= SCALE / 2 {
+            self.integer.checked_add(1)
+        } else {
+            Some(self.integer)
+        }
+    }
+
+    fn from_scaled(scaled: i128) -> Option<Self> {
+        let integer = i64::try_from(scaled.div_euclid(SCALE as i128)).ok()?;
+        Some(Decimal { integer, fractional: scaled.rem_euclid(SCALE as i128) as u64 })
+    }
+
+    fn scaled(&self) -> i128 {
+        self.integer as i128 * SCALE as i128 + self.fractional as i128
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(long: i64) -> Self {
+        Decimal { integer: long, fractional: 0 }
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = Error;
+
+    fn from_str(decimal: &str) -> Result<Self, Self::Err> {
+        Ok(parse(decimal).ok_or_else(|| TypeQLError::InvalidDecimal { decimal: decimal.to_owned() })?)
+    }
+}
+
+pub(crate) fn parse(decimal_text: &str) -> Option<Decimal> {
+    let (integer, fraction) = decimal_text.split_once('.').unwrap_or((decimal_text, ""));
+    let is_negative = integer.starts_with('-');
+    let digits = integer.trim_start_matches(['+', '-']);
+    if digits.is_empty() || fraction.len() > FRACTIONAL_DIGITS || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let integer = i64::from_str(integer).ok()? as i128 * SCALE as i128;
+    let fraction = if fraction.is_empty() { 0 } else { format!("{fraction:0<19}").parse::<i128>().ok()? };
+    Decimal::from_scaled(if is_negative { integer - fraction } else { integer + fraction })
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scaled = self.scaled();
+        let sign = if scaled < 0 { "-" } else { "" };
+        let (integer, fraction) = (scaled.abs() / SCALE as i128, scaled.abs() % SCALE as i128);
+        match fraction {
+            0 => write!(f, "{sign}{integer}"),
+            fraction => write!(f, "{sign}{integer}.{}", format!("{fraction:019}").trim_end_matches('0')),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{fmt, str::FromStr};
+
+use chrono::{Days, Months, NaiveDateTime, TimeDelta};
+
+use crate::common::{error::TypeQLError, Error};
+
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const NANOS_PER_MINUTE: i64 = 60 * NANOS_PER_SECOND;
+const NANOS_PER_HOUR: i64 = 60 * NANOS_PER_MINUTE;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Duration {
+    months: u32,
+    days: u64,
+    nanos: i64,
+}
+
+impl Duration {
+    pub(crate) fn add_to(&self, date_time: NaiveDateTime) -> Option<NaiveDateTime> {
+        date_time
+            .checked_add_months(Months::new(self.months))?
+            .checked_add_days(Days::new(self.days))?
+            .checked_add_signed(TimeDelta::nanoseconds(self.nanos))
+    }
+
+    pub(crate) fn subtract_from(&self, date_time: NaiveDateTime) -> Option<NaiveDateTime> {
+        date_time
+            .checked_sub_months(Months::new(self.months))?
+            .checked_sub_days(Days::new(self.days))?
+            .checked_sub_signed(TimeDelta::nanoseconds(self.nanos))
+    }
+}
+
+impl FromStr for Duration {
+    type Err = Error;
+
+    fn from_str(duration: &str) -> Result<Self, Self::Err> {
+        Ok(parse(duration).ok_or_else(|| TypeQLError::InvalidDuration { duration: duration.to_owned() })?)
+    }
+}
+
+pub(crate) fn parse(duration_text: &str) -> Option<Duration> {
+    let (date, time) = match duration_text.strip_prefix('P')?.split_once('T') {
+        Some((_, "")) => return None,
+        Some((date, time)) => (date, Some(time)),
+        None => (duration_text.strip_prefix('P')?, None),
+    };
+    let mut duration = Duration { months: 0, days: 0, nanos: 0 };
+    let mut has_component = false;
+    for (amount, designator) in components(date, "YMWD")? {
+        let amount: u32 = amount.parse().ok()?;
+        match designator {
+            'Y' => duration.months = duration.months.checked_add(amount.checked_mul(12)?)?,
+            'M' => duration.months = duration.months.checked_add(amount)?,
+            'W' => duration.days += u64::from(amount) * 7,
+            _ => duration.days += u64::from(amount),
+        }
+        has_component = true;
+    }
+    for (amount, designator) in time.map_or(Some(Vec::new()), |time| components(time, "HMS"))? {
+        let nanos = match designator {
+            'H' => amount.parse::<i64>().ok()?.checked_mul(NANOS_PER_HOUR)?,
+            'M' => amount.parse::<i64>().ok()?.checked_mul(NANOS_PER_MINUTE)?,
+            _ => seconds_as_nanos(amount)?,
+        };
+        duration.nanos = duration.nanos.checked_add(nanos)?;
+        has_component = true;
+    }
+    has_component.then_some(duration)
+}
+
+fn components<'a>(text: &'a str, designators: &str) -> Option<Vec<(&'a str, char)>> {
+    let mut components = Vec::new();
+    let mut designators = designators.chars();
+    let mut start = 0;
+    for (index, char) in text.char_indices() {
+        if char.is_ascii_alphabetic() {
+            if index == start || !designators.any(|designator| designator == char) {
+                return None;
+            }
+            components.push((&text[start..index], char));
+            start = index + 1;
+        } else if !char.is_ascii_digit() && char != '.' {
+            return None;
+        }
+    }
+    (start == text.len()).then_some(components)
+}
+
+fn seconds_as_nanos(seconds_text: &str) -> Option<i64> {
+    let (seconds, fraction) = seconds_text.split_once('.').unwrap_or((seconds_text, ""));
+    if fraction.len() > 9 || seconds.is_empty() {
+        return None;
+    }
+    let fraction = if fraction.is_empty() { 0 } else { format!("{fraction:0<9}").parse::<i64>().ok()? };
+    seconds.parse::<i64>().ok()?.checked_mul(NANOS_PER_SECOND)?.checked_add(fraction)
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "P")?;
+        let (years, months) = (self.months / 12, self.months % 12);
+        if years > 0 {
+            write!(f, "{years}Y")?;
+        }
+        if months > 0 {
+            write!(f, "{months}M")?;
+        }
+        if self.days > 0 {
+            write!(f, "{}D", self.days)?;
+        }
+        if self.nanos == 0 {
+            return if self.months == 0 && self.days == 0 { write!(f, "T0S") } else { Ok(()) };
+        }
+        write!(f, "T")?;
+        let (hours, minutes) = (self.nanos / NANOS_PER_HOUR, self.nanos % NANOS_PER_HOUR / NANOS_PER_MINUTE);
+        let (seconds, nanos) = (self.nanos % NANOS_PER_MINUTE / NANOS_PER_SECOND, self.nanos % NANOS_PER_SECOND);
+        if hours > 0 {
+            write!(f, "{hours}H")?;
+        }
+        if minutes > 0 {
+            write!(f, "{minutes}M")?;
+        }
+        match nanos {
+            0 if seconds > 0 => write!(f, "{seconds}S"),
+            0 => Ok(()),
+            nanos => write!(f, "{seconds}.{}S", format!("{nanos:09}").trim_end_matches('0')),
+        }
+    }
+}
@@ -11,9 +11,12 @@ use itertools::Itertools;
 use pest::error::{Error as PestError, LineColLocation};
 
 use crate::{
-    common::token,
+    common::{date_time::DateTimePrecision, token},
     error_messages,
-    pattern::{Label, Pattern, ThingStatement, Value},
+    pattern::{
+        Annotation, Cardinality, Constant, Expression, FunctionCall, FunctionType, Label, Pattern, Range,
+        ThingStatement, Value, ValueType,
+    },
     variable::{ConceptVariable, Variable},
     write_joined,
 };
@@ -29,6 +32,25 @@ pub struct Error {
     errors: Vec<TypeQLError>,
 }
 
+impl Error {
+    pub(crate) fn admitting_precision(self, precision: DateTimePrecision) -> Result<(), Error> {
+        let errors: Vec<TypeQLError> = self
+            .errors
+            .into_iter()
+            .filter_map(|error| match error {
+                TypeQLError::InvalidConstraintDatetimePrecision { date_time, .. } => (!precision.admits(&date_time))
+                    .then_some(TypeQLError::InvalidConstraintDatetimePrecision { date_time, precision }),
+                error => Some(error),
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error { errors })
+        }
+    }
+}
+
 impl StdError for Error {}
 
 impl From<TypeQLError> for Error {
@@ -126,8 +148,8 @@ error_messages! { TypeQLError
         23: "A relation variable has not been provided with role players.",
     InvalidConstraintPredicate { predicate: token::Predicate, value: Value } =
         24: "The '{predicate}' constraint may only accept a string value as its operand, got '{value}' instead.",
-    InvalidConstraintDatetimePrecision { date_time: NaiveDateTime } =
-        25: "Attempted to assign DateTime value of '{date_time}' which is more precise than 1 millisecond.",
+    InvalidConstraintDatetimePrecision { date_time: NaiveDateTime, precision: DateTimePrecision } =
+        25: "Attempted to assign DateTime value of '{date_time}' which is more precise than 1 {precision}.",
     InvalidDefineQueryVariable =
         26: "Invalid define/undefine query. User-defined variables are not accepted in a define/undefine query.",
     InvalidUndefineQueryRule { rule_label: Label } =
@@ -154,4 +176,150 @@ error_messages! { TypeQLError
         37: "Illegal grammar: '{input}'",
     InvalidTypeLabel { label: String } =
         38: "The type label '{label}' is invalid. Type labels must be valid Unicode identifiers with restrictions on the leading character.",
+    InvalidRuleNegationCycle { rule_label: Label, negated: String, dependency_label: Label, cycle: String } =
+        39: "Rule '{rule_label}' negates '{negated}', which is inferred by rule '{dependency_label}' in the same recursive cycle ({cycle}). Rules that depend on each other recursively cannot negate each other's conclusions, so the rules cannot be stratified.",
+    TypeNotDefined { label: Label } =
+        40: "The type '{label}' has not been defined.",
+    InvalidInsertAbstractType { label: Label } =
+        41: "Instances of the abstract type '{label}' cannot be inserted.",
+    InvalidInsertUnboundThing { statement: ThingStatement } =
+        42: "The insert statement '{statement}' refers to a thing that is neither bound in the 'match' clause nor given a type with 'isa'.",
+    InvalidInsertAttributeValue { statement: ThingStatement } =
+        43: "The insert statement '{statement}' must assign exactly one value to the attribute.",
+    InvalidInsertRoleType { statement: ThingStatement } =
+        44: "The insert statement '{statement}' must specify the role type of every role player.",
+    InvalidAttributeValueType { label: Label, value_type: ValueType, value: Constant } =
+        45: "The value '{value}' cannot be assigned to the attribute type '{label}' of value type '{value_type}'.",
+    InvalidOwnership { owner: Label, attribute: Label } =
+        46: "Instances of type '{owner}' are not allowed to own attributes of type '{attribute}'.",
+    InvalidRelationRole { relation: Label, role: Label } =
+        47: "The relation type '{relation}' does not relate the role '{role}'.",
+    InvalidRolePlayer { player: Label, role: Label } =
+        48: "Instances of type '{player}' are not allowed to play the role '{role}'.",
+    TypeVariableNotBound { variable: ConceptVariable } =
+        49: "The type variable '{variable}' is not bound to a type.",
+    InvalidAggregateValue { method: token::Aggregate, variable: Variable } =
+        50: "The aggregate '{method}' of '{variable}' can only be computed over numeric values.",
+    PatternNotEvaluable { pattern: Pattern } =
+        51: "The pattern '{pattern}' cannot be evaluated, because the variables it depends on are never bound.",
+    AggregateOverflow { method: token::Aggregate, variable: Variable } =
+        52: "The aggregate '{method}' of '{variable}' overflowed.",
+    ExpressionVariableNotBound { variable: Variable } =
+        53: "The expression variable '{variable}' is not bound to a value.",
+    FetchVarNotBound { variable: Variable } =
+        54: "The fetch variable '{variable}' is not bound in the match clause.",
+    InvalidFetchAttributeOwner { variable: Variable } =
+        55: "The fetch variable '{variable}' must be bound to a thing for its attributes to be fetched.",
+    ExpressionDivisionByZero { expression: Expression } =
+        56: "The expression '{expression}' divides by zero.",
+    InvalidExpressionOperandType { expression: Expression, operand: Expression } =
+        57: "The expression '{expression}' can only be applied to numeric operands, but the operand '{operand}' is not numeric.",
+    InvalidFunctionArgumentType { function: token::Function, expected: String, value_type: token::ValueType } =
+        58: "The function '{function}' expects an argument of type {expected}, but was given an argument of type '{value_type}'.",
+    InvalidFunctionArgument { function: token::Function, expected: String, value: Constant } =
+        59: "The function '{function}' expects an argument of type {expected}, got '{value}' instead.",
+    InvalidDuration { duration: String } =
+        60: "The duration '{duration}' is not a valid ISO-8601 duration, such as 'P1Y2M3DT4H5M6S'.",
+    ExpressionDateTimeOutOfRange { expression: Expression } =
+        61: "The result of the expression '{expression}' is not a valid datetime.",
+    InvalidDecimal { decimal: String } =
+        62: "The decimal '{decimal}' is not valid: its integer part must fit in a long, and it can have at most 19 fractional digits.",
+    InvalidConstraintList { list: Constant } =
+        63: "The list '{list}' must contain values of a single value type, which cannot itself be a list.",
+    InvalidListOwnership { owner: Label, attribute: Label } =
+        64: "Instances of type '{owner}' can only own a list of '{attribute}' values if they are declared to own '{attribute}[]'.",
+    InvalidStructWhenMissingFields { struct_name: String } =
+        65: "Struct '{struct_name}' has not been provided with any fields.",
+    InvalidUndefineQueryStruct { struct_name: String } =
+        66: "Invalid undefine query: the fields of struct '{struct_name}' cannot be undefined. The struct must be undefined entirely by referring to its name.",
+    RepeatingStructField { field: String } =
+        67: "The struct field '{field}' is declared more than once.",
+    StructNotDefined { struct_name: String } =
+        68: "The struct '{struct_name}' has not been defined.",
+    StructFieldNotDefined { struct_name: String, field: String } =
+        69: "The field '{field}' is not defined in the struct '{struct_name}'.",
+    MissingStructField { struct_name: String, field: String } =
+        70: "The struct value does not provide the field '{field}', which is required by the struct '{struct_name}'.",
+    InvalidStructFieldAccess { label: Label, field: String } =
+        71: "The field '{field}' cannot be accessed on '{label}', as its value type is not a struct.",
+    InvalidCardinality { cardinality: Cardinality } =
+        72: "The cardinality '{cardinality}' is not valid: its maximum must be positive and cannot be smaller than its minimum.",
+    InvalidConstraintAnnotation { annotation: Annotation, constraint: token::Constraint } =
+        73: "The annotation '{annotation}' cannot be applied to a '{constraint}' constraint.",
+    InvalidAnnotationValueType { annotation: Annotation, value_type: ValueType } =
+        74: "The annotation '{annotation}' cannot restrict values of type '{value_type}'.",
+    InvalidValueRange { range: Range } =
+        75: "The range '{range}' is not valid: it must have a bound, its bounds must have the same value type, and its minimum cannot exceed its maximum.",
+    MissingAnnotationValues =
+        76: "The '@values' annotation must be given at least one value.",
+    RepeatingAnnotationValue { value: Constant } =
+        77: "The value '{value}' is repeated in a '@values' annotation.",
+    MissingPipelineStages =
+        78: "The pipeline has not been provided with any stages.",
+    PipelineStageAfterFetch { stage: String } =
+        79: "The pipeline stage '{stage}' cannot follow a fetch stage, which must be the last stage of a pipeline.",
+    PutClauseNotBound { put_statements: String, bounds: String } =
+        80: "None of the variables in 'put' ('{put_statements}') is within scope of 'match' ('{bounds}')",
+    RedundantNestedOptional =
+        81: "Invalid query containing redundant nested optional patterns.",
+    InvalidFunctionWhenMissingBody { function_label: Label } =
+        82: "Function '{function_label}' has not been provided with a signature, a 'match' body and a 'return'.",
+    InvalidUndefineQueryFunction { function_label: Label } =
+        83: "Invalid undefine query: the body of function '{function_label}' cannot be undefined. The function must be undefined entirely by referring to its label.",
+    RepeatingFunctionArgument { function_label: Label, variable: Variable } =
+        84: "Function '{function_label}' declares the argument '{variable}' more than once.",
+    FunctionArgumentNotUsed { function_label: Label, variable: Variable } =
+        85: "Function '{function_label}' argument '{variable}' is not used in its 'match' body.",
+    FunctionReturnNotBound { function_label: Label, variable: Variable } =
+        86: "Function '{function_label}' returns '{variable}', which is not bound by its 'match' body.",
+    InvalidFunctionVariableType { function_label: Label, variable: Variable, type_: FunctionType } =
+        87: "Function '{function_label}' variable '{variable}' cannot hold '{type_}': types are held by concept variables and value types by value variables.",
+    FunctionCallNotEvaluable { call: FunctionCall } =
+        88: "The function call '{call}' can only be evaluated against a schema that defines the function.",
+    FunctionNotDefined { function_label: Label } =
+        89: "The function '{function_label}' has not been defined.",
+    InvalidFunctionCallArity { function_label: Label, expected: usize, arity: usize } =
+        90: "The function '{function_label}' takes {expected} argument(s), but was called with {arity}.",
+    RecursiveFunction { function_label: Label } =
+        91: "Function '{function_label}' calls itself, directly or through other functions, which is not supported.",
+    MissingGroupVariable =
+        92: "The get-group query must group by at least one variable.",
+    MissingAggregation =
+        93: "The get-aggregate query must compute at least one aggregate.",
+    MissingAggregateVariable { method: token::Aggregate } =
+        94: "Aggregate '{method}' requires a variable, as only 'count' may omit it.",
+    AggregateRequiresSorting { method: token::Aggregate } =
+        95: "Aggregate '{method}' depends on the order of the answers, so the get query must be sorted.",
+    InvalidAggregatePercentile { method: token::Aggregate } =
+        96: "Aggregate '{method}' must be given a percentile if and only if it is 'percentile'.",
+    InvalidPercentile { percentile: i64 } =
+        97: "The percentile '{percentile}' must be between 0 and 100.",
+    InvalidReduceMethod { method: token::Aggregate } =
+        98: "Aggregate '{method}' cannot be reduced, as it depends on the order of the answers or takes a parameter.",
+    ReduceVarNotBound { variable: Variable } =
+        99: "The reduce variable '{variable}' is not bound by the other patterns of the match clause.",
+    ValueVarAssignedMoreThanOnce { variable: Variable } =
+        100: "The value variable '{variable}' is assigned by more than one reduce or assignment in the match clause.",
+    SelectVarRepeating { variable: Variable } =
+        101: "The variable '{variable}' occurred more than once in the select clause.",
+    SelectVarNotBound { variable: Variable } =
+        102: "The select variable '{variable}' is not bound in the match clause.",
+    MissingSelectVariable =
+        103: "The select clause must select at least one variable.",
+    CursorRequiresSorting =
+        104: "A cursor requires the query to be sorted by the variables it continues from.",
+    CursorVarsNotSorted { cursor: String, sorting: String } =
+        105: "The cursor '{cursor}' must list exactly the variables of '{sorting}', in the same order.",
+    CursorValuesMismatch { cursor: String } =
+        106: "The cursor '{cursor}' must give exactly one value for each of its variables.",
+    InvalidCursorValue { value: Constant } =
+        107: "The cursor value '{value}' cannot be ordered, so it may not be a list or a struct.",
+    CursorTypeMismatch { variable: Variable, value: Constant } =
+        108: "The cursor value '{value}' cannot be compared with the answers of the sort variable '{variable}'.",
+    CursorValueMissing { variable: Variable } =
+        109: "The answer has no value of the sort variable '{variable}' to continue the cursor from.",
+    ExpressionOverflow { expression: Expression } =
+        110: "The result of the expression '{expression}' does not fit in a long value.",
+    InvalidFunctionArity { function: token::Function, arity: usize } =
+        111: "The function '{function}' cannot be applied to {arity} argument(s).",
 }
@@ -5,6 +5,8 @@
  */
 
 pub mod date_time;
+pub mod decimal;
+pub mod duration;
 pub mod error;
 pub(crate) mod identifier;
 pub mod string;
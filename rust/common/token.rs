@@ -57,13 +57,20 @@ string_enum! { Clause
     Undefine = "undefine",
     Insert = "insert",
     Delete = "delete",
+    Put = "put",
     Match = "match",
     Group = "group",
     Fetch = "fetch",
     Get = "get",
+    Select = "select",
 }
 
 string_enum! { Modifier
+    Distinct = "distinct",
+    After = "after",
+    Nulls = "nulls",
+    Collate = "collate",
+    NoCase = "nocase",
     Sort = "sort",
     Offset = "offset",
     Limit = "limit",
@@ -73,6 +80,7 @@ string_enum! { LogicOperator
     And = "and",
     Or = "or",
     Not = "not",
+    Try = "try",
 }
 
 string_enum! { Predicate
@@ -103,8 +111,13 @@ impl Predicate {
 
 string_enum! { Schema
     Rule = "rule",
+    Fun = "fun",
+    Return = "return",
+    Arrow = "->",
     When = "when",
     Then = "then",
+    Struct = "struct",
+    Optional = "?",
 }
 
 string_enum! { Constraint
@@ -113,40 +126,87 @@ string_enum! { Constraint
     Assign = "=",
     Has = "has",
     IID = "iid",
+    In = "in",
     Is = "is",
     Isa = "isa",
     IsaX = "isa!",
     Owns = "owns",
     Plays = "plays",
+    Reduce = "reduce",
     Regex = "regex",
     Relates = "relates",
     Sub = "sub",
     SubX = "sub!",
     Type = "type",
     ValueType = "value",
+    Within = "within",
 }
 
 string_enum! { Annotation
+    Cardinality = "card",
     Key = "key",
+    Range = "range",
+    Regex = "regex",
     Unique = "unique",
+    Values = "values",
 }
 
 string_enum! { Aggregate
     Count = "count",
+    CountDistinct = "count distinct",
+    First = "first",
+    Last = "last",
+    List = "list",
     Max = "max",
     Mean = "mean",
     Median = "median",
     Min = "min",
+    Percentile = "percentile",
     Std = "std",
     Sum = "sum",
 }
 
 string_enum! { ValueType
     Boolean = "boolean",
+    Date = "date",
     DateTime = "datetime",
+    DateTimeTZ = "datetime-tz",
+    Decimal = "decimal",
     Double = "double",
+    Duration = "duration",
     Long = "long",
     String = "string",
+    BooleanList = "boolean[]",
+    DateList = "date[]",
+    DateTimeList = "datetime[]",
+    DateTimeTZList = "datetime-tz[]",
+    DecimalList = "decimal[]",
+    DoubleList = "double[]",
+    DurationList = "duration[]",
+    LongList = "long[]",
+    StringList = "string[]",
+}
+
+impl ValueType {
+    const LISTS: [(ValueType, ValueType); 9] = [
+        (ValueType::Boolean, ValueType::BooleanList),
+        (ValueType::Date, ValueType::DateList),
+        (ValueType::DateTime, ValueType::DateTimeList),
+        (ValueType::DateTimeTZ, ValueType::DateTimeTZList),
+        (ValueType::Decimal, ValueType::DecimalList),
+        (ValueType::Double, ValueType::DoubleList),
+        (ValueType::Duration, ValueType::DurationList),
+        (ValueType::Long, ValueType::LongList),
+        (ValueType::String, ValueType::StringList),
+    ];
+
+    pub fn element_type(&self) -> Option<ValueType> {
+        Self::LISTS.iter().find(|(_, list)| list == self).map(|(element, _)| *element)
+    }
+
+    pub fn list_type(&self) -> Option<ValueType> {
+        Self::LISTS.iter().find(|(element, _)| element == self).map(|(_, list)| *list)
+    }
 }
 
 string_enum! { Order
@@ -154,6 +214,11 @@ string_enum! { Order
     Desc = "desc",
 }
 
+string_enum! { NullsOrder
+    First = "first",
+    Last = "last",
+}
+
 string_enum! { ArithmeticOperator
     Add = "+",
     Subtract = "-",
@@ -170,6 +235,16 @@ string_enum! { Function
     Max = "max",
     Min = "min",
     Round = "round",
+    Length = "length",
+    Lowercase = "lowercase",
+    Uppercase = "uppercase",
+    Substring = "substring",
+    Concat = "concat",
+    Year = "year",
+    Month = "month",
+    Day = "day",
+    AddDuration = "add_duration",
+    SubtractDuration = "subtract_duration",
 }
 
 string_enum! { Projection
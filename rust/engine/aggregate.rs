@@ -0,0 +1,147 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::cmp::Ordering;
+
+use crate::{
+    common::{decimal::Decimal, error::TypeQLError, token, Result},
+    engine::{
+        answer::{compare_constants, Concept, ConceptMap},
+        matcher::is_same,
+    },
+    pattern::Constant,
+    query::Aggregation,
+    variable::Variable,
+};
+
+pub(crate) fn aggregations(answers: &[ConceptMap], aggregations: &[Aggregation]) -> Result<Vec<Option<Constant>>> {
+    aggregations.iter().map(|aggregation| aggregate(answers, aggregation)).collect()
+}
+
+pub(crate) fn aggregate(answers: &[ConceptMap], aggregation: &Aggregation) -> Result<Option<Constant>> {
+    let method = aggregation.method;
+    let Some(variable) = aggregation.var.as_ref() else { return Ok(Some(Constant::Long(answers.len() as i64))) };
+    if method == token::Aggregate::CountDistinct {
+        let mut distinct: Vec<&Concept> = Vec::new();
+        for concept in answers.iter().filter_map(|answer| answer.get_variable(variable)) {
+            if !distinct.iter().any(|other| is_same(other, concept)) {
+                distinct.push(concept);
+            }
+        }
+        return Ok(Some(Constant::Long(distinct.len() as i64)));
+    }
+    let values: Vec<&Constant> =
+        answers.iter().filter_map(|answer| answer.get_variable(variable).and_then(Concept::value)).collect();
+    match method {
+        token::Aggregate::List => return Ok(Some(Constant::List(values.into_iter().cloned().collect()))),
+        token::Aggregate::First => return Ok(values.first().copied().cloned()),
+        token::Aggregate::Last => return Ok(values.last().copied().cloned()),
+        _ => (),
+    }
+    if values.is_empty() {
+        return Ok(None);
+    }
+    if method == token::Aggregate::Max || method == token::Aggregate::Min {
+        let extreme = values.into_iter().reduce(|lhs, rhs| match (compare_constants(lhs, rhs), method) {
+            (Some(Ordering::Less), token::Aggregate::Max) | (Some(Ordering::Greater), token::Aggregate::Min) => rhs,
+            _ => lhs,
+        });
+        return Ok(extreme.cloned());
+    }
+
+    let numbers = values
+        .iter()
+        .map(|value| match value {
+            Constant::Long(long) => Ok(*long as f64),
+            Constant::Double(double) => Ok(*double),
+            Constant::Decimal(decimal) => Ok(decimal.as_double()),
+            _ => Err(TypeQLError::InvalidAggregateValue { method, variable: variable.clone() }.into()),
+        })
+        .collect::<Result<Vec<f64>>>()?;
+    let is_long = values.iter().all(|value| matches!(value, Constant::Long(_)));
+    let decimals: Option<Vec<Decimal>> = values
+        .iter()
+        .map(|value| match value {
+            Constant::Long(long) => Some(Decimal::from(*long)),
+            Constant::Decimal(decimal) => Some(*decimal),
+            _ => None,
+        })
+        .collect();
+    if let (false, Some(decimals)) = (is_long, decimals) {
+        if method != token::Aggregate::Std {
+            let aggregated = decimal_aggregate(method, decimals, aggregation.percentile)
+                .ok_or_else(|| TypeQLError::AggregateOverflow { method, variable: variable.clone() })?;
+            return Ok(Some(Constant::Decimal(aggregated)));
+        }
+    }
+    let count = numbers.len() as f64;
+    let mean = numbers.iter().sum::<f64>() / count;
+    Ok(match method {
+        token::Aggregate::Sum if is_long => Some(Constant::Long(
+            values
+                .iter()
+                .try_fold(
+                    0i64,
+                    |sum, value| if let Constant::Long(long) = value { sum.checked_add(*long) } else { Some(sum) },
+                )
+                .ok_or_else(|| TypeQLError::AggregateOverflow { method, variable: variable.clone() })?,
+        )),
+        token::Aggregate::Sum => Some(Constant::Double(numbers.iter().sum())),
+        token::Aggregate::Mean => Some(Constant::Double(mean)),
+        token::Aggregate::Median => Some(Constant::Double(percentile(numbers, 50))),
+        token::Aggregate::Percentile => Some(Constant::Double(percentile(numbers, aggregation.percentile.unwrap()))),
+        token::Aggregate::Std if numbers.len() > 1 => {
+            let variance = numbers.iter().map(|number| (number - mean).powi(2)).sum::<f64>() / (count - 1.0);
+            Some(Constant::Double(variance.sqrt()))
+        }
+        token::Aggregate::Std => None,
+        token::Aggregate::Count
+        | token::Aggregate::CountDistinct
+        | token::Aggregate::First
+        | token::Aggregate::Last
+        | token::Aggregate::List
+        | token::Aggregate::Max
+        | token::Aggregate::Min => unreachable!(),
+    })
+}
+
+fn decimal_aggregate(method: token::Aggregate, mut decimals: Vec<Decimal>, percentile: Option<i64>) -> Option<Decimal> {
+    let sum = decimals.iter().try_fold(Decimal::from(0), |sum, decimal| sum.checked_add(*decimal))?;
+    let percentile = match method {
+        token::Aggregate::Sum => return Some(sum),
+        token::Aggregate::Mean => return sum.checked_div(decimals.len() as i64),
+        token::Aggregate::Median => 50,
+        _ => percentile.unwrap(),
+    };
+    decimals.sort();
+    let rank = percentile * (decimals.len() as i64 - 1);
+    let (lower, upper) = (decimals[(rank / 100) as usize], decimals[((rank + 99) / 100) as usize]);
+    lower.checked_add(upper.checked_sub(lower)?.checked_mul(Decimal::from(rank % 100).checked_div(100)?)?)
+}
+
+// linear interpolation between the closest ranks, so the 50th percentile is the median
+fn percentile(mut numbers: Vec<f64>, percentile: i64) -> f64 {
+    numbers.sort_by(|lhs, rhs| lhs.partial_cmp(rhs).unwrap_or(Ordering::Equal));
+    let rank = percentile as f64 / 100.0 * (numbers.len() - 1) as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    numbers[lower] + (numbers[upper] - numbers[lower]) * (rank - lower as f64)
+}
+
+pub(crate) fn group(answers: Vec<ConceptMap>, variables: &[Variable]) -> Vec<(Vec<Concept>, Vec<ConceptMap>)> {
+    let mut groups: Vec<(Vec<Concept>, Vec<ConceptMap>)> = Vec::new();
+    for answer in answers {
+        let Some(owners) = variables.iter().map(|variable| answer.get_variable(variable).cloned()).collect() else {
+            continue;
+        };
+        let is_same_owners =
+            |group_owners: &Vec<Concept>| group_owners.iter().zip(&owners).all(|(lhs, rhs)| is_same(lhs, rhs));
+        match groups.iter_mut().find(|(group_owners, _)| is_same_owners(group_owners)) {
+            Some((_, group)) => group.push(answer),
+            None => groups.push((owners, vec![answer])),
+        }
+    }
+    groups
+}
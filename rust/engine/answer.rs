@@ -0,0 +1,204 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{cmp::Ordering, collections::HashMap, fmt};
+
+use chrono::NaiveDateTime;
+
+use crate::{
+    common::decimal::Decimal,
+    pattern::{Constant, Label},
+    variable::Variable,
+};
+
+#[derive(Debug, Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ThingId(pub(crate) usize);
+
+impl fmt::Display for ThingId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:016x}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thing {
+    pub iid: ThingId,
+    pub type_: Label,
+    pub value: Option<Constant>,
+}
+
+impl fmt::Display for Thing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}:{}", self.type_, value),
+            None => write!(f, "{}:{}", self.type_, self.iid),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Concept {
+    Thing(Thing),
+    Type(Label),
+    Value(Constant),
+}
+
+impl Concept {
+    pub fn as_thing(&self) -> Option<&Thing> {
+        match self {
+            Concept::Thing(thing) => Some(thing),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> Option<&Constant> {
+        match self {
+            Concept::Thing(thing) => thing.value.as_ref(),
+            Concept::Value(value) => Some(value),
+            Concept::Type(_) => None,
+        }
+    }
+
+    pub(crate) fn compare(&self, other: &Concept) -> Ordering {
+        match (self.value(), other.value()) {
+            (Some(lhs), Some(rhs)) => compare_constants(lhs, rhs).unwrap_or_else(|| rank(lhs).cmp(&rank(rhs))),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => match (self, other) {
+                (Concept::Thing(lhs), Concept::Thing(rhs)) => lhs.iid.cmp(&rhs.iid),
+                (Concept::Type(lhs), Concept::Type(rhs)) => lhs.to_string().cmp(&rhs.to_string()),
+                (Concept::Thing(_), _) => Ordering::Less,
+                _ => Ordering::Greater,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Concept {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Concept::Thing(thing) => write!(f, "{thing}"),
+            Concept::Type(label) => write!(f, "{label}"),
+            Concept::Value(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+pub(crate) fn compare_constants(lhs: &Constant, rhs: &Constant) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Constant::Long(lhs), Constant::Long(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::Long(lhs), Constant::Double(rhs)) => (*lhs as f64).partial_cmp(rhs),
+        (Constant::Double(lhs), Constant::Long(rhs)) => lhs.partial_cmp(&(*rhs as f64)),
+        (Constant::Double(lhs), Constant::Double(rhs)) => lhs.partial_cmp(rhs),
+        (Constant::Boolean(lhs), Constant::Boolean(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::String(lhs), Constant::String(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::Decimal(lhs), Constant::Decimal(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::Decimal(lhs), Constant::Long(rhs)) => Some(lhs.cmp(&Decimal::from(*rhs))),
+        (Constant::Long(lhs), Constant::Decimal(rhs)) => Some(Decimal::from(*lhs).cmp(rhs)),
+        (Constant::Decimal(lhs), Constant::Double(rhs)) => lhs.as_double().partial_cmp(rhs),
+        (Constant::Double(lhs), Constant::Decimal(rhs)) => lhs.partial_cmp(&rhs.as_double()),
+        (Constant::Date(lhs), Constant::Date(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::DateTime(lhs), Constant::DateTime(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::Date(lhs), Constant::DateTime(rhs)) => Some(NaiveDateTime::from(*lhs).cmp(rhs)),
+        (Constant::DateTime(lhs), Constant::Date(rhs)) => Some(lhs.cmp(&NaiveDateTime::from(*rhs))),
+        (Constant::DateTimeTZ(lhs), Constant::DateTimeTZ(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::Duration(lhs), Constant::Duration(rhs)) => (lhs == rhs).then_some(Ordering::Equal),
+        (Constant::List(lhs), Constant::List(rhs)) => {
+            for (lhs, rhs) in lhs.iter().zip(rhs) {
+                match compare_constants(lhs, rhs)? {
+                    Ordering::Equal => (),
+                    ordering => return Some(ordering),
+                }
+            }
+            Some(lhs.len().cmp(&rhs.len()))
+        }
+        (Constant::Struct(lhs), Constant::Struct(rhs)) => {
+            let is_equal = lhs.len() == rhs.len()
+                && lhs.iter().all(|(name, lhs)| {
+                    rhs.iter().any(|(other, rhs)| name == other && compare_constants(lhs, rhs) == Some(Ordering::Equal))
+                });
+            is_equal.then_some(Ordering::Equal)
+        }
+        _ => None,
+    }
+}
+
+fn rank(constant: &Constant) -> usize {
+    match constant {
+        Constant::Long(_) | Constant::Double(_) | Constant::Decimal(_) => 0,
+        Constant::Boolean(_) => 1,
+        Constant::String(_) => 2,
+        Constant::Date(_) | Constant::DateTime(_) => 3,
+        Constant::DateTimeTZ(_) => 4,
+        Constant::Duration(_) => 5,
+        Constant::List(_) => 6,
+        Constant::Struct(_) => 7,
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConceptMap {
+    map: HashMap<Variable, Concept>,
+}
+
+impl ConceptMap {
+    pub fn get(&self, name: &str) -> Option<&Concept> {
+        self.map.iter().find(|(variable, _)| variable_name(variable) == name).map(|(_, concept)| concept)
+    }
+
+    pub fn get_variable(&self, variable: &Variable) -> Option<&Concept> {
+        self.map.get(variable)
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = &Variable> {
+        self.map.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub(crate) fn insert(&mut self, variable: Variable, concept: Concept) {
+        self.map.insert(variable, concept);
+    }
+
+    pub(crate) fn retain(&mut self, variables: &[Variable]) {
+        self.map.retain(|variable, _| variables.contains(variable))
+    }
+
+    pub(crate) fn concepts(&self) -> impl Iterator<Item = (&Variable, &Concept)> {
+        self.map.iter()
+    }
+}
+
+fn variable_name(variable: &Variable) -> &str {
+    match variable {
+        Variable::Concept(var) => var.name(),
+        Variable::Value(var) => var.name(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConceptMapGroup {
+    pub owners: Vec<Concept>,
+    pub concept_maps: Vec<ConceptMap>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueGroup {
+    pub owners: Vec<Concept>,
+    pub values: Vec<Option<Constant>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineAnswers {
+    ConceptMaps(Vec<ConceptMap>),
+    Documents(Vec<serde_json::Value>),
+}
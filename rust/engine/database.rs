@@ -0,0 +1,227 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::BTreeMap;
+
+use crate::{
+    common::{validatable::Validatable, Result},
+    engine::{
+        aggregate::{aggregations, group},
+        answer::{Concept, ConceptMap, ConceptMapGroup, PipelineAnswers, Thing, ThingId, ValueGroup},
+        modifier::{apply_filter, apply_modifiers, apply_selection, filter_variables},
+        reasoner::{Explanation, Inferred},
+        schema::Schema,
+    },
+    pattern::{Constant, Label},
+    query::{
+        modifier::Modifiers, Stage, TypeQLDefine, TypeQLDelete, TypeQLFetch, TypeQLGet, TypeQLGetAggregate,
+        TypeQLGetGroup, TypeQLGetGroupAggregate, TypeQLInsert, TypeQLPipeline, TypeQLPut, TypeQLUpdate,
+    },
+};
+
+#[derive(Debug, Clone)]
+pub(crate) struct ThingData {
+    pub(crate) type_: Label,
+    pub(crate) value: Option<Constant>,
+    pub(crate) has: Vec<ThingId>,
+    pub(crate) role_players: Vec<(Label, ThingId)>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Database {
+    pub(crate) schema: Schema,
+    pub(crate) things: BTreeMap<ThingId, ThingData>,
+    next_id: usize,
+    explanations: Vec<Explanation>,
+}
+
+impl Database {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn define(&mut self, query: &TypeQLDefine) -> Result {
+        query.validate()?;
+        self.schema.define(query.statements(), query.rules(), query.functions(), query.structs())
+    }
+
+    pub fn insert(&mut self, query: &TypeQLInsert) -> Result<Vec<ConceptMap>> {
+        query.validate()?;
+        let answers = match &query.match_clause {
+            Some(match_clause) => apply_modifiers(self.match_(&match_clause.conjunction)?, &query.modifiers)?,
+            None => vec![ConceptMap::default()],
+        };
+        self.atomically(|database| {
+            answers.into_iter().map(|answer| database.insert_statements(&query.statements, answer)).collect()
+        })
+    }
+
+    pub fn put(&mut self, query: &TypeQLPut) -> Result<Vec<ConceptMap>> {
+        query.validate()?;
+        let answers = match &query.match_clause {
+            Some(match_clause) => self.match_(&match_clause.conjunction)?,
+            None => vec![ConceptMap::default()],
+        };
+        self.atomically(|database| {
+            answers.into_iter().map(|answer| database.put_statements(&query.statements, answer)).collect()
+        })
+    }
+
+    pub fn delete(&mut self, query: &TypeQLDelete) -> Result {
+        query.validate()?;
+        let answers = apply_modifiers(self.match_(&query.match_clause.conjunction)?, &query.modifiers)?;
+        self.atomically(|database| {
+            answers.iter().try_for_each(|answer| database.delete_statements(&query.statements, answer))
+        })
+    }
+
+    pub fn update(&mut self, query: &TypeQLUpdate) -> Result<Vec<ConceptMap>> {
+        query.validate()?;
+        let delete = &query.query_delete;
+        let answers = apply_modifiers(self.match_(&delete.match_clause.conjunction)?, &query.modifiers)?;
+        self.atomically(|database| {
+            answers.iter().try_for_each(|answer| database.delete_statements(&delete.statements, answer))?;
+            answers
+                .into_iter()
+                .map(|answer| database.insert_statements(&query.insert_statements, database.without_deleted(answer)))
+                .collect()
+        })
+    }
+
+    pub fn pipeline(&mut self, query: &TypeQLPipeline) -> Result<PipelineAnswers> {
+        query.validate()?;
+        self.atomically(|database| {
+            let mut answers = vec![ConceptMap::default()];
+            for stage in &query.stages {
+                answers = match stage {
+                    Stage::Match(match_clause) => answers
+                        .into_iter()
+                        .map(|answer| {
+                            database.match_patterns(match_clause.conjunction.patterns.iter().collect(), answer)
+                        })
+                        .collect::<Result<Vec<_>>>()?
+                        .concat(),
+                    Stage::Insert(statements) => answers
+                        .into_iter()
+                        .map(|answer| database.insert_statements(statements, answer))
+                        .collect::<Result<_>>()?,
+                    Stage::Put(statements) => answers
+                        .into_iter()
+                        .map(|answer| database.put_statements(statements, answer))
+                        .collect::<Result<_>>()?,
+                    Stage::Delete(statements) => {
+                        answers.iter().try_for_each(|answer| database.delete_statements(statements, answer))?;
+                        answers.into_iter().map(|answer| database.without_deleted(answer)).collect()
+                    }
+                    Stage::Get(filter) if filter.vars.is_empty() => answers,
+                    Stage::Get(filter) => apply_filter(answers, &filter.vars),
+                    Stage::Select(selection) => apply_selection(answers, selection),
+                    Stage::Distinct(_) => apply_modifiers(answers, &Modifiers::default().distinct())?,
+                    Stage::Sort(sorting) => apply_modifiers(answers, &Modifiers::default().sort(sorting.clone()))?,
+                    Stage::Offset(offset) => apply_modifiers(answers, &Modifiers::default().offset(offset.offset))?,
+                    Stage::Limit(limit) => apply_modifiers(answers, &Modifiers::default().limit(limit.limit))?,
+                    Stage::Fetch(projections) => {
+                        let documents = answers.into_iter().map(|answer| database.fetch_answer(projections, answer));
+                        return Ok(PipelineAnswers::Documents(documents.collect::<Result<_>>()?));
+                    }
+                };
+            }
+            Ok(PipelineAnswers::ConceptMaps(answers))
+        })
+    }
+
+    pub fn reason(&mut self) -> Result<Vec<Explanation>> {
+        let explanations = self.atomically(Database::materialise)?;
+        self.explanations.extend(explanations.iter().cloned());
+        Ok(explanations)
+    }
+
+    pub fn explain(&self, inferred: &Inferred) -> Option<&Explanation> {
+        self.explanations.iter().find(|explanation| &explanation.inferred == inferred)
+    }
+
+    pub fn get(&self, query: &TypeQLGet) -> Result<Vec<ConceptMap>> {
+        query.validate()?;
+        self.get_answers(query, ConceptMap::default())
+    }
+
+    pub fn get_aggregate(&self, query: &TypeQLGetAggregate) -> Result<Vec<Option<Constant>>> {
+        query.validate()?;
+        aggregations(&self.get(&query.query)?, &query.aggregations)
+    }
+
+    pub fn get_group(&self, query: &TypeQLGetGroup) -> Result<Vec<ConceptMapGroup>> {
+        query.validate()?;
+        Ok(group(self.get(&query.query)?, &query.group_vars)
+            .into_iter()
+            .map(|(owners, concept_maps)| ConceptMapGroup { owners, concept_maps })
+            .collect())
+    }
+
+    pub fn get_group_aggregate(&self, query: &TypeQLGetGroupAggregate) -> Result<Vec<ValueGroup>> {
+        query.validate()?;
+        self.get_group(&query.query)?
+            .into_iter()
+            .map(|group| {
+                let values = aggregations(&group.concept_maps, &query.aggregations)?;
+                Ok(ValueGroup { owners: group.owners, values })
+            })
+            .collect()
+    }
+
+    pub fn fetch(&self, query: &TypeQLFetch) -> Result<Vec<serde_json::Value>> {
+        query.validate()?;
+        self.fetch_answers(query, ConceptMap::default())
+    }
+
+    pub(crate) fn get_answers(&self, query: &TypeQLGet, bound: ConceptMap) -> Result<Vec<ConceptMap>> {
+        let answers = self.match_patterns(query.match_clause.conjunction.patterns.iter().collect(), bound)?;
+        let answers = match &query.selection {
+            Some(selection) => apply_selection(answers, selection),
+            None => apply_filter(answers, &filter_variables(query)),
+        };
+        apply_modifiers(answers, &query.modifiers)
+    }
+
+    pub(crate) fn thing(&self, iid: ThingId) -> Thing {
+        let data = &self.things[&iid];
+        Thing { iid, type_: data.type_.clone(), value: data.value.clone() }
+    }
+
+    pub(crate) fn put_thing(&mut self, type_: Label, value: Option<Constant>) -> ThingId {
+        if value.is_some() {
+            let existing = self.things.iter().find(|(_, data)| data.type_ == type_ && data.value == value);
+            if let Some((iid, _)) = existing {
+                return *iid;
+            }
+        }
+        let iid = ThingId(self.next_id);
+        self.next_id += 1;
+        self.things.insert(iid, ThingData { type_, value, has: Vec::new(), role_players: Vec::new() });
+        iid
+    }
+
+    fn atomically<T>(&mut self, write: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let (things, next_id) = (self.things.clone(), self.next_id);
+        let result = write(self);
+        if result.is_err() {
+            self.things = things;
+            self.next_id = next_id;
+        }
+        result
+    }
+
+    fn without_deleted(&self, mut answer: ConceptMap) -> ConceptMap {
+        let deleted: Vec<_> = answer
+            .concepts()
+            .filter(|(_, concept)| matches!(concept, Concept::Thing(thing) if !self.things.contains_key(&thing.iid)))
+            .map(|(variable, _)| variable.clone())
+            .collect();
+        let retained: Vec<_> = answer.variables().filter(|variable| !deleted.contains(variable)).cloned().collect();
+        answer.retain(&retained);
+        answer
+    }
+}
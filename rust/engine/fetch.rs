@@ -0,0 +1,192 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use serde_json::{json, Map, Value as JSON};
+
+use crate::{
+    common::{date_time, error::TypeQLError, Result},
+    engine::{
+        aggregate::aggregations,
+        answer::{Concept, ConceptMap, Thing},
+        modifier::{apply_filter, apply_modifiers},
+        Database,
+    },
+    pattern::{Constant, Label},
+    query::{Projection, ProjectionAttribute, ProjectionKeyVar, ProjectionSubquery, TypeQLFetch},
+    variable::Variable,
+};
+
+impl Database {
+    pub(crate) fn fetch_answers(&self, query: &TypeQLFetch, bound: ConceptMap) -> Result<Vec<JSON>> {
+        let answers = self.match_patterns(query.match_clause.conjunction.patterns.iter().collect(), bound)?;
+        let answers = apply_modifiers(apply_filter(answers, &projected_variables(query)), &query.modifiers)?;
+        answers.into_iter().map(|answer| self.fetch_answer(&query.projections, answer)).collect()
+    }
+
+    pub(crate) fn fetch_answer(&self, projections: &[Projection], answer: ConceptMap) -> Result<JSON> {
+        let mut document = Map::new();
+        for projection in projections {
+            let (key, value) = match projection {
+                Projection::Variable(key) => {
+                    (key_of(key), projected(key, &answer).map_or(JSON::Null, |concept| self.concept_json(concept)))
+                }
+                Projection::Attribute(key, attributes) => (
+                    key_of(key),
+                    projected(key, &answer)
+                        .map_or(Ok(JSON::Null), |concept| self.attributes_json(key, concept, attributes))?,
+                ),
+                Projection::Field(key, fields) => (
+                    field_key_of(key, fields),
+                    projected(key, &answer).map_or(Ok(JSON::Null), |concept| self.field_json(concept, fields))?,
+                ),
+                Projection::Subquery(label, subquery) => {
+                    (label.label.clone(), self.subquery_json(subquery, answer.clone())?)
+                }
+            };
+            document.insert(key, value);
+        }
+        Ok(JSON::Object(document))
+    }
+
+    fn attributes_json(
+        &self,
+        key: &ProjectionKeyVar,
+        concept: &Concept,
+        attributes: &[ProjectionAttribute],
+    ) -> Result<JSON> {
+        let Concept::Thing(owner) = concept else {
+            Err(TypeQLError::InvalidFetchAttributeOwner { variable: key.variable.clone() })?
+        };
+        let mut document = Map::new();
+        document.insert("type".to_owned(), self.type_json(&owner.type_));
+        for attribute in attributes {
+            self.schema.get(&attribute.attribute)?;
+            let owned = self
+                .owned_attributes(owner.iid, Some(&attribute.attribute))
+                .into_iter()
+                .map(|iid| self.concept_json(&Concept::Thing(self.thing(iid))))
+                .collect();
+            let key = attribute.label.as_ref().map_or_else(|| attribute.attribute.to_string(), |key| key.label.clone());
+            document.insert(key, JSON::Array(owned));
+        }
+        Ok(JSON::Object(document))
+    }
+
+    fn field_json(&self, concept: &Concept, fields: &[String]) -> Result<JSON> {
+        let value = match concept {
+            Concept::Thing(thing) => {
+                self.schema.validate_field_path(&thing.type_, fields)?;
+                thing.value.as_ref()
+            }
+            Concept::Value(value) => Some(value),
+            Concept::Type(label) => {
+                Err(TypeQLError::InvalidStructFieldAccess { label: label.clone(), field: fields[0].clone() })?
+            }
+        };
+        let field = fields.iter().try_fold(value, |value, field| match value {
+            Some(Constant::Struct(values)) => {
+                Some(values.iter().find(|(name, _)| name == field).map(|(_, value)| value))
+            }
+            _ => None,
+        });
+        Ok(field.flatten().map_or(JSON::Null, value_json))
+    }
+
+    fn subquery_json(&self, subquery: &ProjectionSubquery, bound: ConceptMap) -> Result<JSON> {
+        match subquery {
+            ProjectionSubquery::Fetch(query) => Ok(JSON::Array(self.fetch_answers(query, bound)?)),
+            ProjectionSubquery::GetAggregate(query) => {
+                let answers = self.get_answers(&query.query, bound)?;
+                let values = aggregations(&answers, &query.aggregations)?;
+                let mut values = values.iter().map(|value| value.as_ref().map_or(JSON::Null, value_json));
+                Ok(match query.aggregations.len() {
+                    1 => values.next().unwrap(),
+                    _ => JSON::Array(values.collect()),
+                })
+            }
+        }
+    }
+
+    fn concept_json(&self, concept: &Concept) -> JSON {
+        match concept {
+            Concept::Thing(Thing { type_, value: Some(value), .. }) => {
+                let mut document = value_json(value);
+                document["type"] = self.type_json(type_);
+                document
+            }
+            Concept::Thing(thing) => json!({ "type": self.type_json(&thing.type_) }),
+            Concept::Type(label) => self.type_json(label),
+            Concept::Value(value) => value_json(value),
+        }
+    }
+
+    fn type_json(&self, label: &Label) -> JSON {
+        json!({ "label": label.to_string(), "root": self.schema.root(label).to_string() })
+    }
+}
+
+fn projected<'a>(key: &ProjectionKeyVar, answer: &'a ConceptMap) -> Option<&'a Concept> {
+    answer.get_variable(&key.variable)
+}
+
+fn projected_variables(query: &TypeQLFetch) -> Vec<Variable> {
+    let subquery_variables = query.projections.iter().flat_map(Projection::value_variables);
+    let sort_variables = query.modifiers.sorting.iter().flat_map(|sorting| sorting.vars.iter());
+    query
+        .projections
+        .iter()
+        .filter_map(Projection::key_variable)
+        .chain(subquery_variables)
+        .filter(|variable| variable.is_name())
+        .map(|variable| variable.to_owned())
+        .chain(sort_variables.map(|sort_var| sort_var.variable.clone()))
+        .collect()
+}
+
+fn key_of(key: &ProjectionKeyVar) -> String {
+    match &key.label {
+        Some(label) => label.label.clone(),
+        None => match &key.variable {
+            Variable::Concept(variable) => variable.name().to_owned(),
+            Variable::Value(variable) => variable.name().to_owned(),
+        },
+    }
+}
+
+fn field_key_of(key: &ProjectionKeyVar, fields: &[String]) -> String {
+    match &key.label {
+        Some(label) => label.label.clone(),
+        None => format!("{}.{}", key_of(key), fields.join(".")),
+    }
+}
+
+fn value_json(value: &Constant) -> JSON {
+    json!({ "value": raw_value_json(value), "value_type": value.value_type().map(|value_type| value_type.to_string()) })
+}
+
+fn raw_value_json(value: &Constant) -> JSON {
+    match value {
+        Constant::Long(long) => JSON::from(*long),
+        Constant::Double(double) => JSON::from(*double),
+        Constant::Boolean(boolean) => JSON::from(*boolean),
+        Constant::String(string) => JSON::from(string.as_str()),
+        Constant::Date(date) => JSON::from(date.format("%Y-%m-%d").to_string()),
+        Constant::DateTime(date_time) => {
+            let format = format!("%Y-%m-%dT%H:%M:%S{}", date_time::fraction_format(date_time));
+            JSON::from(date_time.format(&format).to_string())
+        }
+        Constant::DateTimeTZ(date_time) => {
+            let format = format!("%Y-%m-%dT%H:%M:%S{}%:z", date_time::fraction_format(&date_time.naive_local()));
+            JSON::from(date_time.format(&format).to_string())
+        }
+        Constant::Decimal(decimal) => JSON::from(decimal.to_string()),
+        Constant::Duration(duration) => JSON::from(duration.to_string()),
+        Constant::List(values) => JSON::Array(values.iter().map(raw_value_json).collect()),
+        Constant::Struct(fields) => {
+            JSON::Object(fields.iter().map(|(name, value)| (name.clone(), raw_value_json(value))).collect())
+        }
+    }
+}
@@ -0,0 +1,738 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
+
+use regex::Regex;
+
+use crate::{
+    common::{error::TypeQLError, token, Result},
+    engine::{
+        aggregate::{aggregate, group},
+        answer::{compare_constants, Concept, ConceptMap, ThingId},
+        Database,
+    },
+    pattern::{
+        evaluate, ConceptStatement, Conjunction, Constant, Expression, Function, FunctionCall, HasConstraint,
+        InConstraint, IsExplicit, Operation, Optional, Pattern, Predicate, ReduceConstraint, RelationConstraint,
+        RolePlayerConstraint, Statement, ThingStatement, TypeStatement, Value, ValueStatement, ValueType,
+    },
+    query::Aggregation,
+    variable::{variable::VariableRef, ConceptVariable, TypeReference, ValueVariable, Variable},
+    Label,
+};
+
+impl Database {
+    pub(crate) fn match_(&self, conjunction: &Conjunction) -> Result<Vec<ConceptMap>> {
+        self.match_patterns(conjunction.patterns.iter().collect(), ConceptMap::default())
+    }
+
+    pub(crate) fn match_patterns(&self, pending: Vec<&Pattern>, answer: ConceptMap) -> Result<Vec<ConceptMap>> {
+        let mut pending: Vec<&Pattern> = pending.into_iter().flat_map(flatten).collect();
+        if pending.iter().any(|pattern| reduce_of(pattern).is_some()) {
+            return self.match_reducing(pending, answer);
+        }
+        let Some(next) = next_pattern(&pending, &answer) else {
+            return match pending.first() {
+                None => Ok(vec![answer]),
+                Some(pattern) => Err(TypeQLError::PatternNotEvaluable { pattern: (*pattern).clone() })?,
+            };
+        };
+        let pattern = pending.remove(next);
+        if let Pattern::Optional(optional) = pattern {
+            return self.match_optional(optional, pending, answer);
+        }
+        let mut answers = Vec::new();
+        for partial in self.match_pattern(pattern, answer)? {
+            answers.extend(self.match_patterns(pending.clone(), partial)?);
+        }
+        Ok(answers)
+    }
+
+    fn match_reducing(&self, pending: Vec<&Pattern>, answer: ConceptMap) -> Result<Vec<ConceptMap>> {
+        let (reduces, mut before): (Vec<&Pattern>, Vec<&Pattern>) =
+            pending.into_iter().partition(|pattern| reduce_of(pattern).is_some());
+        let mut dependent: HashSet<VariableRef<'_>> =
+            reduces.iter().flat_map(|pattern| reduce_of(pattern)).map(|(statement, _)| statement.owner()).collect();
+        let mut after = Vec::new();
+        loop {
+            let (moved, kept): (Vec<&Pattern>, Vec<&Pattern>) = before
+                .into_iter()
+                .partition(|pattern| pattern.variables_recursive().any(|variable| dependent.contains(&variable)));
+            before = kept;
+            if moved.is_empty() {
+                break;
+            }
+            dependent.extend(moved.iter().filter_map(|pattern| match pattern {
+                Pattern::Statement(statement @ Statement::Value(_)) => Some(statement.owner()),
+                _ => None,
+            }));
+            after.extend(moved);
+        }
+
+        let mut answers = self.match_patterns(before, answer)?;
+        for (statement, reduce) in reduces.into_iter().flat_map(reduce_of) {
+            answers = reduced_answers(statement, reduce, answers)?;
+        }
+        let mut matched = Vec::new();
+        for answer in answers {
+            matched.extend(self.match_patterns(after.clone(), answer)?);
+        }
+        Ok(matched)
+    }
+
+    fn match_pattern(&self, pattern: &Pattern, answer: ConceptMap) -> Result<Vec<ConceptMap>> {
+        match pattern {
+            Pattern::Conjunction(conjunction) => self.match_patterns(conjunction.patterns.iter().collect(), answer),
+            Pattern::Disjunction(disjunction) => {
+                let mut answers = Vec::new();
+                for branch in &disjunction.patterns {
+                    answers.extend(self.match_patterns(vec![branch], answer.clone())?);
+                }
+                Ok(answers)
+            }
+            Pattern::Negation(negation) => {
+                if self.match_patterns(vec![negation.pattern.as_ref()], answer.clone())?.is_empty() {
+                    Ok(vec![answer])
+                } else {
+                    Ok(Vec::new())
+                }
+            }
+            Pattern::Optional(optional) => self.match_optional(optional, Vec::new(), answer),
+            Pattern::Statement(Statement::Thing(thing)) => self.match_thing(thing, answer),
+            Pattern::Statement(Statement::Type(type_)) => self.match_type(type_, answer),
+            Pattern::Statement(Statement::Value(value)) => self.match_value(value, answer),
+            Pattern::Statement(Statement::Concept(concept)) => match &concept.in_constraint {
+                Some(in_) => self.match_in(&concept.variable, in_, answer),
+                None => Ok(match_concept(concept, answer)),
+            },
+        }
+    }
+
+    fn match_optional(
+        &self,
+        optional: &Optional,
+        pending: Vec<&Pattern>,
+        answer: ConceptMap,
+    ) -> Result<Vec<ConceptMap>> {
+        let matched = self.match_patterns(vec![optional.pattern.as_ref()], answer.clone())?;
+        let mut answers = Vec::new();
+        if matched.is_empty() {
+            let unbound: Vec<Variable> = optional
+                .pattern
+                .variables_recursive()
+                .filter(|variable| variable.is_name())
+                .map(|variable| variable.to_owned())
+                .filter(|variable| answer.get_variable(variable).is_none())
+                .collect();
+            for answer in self.match_patterns(pending, answer)? {
+                if unbound.iter().all(|variable| answer.get_variable(variable).is_none()) {
+                    answers.push(answer);
+                }
+            }
+        } else {
+            for partial in matched {
+                answers.extend(self.match_patterns(pending.clone(), partial)?);
+            }
+        }
+        Ok(answers)
+    }
+
+    fn match_thing(&self, statement: &ThingStatement, answer: ConceptMap) -> Result<Vec<ConceptMap>> {
+        let candidates: Vec<ThingId> = match concept_of(&answer, &statement.variable) {
+            Some(Concept::Thing(thing)) => vec![thing.iid],
+            Some(_) => Vec::new(),
+            None => match &statement.iid {
+                Some(iid) => parse_iid(&iid.iid).filter(|iid| self.things.contains_key(iid)).into_iter().collect(),
+                None => self.things.keys().copied().collect(),
+            },
+        };
+        self.match_thing_among(statement, candidates, None, answer)
+    }
+
+    pub(crate) fn match_thing_among(
+        &self,
+        statement: &ThingStatement,
+        candidates: Vec<ThingId>,
+        only_has: Option<(usize, ThingId)>,
+        answer: ConceptMap,
+    ) -> Result<Vec<ConceptMap>> {
+        if let Some(TypeReference::Label(label)) = statement.isa.as_ref().map(|isa| &isa.type_reference) {
+            self.schema.get(label)?;
+        }
+        for has in &statement.has {
+            if let HasConstraint::HasPredicate(label, predicate) = has {
+                self.schema.validate_field_path(label, &predicate.fields)?;
+            }
+        }
+
+        let mut answers = Vec::new();
+        for iid in candidates {
+            let data = &self.things[&iid];
+            if statement.iid.as_ref().is_some_and(|constraint| parse_iid(&constraint.iid) != Some(iid)) {
+                continue;
+            }
+            if let Some(predicate) = &statement.predicate {
+                if matches!(self.schema.value_type(&data.type_), Some(ValueType::Struct(_))) {
+                    self.schema.validate_field_path(&data.type_, &predicate.fields)?;
+                }
+                match &data.value {
+                    Some(value) if self.satisfies(value, predicate, &answer) => (),
+                    _ => continue,
+                }
+            }
+            let Some(partial) = bind_concept(answer.clone(), &statement.variable, Concept::Thing(self.thing(iid)))
+            else {
+                continue;
+            };
+            let mut partials = vec![partial];
+            if let Some(isa) = &statement.isa {
+                partials = partials
+                    .into_iter()
+                    .flat_map(|partial| {
+                        self.match_isa(&data.type_, &isa.type_reference, isa.is_explicit == IsExplicit::Yes, partial)
+                    })
+                    .collect();
+            }
+            for (i, has) in statement.has.iter().enumerate() {
+                let attributes = match only_has {
+                    Some((index, attribute)) if index == i => self
+                        .owned_attributes(iid, has_label(has))
+                        .into_iter()
+                        .filter(|owned| *owned == attribute)
+                        .collect(),
+                    _ => self.owned_attributes(iid, has_label(has)),
+                };
+                partials = partials.into_iter().flat_map(|partial| self.match_has(&attributes, has, partial)).collect();
+            }
+            if let Some(relation) = &statement.relation {
+                partials =
+                    partials.into_iter().flat_map(|partial| self.match_relation(iid, relation, partial)).collect();
+            }
+            answers.extend(partials);
+        }
+        Ok(answers)
+    }
+
+    fn match_isa(
+        &self,
+        type_: &Label,
+        reference: &TypeReference,
+        is_explicit: bool,
+        answer: ConceptMap,
+    ) -> Vec<ConceptMap> {
+        let types = if is_explicit { vec![type_.clone()] } else { self.schema.supertypes(type_) };
+        match reference {
+            TypeReference::Label(label) => types.contains(label).then_some(answer).into_iter().collect(),
+            TypeReference::Variable(variable) => types
+                .into_iter()
+                .filter_map(|type_| bind_concept(answer.clone(), variable, Concept::Type(type_)))
+                .collect(),
+        }
+    }
+
+    fn match_has(&self, attributes: &[ThingId], has: &HasConstraint, answer: ConceptMap) -> Vec<ConceptMap> {
+        match has {
+            HasConstraint::HasConcept(_, variable) => attributes
+                .iter()
+                .filter_map(|attribute| bind_concept(answer.clone(), variable, Concept::Thing(self.thing(*attribute))))
+                .collect(),
+            HasConstraint::HasValue(_, variable) => attributes
+                .iter()
+                .filter_map(|attribute| {
+                    let value = self.things[attribute].value.clone()?;
+                    bind_value(answer.clone(), variable, value)
+                })
+                .collect(),
+            HasConstraint::HasPredicate(_, predicate) => {
+                let satisfied = attributes.iter().any(|attribute| {
+                    self.things[attribute].value.as_ref().is_some_and(|value| self.satisfies(value, predicate, &answer))
+                });
+                satisfied.then_some(answer).into_iter().collect()
+            }
+        }
+    }
+
+    pub(crate) fn owned_attributes(&self, owner: ThingId, label: Option<&Label>) -> Vec<ThingId> {
+        self.things[&owner]
+            .has
+            .iter()
+            .copied()
+            .filter(|attribute| {
+                label.map_or(true, |label| self.schema.is_subtype(&self.things[attribute].type_, label))
+            })
+            .collect()
+    }
+
+    fn match_relation(
+        &self,
+        relation: ThingId,
+        constraint: &RelationConstraint,
+        answer: ConceptMap,
+    ) -> Vec<ConceptMap> {
+        let role_players = &self.things[&relation].role_players;
+        let mut answers = Vec::new();
+        self.match_role_players(
+            role_players,
+            &constraint.role_players,
+            &mut vec![false; role_players.len()],
+            answer,
+            &mut answers,
+        );
+        answers
+    }
+
+    fn match_role_players(
+        &self,
+        role_players: &[(Label, ThingId)],
+        constraints: &[RolePlayerConstraint],
+        used: &mut Vec<bool>,
+        answer: ConceptMap,
+        answers: &mut Vec<ConceptMap>,
+    ) {
+        let Some((constraint, rest)) = constraints.split_first() else {
+            answers.push(answer);
+            return;
+        };
+        for (i, (role, player)) in role_players.iter().enumerate() {
+            if used[i] {
+                continue;
+            }
+            let partial = match &constraint.role_type {
+                None => Some(answer.clone()),
+                Some(TypeReference::Label(label)) => self.schema.role_matches(role, label).then(|| answer.clone()),
+                Some(TypeReference::Variable(variable)) => match concept_of(&answer, variable) {
+                    Some(Concept::Type(label)) => self.schema.role_matches(role, label).then(|| answer.clone()),
+                    Some(_) => None,
+                    None => bind_concept(answer.clone(), variable, Concept::Type(role.clone())),
+                },
+            };
+            let Some(partial) = partial
+                .and_then(|partial| bind_concept(partial, &constraint.player, Concept::Thing(self.thing(*player))))
+            else {
+                continue;
+            };
+            used[i] = true;
+            self.match_role_players(role_players, rest, used, partial, answers);
+            used[i] = false;
+        }
+    }
+
+    fn match_type(&self, statement: &TypeStatement, answer: ConceptMap) -> Result<Vec<ConceptMap>> {
+        let candidates: Vec<Label> = match concept_of(&answer, &statement.variable) {
+            Some(Concept::Type(label)) => vec![label.clone()],
+            Some(_) => Vec::new(),
+            None => match &statement.label {
+                Some(label) => {
+                    self.schema.get(&label.label)?;
+                    vec![label.label.clone()]
+                }
+                None => self.schema.labels().cloned().collect(),
+            },
+        };
+
+        let mut answers = Vec::new();
+        for label in candidates {
+            let info = self.schema.get(&label)?;
+            if statement.label.as_ref().is_some_and(|constraint| constraint.label != label)
+                || statement.abstract_.is_some() && !info.is_abstract
+                || statement
+                    .value_type
+                    .as_ref()
+                    .is_some_and(|constraint| self.schema.value_type(&label).as_ref() != Some(&constraint.value_type))
+            {
+                continue;
+            }
+            let Some(partial) = bind_concept(answer.clone(), &statement.variable, Concept::Type(label.clone())) else {
+                continue;
+            };
+            let mut partials = vec![partial];
+            if let Some(sub) = &statement.sub {
+                partials = partials
+                    .into_iter()
+                    .flat_map(|partial| {
+                        let supertypes = match sub.is_explicit {
+                            IsExplicit::Yes => self.schema.supertype(&label).into_iter().collect(),
+                            IsExplicit::No => self.schema.supertypes(&label),
+                        };
+                        self.match_type_reference(&sub.type_, supertypes, partial)
+                    })
+                    .collect();
+            }
+            for owns in &statement.owns {
+                let owned = self.schema.labels().filter(|attribute| {
+                    self.schema.is_kind(attribute, token::Type::Attribute) && self.schema.owns(&label, attribute)
+                });
+                let owned: Vec<Label> = owned.cloned().collect();
+                partials = partials
+                    .into_iter()
+                    .flat_map(|partial| self.match_type_reference(&owns.attribute_type, owned.clone(), partial))
+                    .collect();
+            }
+            for plays in &statement.plays {
+                let played: Vec<Label> = self
+                    .schema
+                    .labels()
+                    .filter(|role| role.scope.is_some() && self.schema.plays(&label, role))
+                    .cloned()
+                    .collect();
+                partials = partials
+                    .into_iter()
+                    .flat_map(|partial| self.match_type_reference(&plays.role_type, played.clone(), partial))
+                    .collect();
+            }
+            for relates in &statement.relates {
+                let related: Vec<Label> = self
+                    .schema
+                    .supertypes(&label)
+                    .iter()
+                    .filter_map(|label| self.schema.get(label).ok())
+                    .flat_map(|info| info.relates.iter().cloned())
+                    .collect();
+                partials = partials
+                    .into_iter()
+                    .flat_map(|partial| match &relates.role_type {
+                        TypeReference::Label(role) => {
+                            self.schema.resolve_role(&label, role).is_some().then_some(partial).into_iter().collect()
+                        }
+                        reference => self.match_type_reference(reference, related.clone(), partial),
+                    })
+                    .collect();
+            }
+            answers.extend(partials);
+        }
+        Ok(answers)
+    }
+
+    fn match_type_reference(
+        &self,
+        reference: &TypeReference,
+        labels: Vec<Label>,
+        answer: ConceptMap,
+    ) -> Vec<ConceptMap> {
+        match reference {
+            TypeReference::Label(label) => labels.contains(label).then_some(answer).into_iter().collect(),
+            TypeReference::Variable(variable) => labels
+                .into_iter()
+                .filter_map(|label| bind_concept(answer.clone(), variable, Concept::Type(label)))
+                .collect(),
+        }
+    }
+
+    fn match_value(&self, statement: &ValueStatement, answer: ConceptMap) -> Result<Vec<ConceptMap>> {
+        if let Some(assign) = &statement.assign_constraint {
+            return match self.evaluate(&assign.expression, &answer)? {
+                Some(value) => Ok(bind_value(answer, &statement.variable, value).into_iter().collect()),
+                None => Ok(Vec::new()),
+            };
+        }
+        let value = answer.get_variable(&Variable::Value(statement.variable.clone())).and_then(Concept::value);
+        let satisfied = match (&statement.predicate_constraint, value) {
+            (Some(predicate), Some(value)) => self.satisfies(value, predicate, &answer),
+            (None, Some(_)) => true,
+            (_, None) => false,
+        };
+        Ok(satisfied.then_some(answer).into_iter().collect())
+    }
+
+    fn match_in(&self, variable: &ConceptVariable, in_: &InConstraint, answer: ConceptMap) -> Result<Vec<ConceptMap>> {
+        let concepts = self.call(&in_.call, &answer)?.unwrap_or_default();
+        Ok(concepts.into_iter().filter_map(|concept| bind_concept(answer.clone(), variable, concept)).collect())
+    }
+
+    fn call(&self, call: &FunctionCall, answer: &ConceptMap) -> Result<Option<Vec<Concept>>> {
+        let function = self.schema.function(&call.label)?;
+        if function.arguments.len() != call.args.len() {
+            Err(TypeQLError::InvalidFunctionCallArity {
+                function_label: call.label.clone(),
+                expected: function.arguments.len(),
+                arity: call.args.len(),
+            })?
+        }
+        let mut bindings = ConceptMap::default();
+        for (argument, arg) in function.arguments.iter().zip(&call.args) {
+            let concept = match arg {
+                Expression::ThingVariable(variable) => concept_of(answer, variable).cloned(),
+                arg => self.evaluate(arg, answer)?.map(Concept::Value),
+            };
+            let Some(concept) = concept else { return Ok(None) };
+            bindings.insert(argument.variable.clone(), concept);
+        }
+        let mut returned = Vec::new();
+        for answer in self.match_patterns(function.match_clause.conjunction.patterns.iter().collect(), bindings)? {
+            match answer.get_variable(&function.returned) {
+                Some(concept) if !returned.iter().any(|other| is_same(other, concept)) => {
+                    returned.push(concept.clone())
+                }
+                _ => (),
+            }
+        }
+        Ok(Some(returned))
+    }
+
+    fn resolve_calls(&self, expression: &Expression, answer: &ConceptMap) -> Result<Option<Expression>> {
+        let resolved = match expression {
+            Expression::Operation(operation) => {
+                let (Some(left), Some(right)) = (operation.left(), operation.right()) else {
+                    return Ok(Some(expression.clone()));
+                };
+                let (Some(left), Some(right)) = (self.resolve_calls(left, answer)?, self.resolve_calls(right, answer)?)
+                else {
+                    return Ok(None);
+                };
+                Operation::new(operation.op_token(), left, right).into()
+            }
+            Expression::Function(function) => {
+                let args = function
+                    .args
+                    .iter()
+                    .map(|arg| self.resolve_calls(arg, answer))
+                    .collect::<Result<Option<Vec<_>>>>()?;
+                let Some(args) = args else { return Ok(None) };
+                Function { function_name: function.function_name, args }.into()
+            }
+            Expression::Call(call) => match self.call(call, answer)?.and_then(|concepts| concepts.into_iter().next()) {
+                Some(Concept::Value(value)) => Expression::Constant(value),
+                _ => return Ok(None),
+            },
+            expression => expression.clone(),
+        };
+        Ok(Some(resolved))
+    }
+
+    pub(crate) fn evaluate(&self, expression: &Expression, answer: &ConceptMap) -> Result<Option<Constant>> {
+        let Some(expression) = self.resolve_calls(expression, answer)? else { return Ok(None) };
+        let mut bindings = HashMap::new();
+        for variable in expression.variables() {
+            let variable = variable.to_owned();
+            match answer.get_variable(&variable).and_then(Concept::value) {
+                Some(value) => bindings.insert(variable, value.clone()),
+                None => return Ok(None),
+            };
+        }
+        Ok(Some(evaluate(&expression, &bindings)?))
+    }
+
+    pub(crate) fn satisfies(&self, value: &Constant, predicate: &Predicate, answer: &ConceptMap) -> bool {
+        let operand = match &predicate.value {
+            Value::Constant(constant) => Some(constant.clone()),
+            Value::ThingVariable(variable) => concept_of(answer, variable).and_then(|concept| concept.value().cloned()),
+            Value::ValueVariable(variable) => {
+                answer.get_variable(&Variable::Value(variable.clone())).and_then(|concept| concept.value().cloned())
+            }
+        };
+        let value = predicate.fields.iter().try_fold(value, |value, field| match value {
+            Constant::Struct(fields) => fields.iter().find(|(name, _)| name == field).map(|(_, value)| value),
+            _ => None,
+        });
+        let value = match (predicate.index, value) {
+            (None, value) => value,
+            (Some(index), Some(Constant::List(values))) => {
+                let index = if index < 0 { values.len() as i64 + index } else { index };
+                usize::try_from(index).ok().and_then(|index| values.get(index))
+            }
+            (Some(_), _) => None,
+        };
+        match (value, operand) {
+            (Some(value), Some(operand)) => compare(predicate.predicate, value, &operand),
+            _ => false,
+        }
+    }
+}
+
+pub(crate) fn compare(predicate: token::Predicate, value: &Constant, operand: &Constant) -> bool {
+    use token::Predicate::*;
+    match predicate {
+        Contains | Like => match (value, operand) {
+            (Constant::List(values), operand) if predicate == Contains => {
+                values.iter().any(|value| compare_constants(value, operand) == Some(Ordering::Equal))
+            }
+            (Constant::String(value), Constant::String(operand)) if predicate == Contains => {
+                value.to_lowercase().contains(&operand.to_lowercase())
+            }
+            (Constant::String(value), Constant::String(operand)) => {
+                Regex::new(operand).is_ok_and(|regex| regex.is_match(value))
+            }
+            _ => false,
+        },
+        _ => match compare_constants(value, operand) {
+            Some(ordering) => match predicate {
+                Eq | EqLegacy => ordering == Ordering::Equal,
+                Neq => ordering != Ordering::Equal,
+                Gt => ordering == Ordering::Greater,
+                Gte => ordering != Ordering::Less,
+                Lt => ordering == Ordering::Less,
+                Lte => ordering != Ordering::Greater,
+                Contains | Like => unreachable!(),
+            },
+            None => predicate == Neq,
+        },
+    }
+}
+
+pub(crate) fn reduce_of(pattern: &Pattern) -> Option<(&ValueStatement, &ReduceConstraint)> {
+    match pattern {
+        Pattern::Statement(Statement::Value(statement)) => {
+            statement.reduce_constraint.as_ref().map(|reduce| (statement, reduce))
+        }
+        _ => None,
+    }
+}
+
+fn reduced_answers(
+    statement: &ValueStatement,
+    reduce: &ReduceConstraint,
+    answers: Vec<ConceptMap>,
+) -> Result<Vec<ConceptMap>> {
+    let aggregation = Aggregation { method: reduce.method, var: reduce.var.clone(), percentile: None };
+    let mut reduced = Vec::new();
+    for (_, group) in group(answers, &reduce.within) {
+        let Some(value) = aggregate(&group, &aggregation)? else { continue };
+        reduced.extend(group.into_iter().filter_map(|answer| bind_value(answer, &statement.variable, value.clone())));
+    }
+    Ok(reduced)
+}
+
+fn has_label(has: &HasConstraint) -> Option<&Label> {
+    match has {
+        HasConstraint::HasConcept(label, _) => label.as_ref(),
+        HasConstraint::HasValue(label, _) | HasConstraint::HasPredicate(label, _) => Some(label),
+    }
+}
+
+pub(crate) fn flatten(pattern: &Pattern) -> Vec<&Pattern> {
+    match pattern {
+        Pattern::Conjunction(conjunction) => conjunction.patterns.iter().flat_map(flatten).collect(),
+        pattern => vec![pattern],
+    }
+}
+
+fn next_pattern(pending: &[&Pattern], answer: &ConceptMap) -> Option<usize> {
+    let is_bound =
+        |variable: VariableRef<'_>| variable.is_name() && answer.get_variable(&variable.to_owned()).is_some();
+    let introduced = optional_variables(pending, &is_bound);
+    let is_deferred = |i: usize, pattern: &Pattern| {
+        pattern.variables_recursive().any(|variable| introduced.get(&variable).is_some_and(|optional| *optional != i))
+    };
+    let eligible = || pending.iter().enumerate().filter(|(i, pattern)| !is_deferred(*i, pattern));
+    let ready_statements = eligible().filter_map(|(i, pattern)| match pattern {
+        Pattern::Statement(statement) if is_ready(statement, &is_bound) => Some((i, statement)),
+        _ => None,
+    });
+    let first = |kind: fn(&Pattern) -> bool| eligible().find(|(_, pattern)| kind(pattern)).map(|(i, _)| i);
+    ready_statements
+        .min_by_key(|(_, statement)| !is_bound(statement.owner()))
+        .map(|(i, _)| i)
+        .or_else(|| first(|pattern| matches!(pattern, Pattern::Disjunction(_))))
+        .or_else(|| first(|pattern| matches!(pattern, Pattern::Optional(_))))
+        .or_else(|| first(|pattern| matches!(pattern, Pattern::Negation(_))))
+}
+
+// variables first mentioned by a pending optional, mapped to its position; other patterns wait for it to run
+fn optional_variables<'a>(
+    pending: &[&'a Pattern],
+    is_bound: &impl Fn(VariableRef<'_>) -> bool,
+) -> HashMap<VariableRef<'a>, usize> {
+    let mut seen = HashSet::new();
+    let mut introduced = HashMap::new();
+    for (i, pattern) in pending.iter().enumerate() {
+        if matches!(pattern, Pattern::Optional(_)) {
+            for variable in pattern.variables_recursive() {
+                if variable.is_name() && !is_bound(variable) && !seen.contains(&variable) {
+                    introduced.entry(variable).or_insert(i);
+                }
+            }
+        }
+        seen.extend(pattern.variables_recursive());
+    }
+    introduced
+}
+
+fn is_ready(statement: &Statement, is_bound: &impl Fn(VariableRef<'_>) -> bool) -> bool {
+    match statement {
+        Statement::Thing(thing) => thing
+            .predicate
+            .iter()
+            .chain(thing.has.iter().filter_map(|has| match has {
+                HasConstraint::HasPredicate(_, predicate) => Some(predicate),
+                _ => None,
+            }))
+            .flat_map(Predicate::variables)
+            .all(is_bound),
+        Statement::Value(value) => match &value.assign_constraint {
+            Some(assign) => assign.variables().all(is_bound),
+            None => {
+                is_bound(value.owner())
+                    && value.predicate_constraint.iter().flat_map(Predicate::variables).all(is_bound)
+            }
+        },
+        Statement::Concept(concept) => match &concept.in_constraint {
+            Some(in_) => in_.variables().all(is_bound),
+            None => concept.variables().any(is_bound),
+        },
+        Statement::Type(_) => true,
+    }
+}
+
+fn match_concept(statement: &ConceptStatement, answer: ConceptMap) -> Vec<ConceptMap> {
+    let Some(is) = &statement.is_constraint else { return vec![answer] };
+    let bound = concept_of(&answer, &statement.variable).or_else(|| concept_of(&answer, &is.variable)).cloned();
+    let Some(concept) = bound else { return Vec::new() };
+    bind_concept(answer, &statement.variable, concept.clone())
+        .and_then(|answer| bind_concept(answer, &is.variable, concept))
+        .into_iter()
+        .collect()
+}
+
+pub(crate) fn concept_of<'a>(answer: &'a ConceptMap, variable: &ConceptVariable) -> Option<&'a Concept> {
+    if variable.is_named() {
+        answer.get_variable(&Variable::Concept(variable.clone()))
+    } else {
+        None
+    }
+}
+
+pub(crate) fn bind_concept(mut answer: ConceptMap, variable: &ConceptVariable, concept: Concept) -> Option<ConceptMap> {
+    if !variable.is_named() {
+        return Some(answer);
+    }
+    match concept_of(&answer, variable) {
+        Some(bound) if is_same(bound, &concept) => Some(answer),
+        Some(_) => None,
+        None => {
+            answer.insert(Variable::Concept(variable.clone()), concept);
+            Some(answer)
+        }
+    }
+}
+
+pub(crate) fn bind_value(mut answer: ConceptMap, variable: &ValueVariable, value: Constant) -> Option<ConceptMap> {
+    let key = Variable::Value(variable.clone());
+    match answer.get_variable(&key) {
+        Some(bound) if is_same(bound, &Concept::Value(value.clone())) => Some(answer),
+        Some(_) => None,
+        None => {
+            answer.insert(key, Concept::Value(value));
+            Some(answer)
+        }
+    }
+}
+
+pub(crate) fn is_same(lhs: &Concept, rhs: &Concept) -> bool {
+    match (lhs, rhs) {
+        (Concept::Thing(lhs), Concept::Thing(rhs)) => lhs.iid == rhs.iid,
+        (Concept::Type(lhs), Concept::Type(rhs)) => lhs == rhs,
+        (Concept::Value(lhs), Concept::Value(rhs)) => compare_constants(lhs, rhs) == Some(Ordering::Equal),
+        _ => false,
+    }
+}
+
+pub(crate) fn parse_iid(iid: &str) -> Option<ThingId> {
+    usize::from_str_radix(iid.strip_prefix("0x")?, 16).ok().map(ThingId)
+}
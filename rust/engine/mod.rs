@@ -0,0 +1,22 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+mod aggregate;
+mod answer;
+mod database;
+mod fetch;
+mod matcher;
+mod modifier;
+mod reasoner;
+mod schema;
+#[cfg(test)]
+mod test;
+mod writer;
+
+pub use answer::{Concept, ConceptMap, ConceptMapGroup, PipelineAnswers, Thing, ThingId, ValueGroup};
+pub use database::Database;
+pub use modifier::next_page;
+pub use reasoner::{Explanation, Inferred};
@@ -0,0 +1,162 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::cmp::Ordering;
+
+use crate::{
+    common::{error::TypeQLError, token, Result},
+    engine::answer::{compare_constants, Concept, ConceptMap},
+    pattern::{Constant, VariablesRetrieved},
+    query::{
+        modifier::Modifiers,
+        sorting::{Collation, SortVariable},
+        Cursor, Selection, Sorting, TypeQLGet,
+    },
+    variable::Variable,
+};
+
+pub(crate) fn filter_variables(query: &dyn VariablesRetrieved) -> Vec<Variable> {
+    query.retrieved_variables().filter(|variable| variable.is_name()).map(|variable| variable.to_owned()).collect()
+}
+
+pub(crate) fn apply_filter(answers: Vec<ConceptMap>, variables: &[Variable]) -> Vec<ConceptMap> {
+    apply_distinct(
+        answers
+            .into_iter()
+            .map(|mut answer| {
+                answer.retain(variables);
+                answer
+            })
+            .collect(),
+    )
+}
+
+pub(crate) fn apply_selection(answers: Vec<ConceptMap>, selection: &Selection) -> Vec<ConceptMap> {
+    answers
+        .into_iter()
+        .map(|answer| {
+            let mut selected = ConceptMap::default();
+            for select_var in &selection.vars {
+                if let Some(concept) = answer.get_variable(&select_var.variable) {
+                    selected.insert(select_var.output().to_owned(), concept.clone());
+                }
+            }
+            selected
+        })
+        .collect()
+}
+
+fn apply_distinct(answers: Vec<ConceptMap>) -> Vec<ConceptMap> {
+    let mut distinct: Vec<ConceptMap> = Vec::new();
+    for answer in answers {
+        if !distinct.contains(&answer) {
+            distinct.push(answer);
+        }
+    }
+    distinct
+}
+
+pub(crate) fn apply_modifiers(mut answers: Vec<ConceptMap>, modifiers: &Modifiers) -> Result<Vec<ConceptMap>> {
+    if modifiers.distinct.is_some() {
+        answers = apply_distinct(answers);
+    }
+    if let Some(sorting) = &modifiers.sorting {
+        answers.sort_by(|lhs, rhs| {
+            sorting
+                .vars
+                .iter()
+                .map(|sort_var| match (lhs.get_variable(&sort_var.variable), rhs.get_variable(&sort_var.variable)) {
+                    (Some(lhs), Some(rhs)) => directed(compare_concepts(lhs, rhs, sort_var), sort_var),
+                    (Some(_), None) => unbound_ordering(sort_var).reverse(),
+                    (None, Some(_)) => unbound_ordering(sort_var),
+                    (None, None) => Ordering::Equal,
+                })
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+    if let (Some(sorting), Some(cursor)) = (&modifiers.sorting, &modifiers.cursor) {
+        let is_after =
+            answers.iter().map(|answer| is_after_cursor(answer, sorting, cursor)).collect::<Result<Vec<_>>>()?;
+        answers =
+            answers.into_iter().zip(is_after).filter_map(|(answer, is_after)| is_after.then_some(answer)).collect();
+    }
+    let offset = modifiers.offset.as_ref().map_or(0, |offset| offset.offset);
+    let limit = modifiers.limit.as_ref().map_or(usize::MAX, |limit| limit.limit);
+    Ok(answers.into_iter().skip(offset).take(limit).collect())
+}
+
+// unbound sorts last ascending and first descending, unless nulls first/last is given
+fn unbound_ordering(sort_var: &SortVariable) -> Ordering {
+    match (sort_var.nulls, sort_var.order) {
+        (Some(token::NullsOrder::First), _) | (None, Some(token::Order::Desc)) => Ordering::Less,
+        (Some(token::NullsOrder::Last), _) | (None, _) => Ordering::Greater,
+    }
+}
+
+fn directed(ordering: Ordering, sort_var: &SortVariable) -> Ordering {
+    match sort_var.order {
+        Some(token::Order::Desc) => ordering.reverse(),
+        _ => ordering,
+    }
+}
+
+fn compare_concepts(lhs: &Concept, rhs: &Concept, sort_var: &SortVariable) -> Ordering {
+    match (lhs.value(), rhs.value(), &sort_var.collation) {
+        (Some(Constant::String(lhs)), Some(Constant::String(rhs)), Some(collation)) => collate(lhs, rhs, collation),
+        _ => lhs.compare(rhs),
+    }
+}
+
+fn compare_collated(lhs: &Constant, rhs: &Constant, sort_var: &SortVariable) -> Option<Ordering> {
+    match (lhs, rhs, &sort_var.collation) {
+        (Constant::String(lhs), Constant::String(rhs), Some(collation)) => Some(collate(lhs, rhs, collation)),
+        _ => compare_constants(lhs, rhs),
+    }
+}
+
+fn collate(lhs: &str, rhs: &str, collation: &Collation) -> Ordering {
+    match collation {
+        Collation::CaseInsensitive => lhs.to_lowercase().cmp(&rhs.to_lowercase()),
+    }
+}
+
+fn is_after_cursor(answer: &ConceptMap, sorting: &Sorting, cursor: &Cursor) -> Result<bool> {
+    for (sort_var, value) in sorting.vars.iter().zip(&cursor.values) {
+        let ordering = match answer.get_variable(&sort_var.variable) {
+            Some(concept) => directed(
+                concept.value().and_then(|answer_value| compare_collated(answer_value, value, sort_var)).ok_or_else(
+                    || TypeQLError::CursorTypeMismatch { variable: sort_var.variable.clone(), value: value.clone() },
+                )?,
+                sort_var,
+            ),
+            None => unbound_ordering(sort_var),
+        };
+        if ordering.is_ne() {
+            return Ok(ordering.is_gt());
+        }
+    }
+    Ok(false)
+}
+
+pub fn next_page(query: &TypeQLGet, last_answer: &ConceptMap) -> Result<TypeQLGet> {
+    let Some(sorting) = &query.modifiers.sorting else {
+        return Err(TypeQLError::CursorRequiresSorting.into());
+    };
+    let vars: Vec<Variable> = sorting.vars.iter().map(|sort_var| sort_var.variable.clone()).collect();
+    let values = vars
+        .iter()
+        .map(|variable| {
+            last_answer
+                .get_variable(variable)
+                .and_then(Concept::value)
+                .cloned()
+                .ok_or_else(|| TypeQLError::CursorValueMissing { variable: variable.clone() }.into())
+        })
+        .collect::<Result<_>>()?;
+    let modifiers = Modifiers { cursor: Some(Cursor::new(vars, values)), offset: None, ..query.modifiers.clone() };
+    Ok(TypeQLGet { modifiers, ..query.clone() })
+}
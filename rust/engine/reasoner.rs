@@ -0,0 +1,194 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::HashSet;
+
+use crate::{
+    common::{error::TypeQLError, Result},
+    engine::{
+        answer::{ConceptMap, ThingId},
+        matcher::{flatten, parse_iid, reduce_of},
+        Database,
+    },
+    pattern::{
+        Label, Pattern, RelationConstraint, Rule, RuleDependencyGraph, RuleStratification, Statement, ThingStatement,
+    },
+};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Inferred {
+    Has { owner: ThingId, attribute: ThingId },
+    Relation(ThingId),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub rule: Label,
+    pub bindings: ConceptMap,
+    pub inferred: Inferred,
+}
+
+impl Database {
+    pub(crate) fn materialise(&mut self) -> Result<Vec<Explanation>> {
+        let graph = RuleDependencyGraph::with_types(&self.schema.rules, &self.schema.statements);
+        let stratification = RuleStratification::new(&graph)?;
+        let mut explanations = Vec::new();
+        for stratum in stratification.strata() {
+            let mut delta: Option<(HashSet<&Label>, Vec<Inferred>)> = None;
+            loop {
+                let mut fired = HashSet::new();
+                let mut inferred_facts = Vec::new();
+                for label in stratum {
+                    let is_affected = delta.as_ref().map_or(true, |(changed, _)| {
+                        graph.dependencies_of(label).any(|dependency| changed.contains(&dependency.from))
+                    });
+                    if !is_affected {
+                        continue;
+                    }
+                    let rule = self.schema.rules.iter().find(|rule| &rule.label == label).unwrap().clone();
+                    let answers = match &delta {
+                        Some((_, facts)) => self.match_against_delta(&rule, facts)?,
+                        None => self.match_(&rule.when)?,
+                    };
+                    for bindings in answers {
+                        if let Some(inferred) = self.conclude(&rule, &bindings)? {
+                            explanations.push(Explanation { rule: rule.label.clone(), bindings, inferred });
+                            inferred_facts.push(inferred);
+                            fired.insert(label);
+                        }
+                    }
+                }
+                if fired.is_empty() {
+                    break;
+                }
+                delta = Some((fired, inferred_facts));
+            }
+        }
+        Ok(explanations)
+    }
+
+    fn match_against_delta(&self, rule: &Rule, delta: &[Inferred]) -> Result<Vec<ConceptMap>> {
+        let patterns: Vec<&Pattern> = rule.when.patterns.iter().flat_map(flatten).collect();
+        let is_joinable = patterns.iter().all(|pattern| {
+            matches!(pattern, Pattern::Statement(_) | Pattern::Negation(_)) && reduce_of(pattern).is_none()
+        });
+        if !is_joinable {
+            return self.match_(&rule.when);
+        }
+
+        let mut new_things: Vec<ThingId> = delta
+            .iter()
+            .map(|inferred| match inferred {
+                Inferred::Has { attribute, .. } => *attribute,
+                Inferred::Relation(relation) => *relation,
+            })
+            .collect();
+        new_things.sort();
+        new_things.dedup();
+
+        let mut answers = Vec::new();
+        for (i, pattern) in patterns.iter().enumerate() {
+            let Pattern::Statement(Statement::Thing(statement)) = pattern else {
+                continue;
+            };
+            let rest: Vec<&Pattern> = patterns.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, p)| *p).collect();
+            let mut seeds = self.match_thing_among(
+                statement,
+                candidates(statement, new_things.clone()),
+                None,
+                ConceptMap::default(),
+            )?;
+            for (index, _) in statement.has.iter().enumerate() {
+                for inferred in delta {
+                    if let Inferred::Has { owner, attribute } = inferred {
+                        seeds.extend(self.match_thing_among(
+                            statement,
+                            candidates(statement, vec![*owner]),
+                            Some((index, *attribute)),
+                            ConceptMap::default(),
+                        )?);
+                    }
+                }
+            }
+            for seed in seeds {
+                answers.extend(self.match_patterns(rest.clone(), seed)?);
+            }
+        }
+        Ok(answers)
+    }
+
+    fn conclude(&mut self, rule: &Rule, bindings: &ConceptMap) -> Result<Option<Inferred>> {
+        let then = &rule.then;
+        let Some(owner) = self.bound_thing(bindings, &then.variable) else {
+            return match &then.relation {
+                Some(relation) => self.conclude_relation(rule, then, relation, bindings),
+                None => Err(TypeQLError::InvalidRuleThenVariables { rule_label: rule.label.clone() })?,
+            };
+        };
+        let Some(has) = then.has.first() else {
+            Err(TypeQLError::InvalidRuleThen { rule_label: rule.label.clone(), then: then.clone() })?
+        };
+        let attribute = self.inserted_attribute(has, bindings, then)?;
+        if self.things[&owner].has.contains(&attribute) {
+            return Ok(None);
+        }
+        self.put_ownership(owner, attribute)?;
+        Ok(Some(Inferred::Has { owner, attribute }))
+    }
+
+    fn conclude_relation(
+        &mut self,
+        rule: &Rule,
+        then: &ThingStatement,
+        relation: &RelationConstraint,
+        bindings: &ConceptMap,
+    ) -> Result<Option<Inferred>> {
+        let Some(isa) = &then.isa else {
+            Err(TypeQLError::InvalidRuleThen { rule_label: rule.label.clone(), then: then.clone() })?
+        };
+        let relation_type = self.type_of(&isa.type_reference, bindings)?;
+        let mut role_players = Vec::new();
+        for role_player in &relation.role_players {
+            let Some(role_type) = &role_player.role_type else {
+                Err(TypeQLError::InvalidRuleThenRoles { rule_label: rule.label.clone(), then: then.clone() })?
+            };
+            let role = self.type_of(role_type, bindings)?;
+            let Some(role) = self.schema.resolve_role(&relation_type, &role) else {
+                Err(TypeQLError::InvalidRelationRole { relation: relation_type.clone(), role })?
+            };
+            let Some(player) = self.bound_thing(bindings, &role_player.player) else {
+                Err(TypeQLError::InvalidRuleThenVariables { rule_label: rule.label.clone() })?
+            };
+            role_players.push((role, player));
+        }
+
+        let exists = self
+            .things
+            .values()
+            .any(|data| data.type_ == relation_type && is_same_role_players(&data.role_players, &role_players));
+        if exists {
+            return Ok(None);
+        }
+        let iid = self.put_thing(relation_type, None);
+        for (role, player) in &role_players {
+            self.put_role_player(iid, role, *player)?;
+        }
+        Ok(Some(Inferred::Relation(iid)))
+    }
+}
+
+fn candidates(statement: &ThingStatement, things: Vec<ThingId>) -> Vec<ThingId> {
+    match &statement.iid {
+        Some(iid) => things.into_iter().filter(|thing| parse_iid(&iid.iid) == Some(*thing)).collect(),
+        None => things,
+    }
+}
+
+fn is_same_role_players(lhs: &[(Label, ThingId)], rhs: &[(Label, ThingId)]) -> bool {
+    let count =
+        |role_players: &[(Label, ThingId)], role_player| role_players.iter().filter(|rp| *rp == role_player).count();
+    lhs.len() == rhs.len() && lhs.iter().all(|role_player| count(lhs, role_player) == count(rhs, role_player))
+}
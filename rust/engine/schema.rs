@@ -0,0 +1,339 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{collections::HashMap, iter};
+
+use crate::{
+    common::{
+        decimal::Decimal,
+        error::{collect_err, TypeQLError},
+        token, Result,
+    },
+    pattern::{
+        validate_functions_are_not_recursive, validate_value_annotations, Constant, Label, Rule, SchemaFunction,
+        Struct, TypeStatement, ValueType,
+    },
+    variable::TypeReference,
+};
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TypeInfo {
+    pub(crate) supertype: Option<Label>,
+    pub(crate) is_abstract: bool,
+    pub(crate) value_type: Option<ValueType>,
+    pub(crate) owns: Vec<Label>,
+    pub(crate) owns_list: Vec<Label>,
+    pub(crate) plays: Vec<Label>,
+    pub(crate) relates: Vec<Label>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Schema {
+    types: HashMap<Label, TypeInfo>,
+    order: Vec<Label>,
+    structs: HashMap<String, Struct>,
+    pub(crate) statements: Vec<TypeStatement>,
+    pub(crate) rules: Vec<Rule>,
+    pub(crate) functions: Vec<SchemaFunction>,
+}
+
+impl Default for Schema {
+    fn default() -> Self {
+        let mut schema = Schema {
+            types: HashMap::new(),
+            order: Vec::new(),
+            structs: HashMap::new(),
+            statements: Vec::new(),
+            rules: Vec::new(),
+            functions: Vec::new(),
+        };
+        let thing = Label::from(token::Type::Thing);
+        schema.put_type(thing.clone(), TypeInfo { is_abstract: true, ..TypeInfo::default() });
+        for root in [token::Type::Entity, token::Type::Relation, token::Type::Attribute] {
+            let info = TypeInfo { supertype: Some(thing.clone()), is_abstract: true, ..TypeInfo::default() };
+            schema.put_type(Label::from(root), info);
+        }
+        schema.put_type(
+            Label::from((token::Type::Relation.as_str(), token::Type::Role.as_str())),
+            TypeInfo { is_abstract: true, ..TypeInfo::default() },
+        );
+        schema
+    }
+}
+
+impl Schema {
+    fn put_type(&mut self, label: Label, info: TypeInfo) {
+        if !self.types.contains_key(&label) {
+            self.order.push(label.clone());
+        }
+        self.types.insert(label, info);
+    }
+
+    pub(crate) fn define(
+        &mut self,
+        statements: &[TypeStatement],
+        rules: &[Rule],
+        functions: &[SchemaFunction],
+        structs: &[Struct],
+    ) -> Result {
+        self.structs.extend(structs.iter().map(|struct_| (struct_.name.clone(), struct_.clone())));
+        for statement in statements {
+            let Some(label) = statement.label.as_ref().map(|constraint| constraint.label.clone()) else { continue };
+            let mut info = self.types.get(&label).cloned().unwrap_or_default();
+            if let Some(TypeReference::Label(supertype)) = statement.sub.as_ref().map(|sub| &sub.type_) {
+                info.supertype = Some(supertype.clone());
+            }
+            if statement.abstract_.is_some() {
+                info.is_abstract = true;
+            }
+            if let Some(value_type) = &statement.value_type {
+                info.value_type = Some(value_type.value_type.clone());
+            }
+            info.owns.extend(statement.owns.iter().filter_map(|owns| type_label(&owns.attribute_type)));
+            info.owns_list.extend(
+                statement.owns.iter().filter(|owns| owns.is_list).filter_map(|owns| type_label(&owns.attribute_type)),
+            );
+            info.plays.extend(statement.plays.iter().filter_map(|plays| type_label(&plays.role_type)));
+            self.put_type(label, info);
+        }
+        for statement in statements {
+            let Some(label) = statement.label.as_ref().map(|constraint| &constraint.label) else { continue };
+            for relates in &statement.relates {
+                let Some(role) = type_label(&relates.role_type) else { continue };
+                let role = Label::from((label.name.clone(), role.name));
+                let supertype = match relates.overridden_role_type.as_ref().and_then(type_label) {
+                    Some(overridden) => self.resolve_role(self.supertype(label).as_ref().unwrap_or(label), &overridden),
+                    None => None,
+                };
+                let supertype = supertype
+                    .unwrap_or_else(|| Label::from((token::Type::Relation.as_str(), token::Type::Role.as_str())));
+                self.put_type(role.clone(), TypeInfo { supertype: Some(supertype), ..TypeInfo::default() });
+                let info = self.types.get_mut(label).unwrap();
+                if !info.relates.contains(&role) {
+                    info.relates.push(role);
+                }
+            }
+        }
+        self.statements.extend(statements.iter().cloned());
+        self.rules.extend(rules.iter().cloned());
+        self.functions.extend(functions.iter().cloned());
+        collect_err(
+            self.types
+                .values()
+                .filter_map(|info| info.supertype.as_ref())
+                .map(|supertype| self.get(supertype).map(|_| ()))
+                .chain(iter::once(validate_functions_are_not_recursive(&self.functions)))
+                .chain(
+                    statements
+                        .iter()
+                        .filter_map(|statement| statement.value_type.as_ref().map(|constraint| &constraint.value_type))
+                        .chain(structs.iter().flat_map(|struct_| &struct_.fields).map(|field| &field.value_type))
+                        .filter_map(|value_type| match value_type {
+                            ValueType::Struct(struct_name) => Some(self.get_struct(struct_name).map(|_| ())),
+                            ValueType::Builtin(_) => None,
+                        }),
+                )
+                .chain(statements.iter().flat_map(|statement| &statement.owns).filter_map(|owns| {
+                    let value_type = self.value_type(&type_label(&owns.attribute_type)?)?;
+                    Some(validate_value_annotations(&owns.annotations, &value_type))
+                })),
+        )
+    }
+
+    pub(crate) fn get(&self, label: &Label) -> Result<&TypeInfo> {
+        match self.types.get(label) {
+            Some(info) => Ok(info),
+            None => Err(TypeQLError::TypeNotDefined { label: label.clone() })?,
+        }
+    }
+
+    pub(crate) fn function(&self, label: &Label) -> Result<&SchemaFunction> {
+        match self.functions.iter().find(|function| &function.label == label) {
+            Some(function) => Ok(function),
+            None => Err(TypeQLError::FunctionNotDefined { function_label: label.clone() })?,
+        }
+    }
+
+    pub(crate) fn labels(&self) -> impl Iterator<Item = &Label> {
+        self.order.iter()
+    }
+
+    pub(crate) fn supertype(&self, label: &Label) -> Option<Label> {
+        self.types.get(label).and_then(|info| info.supertype.clone())
+    }
+
+    pub(crate) fn supertypes(&self, label: &Label) -> Vec<Label> {
+        let mut supertypes = vec![label.clone()];
+        while let Some(supertype) = self.supertype(supertypes.last().unwrap()) {
+            if supertypes.contains(&supertype) {
+                break;
+            }
+            supertypes.push(supertype);
+        }
+        supertypes
+    }
+
+    pub(crate) fn root(&self, label: &Label) -> Label {
+        let thing = Label::from(token::Type::Thing);
+        self.supertypes(label).into_iter().rfind(|supertype| *supertype != thing).unwrap_or(thing)
+    }
+
+    pub(crate) fn is_subtype(&self, subtype: &Label, supertype: &Label) -> bool {
+        self.supertypes(subtype).contains(supertype)
+    }
+
+    pub(crate) fn is_kind(&self, label: &Label, kind: token::Type) -> bool {
+        self.is_subtype(label, &Label::from(kind))
+    }
+
+    pub(crate) fn value_type(&self, label: &Label) -> Option<ValueType> {
+        self.supertypes(label).iter().find_map(|label| self.types.get(label).and_then(|info| info.value_type.clone()))
+    }
+
+    pub(crate) fn get_struct(&self, name: &str) -> Result<&Struct> {
+        match self.structs.get(name) {
+            Some(struct_) => Ok(struct_),
+            None => Err(TypeQLError::StructNotDefined { struct_name: name.to_owned() })?,
+        }
+    }
+
+    pub(crate) fn validate_field_path(&self, label: &Label, fields: &[String]) -> Result {
+        let mut value_type = self.value_type(label);
+        for field in fields {
+            let Some(ValueType::Struct(struct_name)) = &value_type else {
+                Err(TypeQLError::InvalidStructFieldAccess { label: label.clone(), field: field.clone() })?
+            };
+            match self.get_struct(struct_name)?.get_field(field) {
+                Some(definition) => value_type = Some(definition.value_type.clone()),
+                None => {
+                    Err(TypeQLError::StructFieldNotDefined { struct_name: struct_name.clone(), field: field.clone() })?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn owns(&self, owner: &Label, attribute: &Label) -> bool {
+        let attribute_types = self.supertypes(attribute);
+        self.supertypes(owner)
+            .iter()
+            .filter_map(|label| self.types.get(label))
+            .any(|info| info.owns.iter().any(|owned| attribute_types.contains(owned)))
+    }
+
+    pub(crate) fn owns_list(&self, owner: &Label, attribute: &Label) -> bool {
+        let attribute_types = self.supertypes(attribute);
+        self.supertypes(owner)
+            .iter()
+            .filter_map(|label| self.types.get(label))
+            .any(|info| info.owns_list.iter().any(|owned| attribute_types.contains(owned)))
+    }
+
+    pub(crate) fn plays(&self, player: &Label, role: &Label) -> bool {
+        let role_types = self.supertypes(role);
+        self.supertypes(player)
+            .iter()
+            .filter_map(|label| self.types.get(label))
+            .any(|info| info.plays.iter().any(|played| role_types.contains(played)))
+    }
+
+    pub(crate) fn resolve_role(&self, relation: &Label, role: &Label) -> Option<Label> {
+        self.supertypes(relation).iter().filter_map(|label| self.types.get(label)).find_map(|info| {
+            info.relates
+                .iter()
+                .find(|related| {
+                    related.name == role.name
+                        && role.scope.as_ref().map_or(true, |scope| related.scope.as_ref() == Some(scope))
+                })
+                .cloned()
+        })
+    }
+
+    pub(crate) fn role_matches(&self, role: &Label, pattern_role: &Label) -> bool {
+        self.supertypes(role).iter().any(|label| match &pattern_role.scope {
+            Some(_) => label == pattern_role,
+            None => label.name == pattern_role.name,
+        })
+    }
+
+    pub(crate) fn coerce_value(&self, label: &Label, value: &Constant) -> Result<Constant> {
+        let coerced = match self.value_type(label) {
+            None => Some(value.clone()),
+            Some(value_type) => self.coerce_to(&value_type, value)?,
+        };
+        match coerced {
+            Some(value) => Ok(value),
+            None => Err(TypeQLError::InvalidAttributeValueType {
+                label: label.clone(),
+                value_type: self.value_type(label).unwrap(),
+                value: value.clone(),
+            })?,
+        }
+    }
+
+    fn coerce_to(&self, value_type: &ValueType, value: &Constant) -> Result<Option<Constant>> {
+        match (value_type, value) {
+            (ValueType::Builtin(value_type), Constant::List(values)) => {
+                let element_type = value_type.element_type().unwrap_or(*value_type);
+                Ok(values
+                    .iter()
+                    .map(|value| coerce(element_type, value))
+                    .collect::<Option<Vec<_>>>()
+                    .map(Constant::List))
+            }
+            (ValueType::Builtin(value_type), value) => Ok(coerce(*value_type, value)),
+            (ValueType::Struct(struct_name), Constant::Struct(fields)) => self.coerce_struct(struct_name, fields),
+            (ValueType::Struct(_), _) => Ok(None),
+        }
+    }
+
+    fn coerce_struct(&self, struct_name: &str, fields: &[(String, Constant)]) -> Result<Option<Constant>> {
+        let definition = self.get_struct(struct_name)?;
+        if let Some((name, _)) = fields.iter().find(|(name, _)| definition.get_field(name).is_none()) {
+            Err(TypeQLError::StructFieldNotDefined { struct_name: struct_name.to_owned(), field: name.clone() })?
+        }
+        let mut coerced = Vec::new();
+        for field in &definition.fields {
+            match fields.iter().find(|(name, _)| *name == field.name) {
+                Some((name, value)) => match self.coerce_to(&field.value_type, value)? {
+                    Some(value) => coerced.push((name.clone(), value)),
+                    None => return Ok(None),
+                },
+                None if field.is_optional => (),
+                None => Err(TypeQLError::MissingStructField {
+                    struct_name: struct_name.to_owned(),
+                    field: field.name.clone(),
+                })?,
+            }
+        }
+        Ok(Some(Constant::Struct(coerced)))
+    }
+}
+
+fn type_label(reference: &TypeReference) -> Option<Label> {
+    match reference {
+        TypeReference::Label(label) => Some(label.clone()),
+        TypeReference::Variable(_) => None,
+    }
+}
+
+fn coerce(value_type: token::ValueType, value: &Constant) -> Option<Constant> {
+    match (value_type, value) {
+        (token::ValueType::Long, Constant::Long(_))
+        | (token::ValueType::Double, Constant::Double(_))
+        | (token::ValueType::Boolean, Constant::Boolean(_))
+        | (token::ValueType::String, Constant::String(_))
+        | (token::ValueType::Date, Constant::Date(_))
+        | (token::ValueType::DateTime, Constant::DateTime(_))
+        | (token::ValueType::DateTimeTZ, Constant::DateTimeTZ(_))
+        | (token::ValueType::Decimal, Constant::Decimal(_))
+        | (token::ValueType::Duration, Constant::Duration(_)) => Some(value.clone()),
+        (token::ValueType::Double, Constant::Long(long)) => Some(Constant::Double(*long as f64)),
+        (token::ValueType::Decimal, Constant::Long(long)) => Some(Constant::Decimal(Decimal::from(*long))),
+        (token::ValueType::DateTime, Constant::Date(date)) => Some(Constant::DateTime((*date).into())),
+        _ => None,
+    }
+}
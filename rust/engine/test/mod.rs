@@ -0,0 +1,671 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use serde_json::json;
+
+use crate::{
+    engine::{next_page, Concept, ConceptMap, Database, PipelineAnswers},
+    parse_query,
+    pattern::{Constant, Label},
+};
+
+const SCHEMA: &str = r#"define
+name sub attribute, value string;
+age sub attribute, value long;
+salary sub attribute, value double;
+person sub entity, owns name, owns age, plays employment:employee, plays friendship:friend;
+student sub person;
+company sub entity, owns name, plays employment:employer;
+employment sub relation, relates employee, relates employer, owns salary;
+friendship sub relation, relates friend;"#;
+
+const DATA: &str = r#"insert
+$alice isa person, has name "Alice", has age 30;
+$bob isa student, has name "Bob", has age 20;
+$carol isa person, has name "Carol", has age 40;
+$acme isa company, has name "Acme";
+(employee: $alice, employer: $acme) isa employment, has salary 1000.0;
+(employee: $carol, employer: $acme) isa employment, has salary 3000.0;
+(friend: $alice, friend: $bob) isa friendship;"#;
+
+fn database() -> Database {
+    let mut database = Database::new();
+    database.define(&parse_query(SCHEMA).unwrap().into_define()).unwrap();
+    database.insert(&parse_query(DATA).unwrap().into_insert()).unwrap();
+    database
+}
+
+fn get(database: &Database, query: &str) -> Vec<ConceptMap> {
+    database.get(&parse_query(query).unwrap().into_get()).unwrap()
+}
+
+fn names(answers: &[ConceptMap], variable: &str) -> Vec<String> {
+    answers.iter().map(|answer| answer.get(variable).unwrap().value().unwrap().to_string()).collect()
+}
+
+#[test]
+fn get_honours_isa_and_explicit_isa() {
+    let database = database();
+    let query = r#"match $p isa person, has name $n; get $n; sort $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Alice""#, r#""Bob""#, r#""Carol""#]);
+
+    let query = r#"match $p isa! person, has name $n; get $n; sort $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Alice""#, r#""Carol""#]);
+
+    let query = r#"match $p isa $t, has name "Bob"; get $t;"#;
+    let types: Vec<Concept> = get(&database, query).iter().map(|answer| answer.get("t").unwrap().clone()).collect();
+    assert!(types.contains(&Concept::Type(Label::from("student"))));
+    assert!(types.contains(&Concept::Type(Label::from("person"))));
+}
+
+#[test]
+fn get_honours_role_players_and_predicates() {
+    let database = database();
+    let query = r#"match
+$c isa company, has name "Acme";
+(employee: $p, employer: $c) isa employment, has salary $s;
+$s > 2000.0;
+$p has name $n;
+get $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Carol""#]);
+
+    let query = r#"match ($x, $y) isa friendship; $x has name $n; get $n; sort $n desc;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Bob""#, r#""Alice""#]);
+
+    let query = r#"match $p isa person, has name $n; $n contains "aro"; get $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Carol""#]);
+}
+
+#[test]
+fn get_honours_disjunctions_and_negations() {
+    let database = database();
+    let query = r#"match
+$p isa person, has name $n;
+{ $p has age 20; } or { $p has age 40; };
+get $n; sort $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Bob""#, r#""Carol""#]);
+
+    let query = r#"match
+$p isa person, has name $n;
+not { (employee: $p) isa employment; };
+get $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Bob""#]);
+}
+
+#[test]
+fn get_applies_modifiers() {
+    let database = database();
+    let query = r#"match $p isa person, has age $a; get $a; sort $a desc; offset 1; limit 1;"#;
+    let answers = get(&database, query);
+    assert_eq!(answers.len(), 1);
+    assert_eq!(answers[0].get("a").unwrap().value(), Some(&Constant::Long(30)));
+}
+
+#[test]
+fn cursors_page_through_sorted_answers() {
+    let database = database();
+    let query = parse_query("match $p isa person, has age $a, has name $n; get $a, $n; sort $a desc, $n; limit 2;");
+    let query = query.unwrap().into_get();
+    let first_page = database.get(&query).unwrap();
+    assert_eq!(names(&first_page, "n"), vec![r#""Carol""#, r#""Alice""#]);
+
+    let query = next_page(&query, first_page.last().unwrap()).unwrap();
+    assert_eq!(query.to_string().lines().last(), Some("sort $a desc, $n; after ($a, $n) (30, \"Alice\"); limit 2;"));
+    let second_page = database.get(&query).unwrap();
+    assert_eq!(names(&second_page, "n"), vec![r#""Bob""#]);
+    assert!(database.get(&next_page(&query, &second_page[0]).unwrap()).unwrap().is_empty());
+
+    let query = r#"match $p isa person, has name $n; get $n; sort $n; after ($n) (42);"#;
+    let error = database.get(&parse_query(query).unwrap().into_get()).unwrap_err();
+    assert!(error.to_string().contains("The cursor value '42' cannot be compared"));
+}
+
+#[test]
+fn sorting_places_unbound_variables_and_collates_strings() {
+    let mut database = database();
+    let query = r#"match
+$p isa person, has name $n;
+try { (employee: $p, employer: $c) isa employment, has salary $s; };
+get $n, $s;
+sort $s desc;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Bob""#, r#""Carol""#, r#""Alice""#]);
+    let query = query.replace("sort $s desc;", "sort $s desc nulls last;");
+    assert_eq!(names(&get(&database, &query), "n"), vec![r#""Carol""#, r#""Alice""#, r#""Bob""#]);
+
+    database.insert(&parse_query(r#"insert $d isa person, has name "alice";"#).unwrap().into_insert()).unwrap();
+    let query = r#"match $p isa person, has name $n; get $n; sort $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Alice""#, r#""Bob""#, r#""Carol""#, r#""alice""#]);
+    let query = r#"match $p isa person, has name $n; get $n; sort $n desc collate nocase;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Carol""#, r#""Bob""#, r#""Alice""#, r#""alice""#]);
+    let query = r#"match $p isa person, has name $n; get $n; sort $n desc collate nocase; after ($n) ("bob");"#;
+    assert_eq!(names(&get(&database, query), "n").len(), 2);
+}
+
+#[test]
+fn select_keeps_repeated_answers_unless_distinct() {
+    let database = database();
+    let query = r#"match (employee: $p, employer: $c) isa employment; $c has name $n; select $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Acme""#, r#""Acme""#]);
+
+    let query = r#"match (employee: $p, employer: $c) isa employment; $c has name $n; select $n; distinct;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Acme""#]);
+
+    let query = r#"match $p isa person, has age $a; ?next = $a + 1; select ?next as ?age; sort ?age desc; limit 1;"#;
+    let answers = get(&database, query);
+    assert_eq!(answers[0].get("age").unwrap().value(), Some(&Constant::Long(41)));
+    assert!(answers[0].get("next").is_none());
+}
+
+#[test]
+fn aggregates_and_groups() {
+    let mut database = database();
+    let count = parse_query("match $p isa person; get; count;").unwrap().into_get_aggregate();
+    assert_eq!(database.get_aggregate(&count).unwrap(), vec![Some(Constant::Long(3))]);
+
+    let sum = parse_query("match $p isa person, has age $a; get $a; sum $a;").unwrap().into_get_aggregate();
+    assert_eq!(database.get_aggregate(&sum).unwrap(), vec![Some(Constant::Long(90))]);
+
+    let mean = parse_query("match $e isa employment, has salary $s; get $s; mean $s;").unwrap().into_get_aggregate();
+    assert_eq!(database.get_aggregate(&mean).unwrap(), vec![Some(Constant::Double(2000.0))]);
+
+    let max = parse_query("match $p isa person, has name $n; get $n; max $n;").unwrap().into_get_aggregate();
+    assert_eq!(database.get_aggregate(&max).unwrap(), vec![Some(Constant::String("Carol".to_owned()))]);
+
+    let empty = parse_query("match $p isa person, has age $a; $a > 100; get $a; min $a;").unwrap().into_get_aggregate();
+    assert_eq!(database.get_aggregate(&empty).unwrap(), vec![None]);
+
+    let group = parse_query("match (employer: $c, employee: $p) isa employment; get $c, $p; group $c;")
+        .unwrap()
+        .into_get_group();
+    let groups = database.get_group(&group).unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].concept_maps.len(), 2);
+
+    let group_count = parse_query("match $p isa person, has name $n; get $p, $n; group $p; count;")
+        .unwrap()
+        .into_get_group_aggregate();
+    let groups = database.get_group_aggregate(&group_count).unwrap();
+    assert_eq!(groups.len(), 3);
+    assert!(groups.iter().all(|group| group.values == vec![Some(Constant::Long(1))]));
+
+    database.insert(&parse_query("insert $d isa person, has age 9223372036854775807;").unwrap().into_insert()).unwrap();
+    let error = database.get_aggregate(&sum).unwrap_err();
+    assert!(error.to_string().contains("The aggregate 'sum' of '$a' overflowed."));
+}
+
+#[test]
+fn distinct_list_positional_and_percentile_aggregates() {
+    let database = database();
+    let query = r#"match (employee: $p, employer: $c) isa employment; $p has age $a; get;
+count distinct $c; list $a; percentile 50 $a; percentile 100 $a;"#;
+    let values = database.get_aggregate(&parse_query(query).unwrap().into_get_aggregate()).unwrap();
+    assert_eq!(
+        values,
+        vec![
+            Some(Constant::Long(1)),
+            Some(Constant::List(vec![Constant::Long(30), Constant::Long(40)])),
+            Some(Constant::Double(35.0)),
+            Some(Constant::Double(40.0)),
+        ]
+    );
+
+    let query = "match $p isa person, has name $n, has age $a; get; sort $a desc; first $n; last $n;";
+    let values = database.get_aggregate(&parse_query(query).unwrap().into_get_aggregate()).unwrap();
+    assert_eq!(values, vec![Some(Constant::String("Carol".to_owned())), Some(Constant::String("Bob".to_owned()))]);
+}
+
+#[test]
+fn reduced_values_are_used_by_later_patterns_sorting_and_fetch() {
+    let database = database();
+    let query = r#"match
+(employee: $p, employer: $c) isa employment, has salary $s;
+reduce ?total = sum($s) within $c;
+reduce ?employees = count();
+?share = $s / ?total;
+?share > 0.5;
+$p has name $n;
+get $n, ?share, ?employees;
+sort ?share;"#;
+    let answers = get(&database, query);
+    assert_eq!(names(&answers, "n"), vec![r#""Carol""#]);
+    assert_eq!(answers[0].get("share").unwrap().value(), Some(&Constant::Double(0.75)));
+    assert_eq!(answers[0].get("employees").unwrap().value(), Some(&Constant::Long(2)));
+
+    let query = r#"match $p isa person, has age $a; reduce ?oldest = max($a); $a == ?oldest; fetch $p: name; ?oldest;"#;
+    let documents = database.fetch(&parse_query(query).unwrap().into_fetch()).unwrap();
+    assert_eq!(documents.len(), 1);
+    assert_eq!(documents[0]["p"]["name"][0]["value"], "Carol");
+    assert_eq!(documents[0]["oldest"]["value"], 40);
+}
+
+#[test]
+fn groups_by_several_variables_with_several_aggregates() {
+    let database = database();
+    let query = r#"match
+$p isa person, has age $a;
+{ $p has name "Alice"; } or { $p has name "Carol"; };
+(employee: $p, employer: $c) isa employment, has salary $s;
+$c has name $n;
+get $n, $c, $a, $s;
+group $c, $n;
+count; sum $a; max $s;"#;
+    let groups = database.get_group_aggregate(&parse_query(query).unwrap().into_get_group_aggregate()).unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].owners.len(), 2);
+    assert_eq!(groups[0].owners[1].value(), Some(&Constant::String("Acme".to_owned())));
+    assert_eq!(
+        groups[0].values,
+        vec![Some(Constant::Long(2)), Some(Constant::Long(70)), Some(Constant::Double(3000.0))]
+    );
+
+    let query = "match $p isa person, has age $a; get $a; count; min $a; max $a;";
+    let values = database.get_aggregate(&parse_query(query).unwrap().into_get_aggregate()).unwrap();
+    assert_eq!(values, vec![Some(Constant::Long(3)), Some(Constant::Long(20)), Some(Constant::Long(40))]);
+}
+
+#[test]
+fn optional_patterns_leave_variables_unbound() {
+    let database = database();
+    let answers = get(&database, "match $p isa person, has name $n; try { $e (employee: $p) isa employment; }; get;");
+    assert_eq!(answers.len(), 3);
+    let unemployed: Vec<ConceptMap> = answers.into_iter().filter(|answer| answer.get("e").is_none()).collect();
+    assert_eq!(names(&unemployed, "n"), vec![r#""Bob""#]);
+
+    let query = r#"match
+$p isa person, has name $n;
+try { (employee: $p, employer: $c) isa employment, has salary $s; };
+fetch $n; $s;
+sort $n;"#;
+    let answers = database.fetch(&parse_query(query).unwrap().into_fetch()).unwrap();
+    let salaries: Vec<&serde_json::Value> = answers.iter().map(|answer| &answer["s"]).collect();
+    assert_eq!(salaries[1], &serde_json::Value::Null);
+    assert_eq!(salaries[2]["value"], json!(3000.0));
+
+    let query = r#"match
+$p isa person, has name $n;
+try { (employee: $p, employer: $c) isa employment; };
+$c has name $cn;
+get $n, $cn; sort $n;"#;
+    let answers = get(&database, query);
+    assert_eq!(names(&answers, "n"), vec![r#""Alice""#, r#""Carol""#]);
+    assert_eq!(names(&answers, "cn"), vec![r#""Acme""#, r#""Acme""#]);
+
+    let query = r#"match
+$p isa person, has name $n;
+try { $e (employee: $p) isa employment; };
+$e has salary $s;
+get $n, $s; sort $n;"#;
+    let answers = get(&database, query);
+    assert_eq!(names(&answers, "n"), vec![r#""Alice""#, r#""Carol""#]);
+    assert_eq!(names(&answers, "s"), vec!["1000.0", "3000.0"]);
+}
+
+#[test]
+fn delete_and_update() {
+    let mut database = database();
+    let delete = parse_query(r#"match $p isa person, has name "Carol"; delete $p isa person;"#).unwrap().into_delete();
+    database.delete(&delete).unwrap();
+    assert_eq!(get(&database, "match $p isa person; get;").len(), 2);
+    assert_eq!(get(&database, "match $e (employee: $p) isa employment; get;").len(), 1);
+
+    let update = parse_query(
+        r#"match $p isa person, has name "Bob", has age $a;
+delete $p has $a;
+insert $p has age 21;"#,
+    )
+    .unwrap()
+    .into_update();
+    database.update(&update).unwrap();
+    let answers = get(&database, r#"match $p isa person, has name "Bob", has age $a; get $a;"#);
+    assert_eq!(answers.len(), 1);
+    assert_eq!(answers[0].get("a").unwrap().value(), Some(&Constant::Long(21)));
+
+    let delete = parse_query(r#"match $f (friend: $x) isa friendship; $x has name "Bob"; delete $f (friend: $x);"#)
+        .unwrap()
+        .into_delete();
+    database.delete(&delete).unwrap();
+    let answers = get(&database, r#"match $f (friend: $x) isa friendship; get $x;"#);
+    assert_eq!(answers.len(), 1);
+}
+
+#[test]
+fn insert_is_checked_against_schema() {
+    let mut database = database();
+    let insert = parse_query(r#"insert $c isa company, has age 10;"#).unwrap().into_insert();
+    let error = database.insert(&insert).unwrap_err().to_string();
+    assert!(error.contains("Instances of type 'company' are not allowed to own attributes of type 'age'."));
+
+    let insert = parse_query(r#"insert $x isa entity;"#).unwrap().into_insert();
+    assert!(database.insert(&insert).unwrap_err().to_string().contains("abstract type 'entity'"));
+
+    let insert = parse_query(r#"insert $x isa animal;"#).unwrap().into_insert();
+    assert!(database.insert(&insert).unwrap_err().to_string().contains("The type 'animal' has not been defined."));
+
+    let insert = parse_query(r#"insert $p isa person, has age "old";"#).unwrap().into_insert();
+    assert!(database.insert(&insert).unwrap_err().to_string().contains("of value type 'long'"));
+
+    let insert = parse_query(r#"match $c isa company; insert (friend: $c) isa friendship;"#).unwrap().into_insert();
+    let error = database.insert(&insert).unwrap_err().to_string();
+    assert!(error.contains("Instances of type 'company' are not allowed to play the role 'friendship:friend'."));
+    assert_eq!(get(&database, "match $f isa friendship; get;").len(), 1);
+}
+
+#[test]
+fn temporal_and_decimal_values_are_coerced_and_compared() {
+    let mut database = database();
+    let define = r#"define
+budget sub attribute, value decimal;
+founded sub attribute, value datetime;
+term sub attribute, value duration;
+company owns budget, owns founded, owns term;"#;
+    database.define(&parse_query(define).unwrap().into_define()).unwrap();
+    let insert = r#"match $c isa company; insert $c has budget 100, has founded 2020-01-01, has term P1Y;"#;
+    database.insert(&parse_query(insert).unwrap().into_insert()).unwrap();
+
+    let query = r#"match $c has budget $b; $b > 99.5dec; get $b;"#;
+    assert_eq!(names(&get(&database, query), "b"), vec!["100dec"]);
+    let query = r#"match $c has founded $f; $f < 2020-01-01T12:00; get $f;"#;
+    assert_eq!(names(&get(&database, query), "f"), vec!["2020-01-01T00:00"]);
+    assert_eq!(get(&database, "match $c has founded 2020-01-01; get;").len(), 1);
+    assert_eq!(get(&database, "match $c has founded $f; $f == 2020-01-01T00:00; get;").len(), 1);
+    assert_eq!(get(&database, "match $c has term P12M; get;").len(), 1);
+
+    let insert = parse_query(r#"match $c isa company; insert $c has term 2020-01-01;"#).unwrap().into_insert();
+    assert!(database.insert(&insert).unwrap_err().to_string().contains("of value type 'duration'"));
+
+    let insert = r#"match $c isa company; insert $c has budget 0.1dec, has budget 0.2dec;"#;
+    database.insert(&parse_query(insert).unwrap().into_insert()).unwrap();
+    let query = r#"match $c has budget $b; $b < 1; ?x = $b * 2 + 0.1dec; get ?x; sort ?x;"#;
+    assert_eq!(names(&get(&database, query), "x"), vec!["0.3dec", "0.5dec"]);
+    let aggregate = |method: &str| {
+        let query = format!("match $c has budget $b; get $b; {method} $b;");
+        let answer = database.get_aggregate(&parse_query(&query).unwrap().into_get_aggregate()).unwrap();
+        answer[0].as_ref().unwrap().to_string()
+    };
+    assert_eq!(aggregate("sum"), "100.3dec");
+    assert_eq!(aggregate("mean"), "33.4333333333333333333dec");
+    assert_eq!(aggregate("median"), "0.2dec");
+}
+
+#[test]
+fn list_values_support_membership_and_indexing() {
+    let mut database = database();
+    let define = r#"define
+nickname sub attribute, value string;
+scores sub attribute, value long[];
+person owns nickname[], owns scores;
+company owns nickname;"#;
+    database.define(&parse_query(define).unwrap().into_define()).unwrap();
+    let insert = r#"match $p isa person, has name "Alice";
+insert $p has nickname ["Al", "Ally"], has scores [3, 1, 4];"#;
+    database.insert(&parse_query(insert).unwrap().into_insert()).unwrap();
+
+    let query = r#"match $p has name $n, has nickname contains "Ally"; get $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Alice""#]);
+    let query = r#"match $p has name $n, has scores $s; $s[-1] > 3; $s[0] == 3; get $s;"#;
+    assert_eq!(names(&get(&database, query), "s"), vec!["[3, 1, 4]"]);
+    assert!(get(&database, r#"match $p has scores[5] == 1; get;"#).is_empty());
+
+    let fetched = database.fetch(&parse_query("match $p has scores $s; fetch $s;").unwrap().into_fetch()).unwrap();
+    assert_eq!(
+        fetched,
+        vec![json!({ "s": { "value": [3, 1, 4], "value_type": "long[]", "type": {
+        "label": "scores", "root": "attribute" } } })]
+    );
+
+    let insert = r#"match $c isa company; insert $c has nickname ["Acme Corp"];"#;
+    let error = database.insert(&parse_query(insert).unwrap().into_insert()).unwrap_err().to_string();
+    assert!(
+        error.contains("'company' can only own a list of 'nickname' values if they are declared to own 'nickname[]'")
+    );
+}
+
+#[test]
+fn struct_values_are_validated_matched_and_fetched() {
+    let mut database = database();
+    let define = r#"define
+struct coordinates: latitude value double, longitude value double;
+struct address: street value string, postcode value string?, location value coordinates?;
+home-address sub attribute, value address;
+person owns home-address;"#;
+    database.define(&parse_query(define).unwrap().into_define()).unwrap();
+    let insert = r#"match $p isa person, has name "Alice";
+insert $p has home-address {location: {longitude: -0.1, latitude: 51}, street: "Main Street"};"#;
+    database.insert(&parse_query(insert).unwrap().into_insert()).unwrap();
+
+    let query = r#"match $p has name $n, has home-address.location.latitude > 50; get $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Alice""#]);
+    let query = r#"match $a isa home-address; $a.street contains "main"; get $a;"#;
+    assert_eq!(
+        names(&get(&database, query), "a"),
+        vec![r#"{street: "Main Street", location: {latitude: 51.0, longitude: -0.1}}"#]
+    );
+    assert!(get(&database, r#"match $a isa home-address; $a.postcode == "N1"; get;"#).is_empty());
+    let query = parse_query(r#"match $a isa home-address; $a.city == "London"; get;"#).unwrap().into_get();
+    let error = database.get(&query).unwrap_err().to_string();
+    assert!(error.contains("The field 'city' is not defined in the struct 'address'."));
+
+    let query = "match $a isa home-address; fetch $a.street; $a.postcode as postcode; $a.location.latitude;";
+    let fetched = database.fetch(&parse_query(query).unwrap().into_fetch()).unwrap();
+    assert_eq!(
+        fetched,
+        vec![json!({
+            "a.street": { "value": "Main Street", "value_type": "string" },
+            "postcode": null,
+            "a.location.latitude": { "value": 51.0, "value_type": "double" }
+        })]
+    );
+
+    for (address, message) in [
+        (r#"{postcode: "N1"}"#, "does not provide the field 'street', which is required by the struct 'address'"),
+        (r#"{street: "High Street", city: "London"}"#, "The field 'city' is not defined in the struct 'address'."),
+        (r#"{street: 5}"#, "cannot be assigned to the attribute type 'home-address' of value type 'address'"),
+    ] {
+        let insert = format!(r#"match $p isa person, has name "Alice"; insert $p has home-address {address};"#);
+        let error = database.insert(&parse_query(&insert).unwrap().into_insert()).unwrap_err().to_string();
+        assert!(error.contains(message), "{error}");
+    }
+
+    let define = parse_query("define postcode sub attribute, value strnig;").unwrap().into_define();
+    let error = database.define(&define).unwrap_err().to_string();
+    assert!(error.contains("The struct 'strnig' has not been defined."));
+}
+
+#[test]
+fn value_restrictions_are_checked_against_defined_value_types() {
+    let mut database = database();
+    let define = r#"define person owns name @values("Alice", "Bob", "Carol");"#;
+    assert!(database.define(&parse_query(define).unwrap().into_define()).is_ok());
+
+    let define = r#"define company owns name @range(1..10);"#;
+    let error = database.define(&parse_query(define).unwrap().into_define()).unwrap_err();
+    assert!(error.to_string().contains("The annotation '@range(1..10)' cannot restrict values of type 'string'."));
+}
+
+#[test]
+fn fetch_produces_json_documents() {
+    let database = database();
+    let query = r#"match
+$p isa person, has name $n;
+$n "Alice";
+fetch
+$n as name;
+$p as person: name, age as "years";
+employers: {
+    match
+    (employee: $p, employer: $c) isa employment, has salary $s;
+    fetch
+    $c: name;
+    $s;
+};
+friends: {
+    match
+    (friend: $p, friend: $f) isa friendship;
+    get $f;
+    count;
+};"#;
+    let answers = database.fetch(&parse_query(query).unwrap().into_fetch()).unwrap();
+    let name = json!({ "value": "Alice", "value_type": "string", "type": { "label": "name", "root": "attribute" } });
+    assert_eq!(
+        answers,
+        vec![json!({
+            "name": name,
+            "person": {
+                "type": { "label": "person", "root": "entity" },
+                "name": [name],
+                "years": [{ "value": 30, "value_type": "long", "type": { "label": "age", "root": "attribute" } }],
+            },
+            "employers": [{
+                "c": {
+                    "type": { "label": "company", "root": "entity" },
+                    "name": [{ "value": "Acme", "value_type": "string", "type": { "label": "name", "root": "attribute" } }],
+                },
+                "s": { "value": 1000.0, "value_type": "double", "type": { "label": "salary", "root": "attribute" } },
+            }],
+            "friends": { "value": 1, "value_type": "long" },
+        })]
+    );
+
+    let query = r#"match $p isa person, has age $a; fetch $p: name; sort $a desc; limit 2;"#;
+    let answers = database.fetch(&parse_query(query).unwrap().into_fetch()).unwrap();
+    let names: Vec<&serde_json::Value> = answers.iter().map(|answer| &answer["p"]["name"][0]["value"]).collect();
+    assert_eq!(names, vec!["Carol", "Alice"]);
+}
+
+#[test]
+fn pipeline_stages_apply_in_order() {
+    let mut database = database();
+    let query = r#"match $p isa person, has name $n;
+sort $n desc;
+limit 2;
+insert $p has age 50;
+match $p has age $a; $a 50;
+fetch $p: name;"#;
+    let answers = database.pipeline(&parse_query(query).unwrap().into_pipeline()).unwrap();
+    let PipelineAnswers::Documents(documents) = answers else { panic!("expected documents, found {answers:?}") };
+    let names: Vec<&serde_json::Value> = documents.iter().map(|document| &document["p"]["name"][0]["value"]).collect();
+    assert_eq!(names, vec!["Carol", "Bob"]);
+    assert_eq!(get(&database, "match $p isa person, has age 50; get;").len(), 2);
+}
+
+#[test]
+fn schema_functions_are_called_from_patterns_and_expressions() {
+    let mut database = database();
+    let functions = r#"define
+fun friends($p: person) -> person:
+match
+($p, $f) isa friendship;
+return $f;
+fun age-in-days($p: person) -> long:
+match
+$p has age $a;
+?d = $a * 365;
+return ?d;"#;
+    database.define(&parse_query(functions).unwrap().into_define()).unwrap();
+
+    let query = r#"match $p has name "Alice"; $f in friends($p); $f has name $n; get $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Bob""#]);
+
+    let query = r#"match $p isa person, has name $n; ?d = age-in-days($p); ?d > 10000; get $n; sort $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""Alice""#, r#""Carol""#]);
+
+    let query = parse_query("match $p isa person; $f in enemies($p); get;").unwrap().into_get();
+    assert!(database.get(&query).unwrap_err().to_string().contains("'enemies' has not been defined"));
+
+    let ping = "define fun ping($p: person) -> person: match $f in pong($p); return $f;";
+    database.define(&parse_query(ping).unwrap().into_define()).unwrap();
+    let pong = "define fun pong($p: person) -> person: match $f in ping($p); return $f;";
+    let error = database.define(&parse_query(pong).unwrap().into_define()).unwrap_err().to_string();
+    assert!(error.contains("Function 'pong' calls itself"));
+}
+
+#[test]
+fn reasoning_materialises_rules_to_fixpoint() {
+    let schema = r#"define
+name sub attribute, value string;
+status sub attribute, value string;
+node sub entity, owns name, owns status, plays edge:from, plays edge:to, plays reachable:from, plays reachable:to;
+edge sub relation, relates from, relates to;
+reachable sub relation, relates from, relates to;
+rule direct-reachability: when {
+    (from: $x, to: $y) isa edge;
+} then {
+    (from: $x, to: $y) isa reachable;
+};
+rule transitive-reachability: when {
+    (from: $x, to: $y) isa reachable;
+    (from: $y, to: $z) isa edge;
+} then {
+    (from: $x, to: $z) isa reachable;
+};
+rule isolated: when {
+    $x isa node;
+    not { (from: $x, to: $y) isa reachable; };
+    not { (from: $y, to: $x) isa reachable; };
+} then {
+    $x has status "isolated";
+};"#;
+    let data = r#"insert
+$a isa node, has name "a";
+$b isa node, has name "b";
+$c isa node, has name "c";
+$d isa node, has name "d";
+(from: $a, to: $b) isa edge;
+(from: $b, to: $c) isa edge;"#;
+    let mut database = Database::new();
+    database.define(&parse_query(schema).unwrap().into_define()).unwrap();
+    database.insert(&parse_query(data).unwrap().into_insert()).unwrap();
+
+    let explanations = database.reason().unwrap();
+    assert_eq!(explanations.len(), 4);
+    assert!(database.reason().unwrap().is_empty());
+
+    let query = r#"match
+(from: $x, to: $y) isa reachable;
+$x has name $n;
+$y has name "c";
+get $n; sort $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""a""#, r#""b""#]);
+
+    let query = r#"match $x has status "isolated", has name $n; get $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""d""#]);
+
+    let transitive = explanations.iter().find(|explanation| explanation.rule == Label::from("transitive-reachability"));
+    let explanation = database.explain(&transitive.unwrap().inferred).unwrap();
+    assert_eq!(explanation.bindings.get("z").unwrap().as_thing().unwrap().type_, Label::from("node"));
+    assert_eq!(explanation.bindings.len(), 3);
+}
+
+#[test]
+fn reasoning_joins_recursive_rules_against_new_facts() {
+    let schema = r#"define
+name sub attribute, value string;
+status sub attribute, value string;
+node sub entity, owns name, owns status, plays edge:from, plays edge:to;
+edge sub relation, relates from, relates to;
+rule spread: when {
+    $x has status "infected";
+    (from: $x, to: $y) isa edge;
+} then {
+    $y has status "infected";
+};"#;
+    let data = r#"insert
+$a isa node, has name "a", has status "infected";
+$b isa node, has name "b";
+$c isa node, has name "c";
+$d isa node, has name "d";
+$e isa node, has name "e";
+(from: $a, to: $b) isa edge;
+(from: $b, to: $c) isa edge;
+(from: $c, to: $d) isa edge;"#;
+    let mut database = Database::new();
+    database.define(&parse_query(schema).unwrap().into_define()).unwrap();
+    database.insert(&parse_query(data).unwrap().into_insert()).unwrap();
+
+    assert_eq!(database.reason().unwrap().len(), 3);
+    let query = r#"match $x has status "infected", has name $n; get $n; sort $n;"#;
+    assert_eq!(names(&get(&database, query), "n"), vec![r#""a""#, r#""b""#, r#""c""#, r#""d""#]);
+}
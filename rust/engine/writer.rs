@@ -0,0 +1,264 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::HashMap;
+
+use crate::{
+    common::{error::TypeQLError, token, Result},
+    engine::{
+        answer::{Concept, ConceptMap, ThingId},
+        matcher::{bind_concept, compare, concept_of},
+        Database,
+    },
+    pattern::{Constant, HasConstraint, Pattern, Predicate, ThingStatement, Value, ValueType},
+    variable::{ConceptVariable, TypeReference, Variable},
+    Label,
+};
+
+impl Database {
+    pub(crate) fn insert_statements(
+        &mut self,
+        statements: &[ThingStatement],
+        answer: ConceptMap,
+    ) -> Result<ConceptMap> {
+        let mut answer = answer;
+        let mut inserted: HashMap<usize, ThingId> = HashMap::new();
+        for (i, statement) in statements.iter().enumerate() {
+            if self.bound_thing(&answer, &statement.variable).is_some() {
+                continue;
+            }
+            let Some(isa) = &statement.isa else { continue };
+            let label = self.type_of(&isa.type_reference, &answer)?;
+            let info = self.schema.get(&label)?;
+            if info.is_abstract {
+                Err(TypeQLError::InvalidInsertAbstractType { label: label.clone() })?
+            }
+            let value = if self.schema.is_kind(&label, token::Type::Attribute) {
+                let value = statement.predicate.as_ref().and_then(|predicate| self.inserted_value(predicate, &answer));
+                match value {
+                    Some(value) => Some(self.schema.coerce_value(&label, &value)?),
+                    None => Err(TypeQLError::InvalidInsertAttributeValue { statement: statement.clone() })?,
+                }
+            } else {
+                None
+            };
+            let iid = self.put_thing(label, value);
+            inserted.insert(i, iid);
+            if let Some(bound) = bind_concept(answer.clone(), &statement.variable, Concept::Thing(self.thing(iid))) {
+                answer = bound;
+            }
+        }
+
+        for (i, statement) in statements.iter().enumerate() {
+            let owner = match inserted.get(&i) {
+                Some(iid) => *iid,
+                None => match self.bound_thing(&answer, &statement.variable) {
+                    Some(iid) => iid,
+                    None => Err(TypeQLError::InvalidInsertUnboundThing { statement: statement.clone() })?,
+                },
+            };
+            for has in &statement.has {
+                let attribute = self.inserted_attribute(has, &answer, statement)?;
+                self.put_ownership(owner, attribute)?;
+            }
+            if let Some(relation) = &statement.relation {
+                for role_player in &relation.role_players {
+                    let role = match &role_player.role_type {
+                        Some(reference) => self.type_of(reference, &answer)?,
+                        None => Err(TypeQLError::InvalidInsertRoleType { statement: statement.clone() })?,
+                    };
+                    let player = match self.bound_thing(&answer, &role_player.player) {
+                        Some(player) => player,
+                        None => Err(TypeQLError::InvalidInsertUnboundThing { statement: statement.clone() })?,
+                    };
+                    self.put_role_player(owner, &role, player)?;
+                }
+            }
+            let data = &self.things[&owner];
+            if self.schema.is_kind(&data.type_, token::Type::Relation) && data.role_players.is_empty() {
+                Err(TypeQLError::MissingConstraintRelationPlayer)?
+            }
+        }
+        Ok(answer)
+    }
+
+    pub(crate) fn put_statements(&mut self, statements: &[ThingStatement], answer: ConceptMap) -> Result<ConceptMap> {
+        let patterns: Vec<Pattern> = statements.iter().cloned().map(Pattern::from).collect();
+        match self.match_patterns(patterns.iter().collect(), answer.clone())?.into_iter().next() {
+            Some(existing) => Ok(existing),
+            None => self.insert_statements(statements, answer),
+        }
+    }
+
+    pub(crate) fn delete_statements(&mut self, statements: &[ThingStatement], answer: &ConceptMap) -> Result {
+        for statement in statements {
+            let Some(owner) = self.bound_thing(answer, &statement.variable) else { continue };
+            for has in &statement.has {
+                let owned = self.things[&owner].has.clone();
+                let deleted: Vec<ThingId> = match has {
+                    HasConstraint::HasConcept(_, variable) => self.bound_thing(answer, variable).into_iter().collect(),
+                    HasConstraint::HasValue(label, variable) => {
+                        let value = answer.get_variable(&Variable::Value(variable.clone())).and_then(Concept::value);
+                        owned
+                            .into_iter()
+                            .filter(|attribute| {
+                                let data = &self.things[attribute];
+                                self.schema.is_subtype(&data.type_, label)
+                                    && value.is_some_and(|value| {
+                                        data.value
+                                            .as_ref()
+                                            .is_some_and(|owned| compare(token::Predicate::Eq, owned, value))
+                                    })
+                            })
+                            .collect()
+                    }
+                    HasConstraint::HasPredicate(label, predicate) => owned
+                        .into_iter()
+                        .filter(|attribute| {
+                            let data = &self.things[attribute];
+                            self.schema.is_subtype(&data.type_, label)
+                                && data.value.as_ref().is_some_and(|value| self.satisfies(value, predicate, answer))
+                        })
+                        .collect(),
+                };
+                self.things.get_mut(&owner).unwrap().has.retain(|attribute| !deleted.contains(attribute));
+            }
+            if let Some(relation) = &statement.relation {
+                for role_player in &relation.role_players {
+                    let Some(player) = self.bound_thing(answer, &role_player.player) else { continue };
+                    let role = match &role_player.role_type {
+                        Some(reference) => Some(self.type_of(reference, answer)?),
+                        None => None,
+                    };
+                    let role_players = &mut self.things.get_mut(&owner).unwrap().role_players;
+                    let position = role_players.iter().position(|(played, iid)| {
+                        *iid == player && role.as_ref().map_or(true, |role| self.schema.role_matches(played, role))
+                    });
+                    if let Some(position) = position {
+                        role_players.remove(position);
+                    }
+                }
+                if self.things[&owner].role_players.is_empty() {
+                    self.delete_thing(owner);
+                }
+            }
+            if let Some(isa) = &statement.isa {
+                let label = self.type_of(&isa.type_reference, answer)?;
+                if self.things.get(&owner).is_some_and(|data| self.schema.is_subtype(&data.type_, &label)) {
+                    self.delete_thing(owner);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn delete_thing(&mut self, iid: ThingId) {
+        if self.things.remove(&iid).is_none() {
+            return;
+        }
+        let mut emptied = Vec::new();
+        for (relation, data) in self.things.iter_mut() {
+            data.has.retain(|attribute| *attribute != iid);
+            let players = data.role_players.len();
+            data.role_players.retain(|(_, player)| *player != iid);
+            if players > 0 && data.role_players.is_empty() {
+                emptied.push(*relation);
+            }
+        }
+        emptied.into_iter().for_each(|relation| self.delete_thing(relation));
+    }
+
+    pub(crate) fn put_ownership(&mut self, owner: ThingId, attribute: ThingId) -> Result {
+        let (owner_type, attribute_type) = (&self.things[&owner].type_, &self.things[&attribute].type_);
+        if !self.schema.owns(owner_type, attribute_type) {
+            Err(TypeQLError::InvalidOwnership { owner: owner_type.clone(), attribute: attribute_type.clone() })?
+        }
+        let is_list_value = matches!(self.things[&attribute].value, Some(Constant::List(_)));
+        let is_list_type = matches!(self.schema.value_type(attribute_type), Some(ValueType::Builtin(value_type)) if value_type.element_type().is_some());
+        if is_list_value && !is_list_type && !self.schema.owns_list(owner_type, attribute_type) {
+            Err(TypeQLError::InvalidListOwnership { owner: owner_type.clone(), attribute: attribute_type.clone() })?
+        }
+        let has = &mut self.things.get_mut(&owner).unwrap().has;
+        if !has.contains(&attribute) {
+            has.push(attribute);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn put_role_player(&mut self, relation: ThingId, role: &Label, player: ThingId) -> Result {
+        let relation_type = &self.things[&relation].type_;
+        let Some(role) = self.schema.resolve_role(relation_type, role) else {
+            Err(TypeQLError::InvalidRelationRole { relation: relation_type.clone(), role: role.clone() })?
+        };
+        let player_type = &self.things[&player].type_;
+        if !self.schema.plays(player_type, &role) {
+            Err(TypeQLError::InvalidRolePlayer { player: player_type.clone(), role: role.clone() })?
+        }
+        self.things.get_mut(&relation).unwrap().role_players.push((role, player));
+        Ok(())
+    }
+
+    pub(crate) fn inserted_attribute(
+        &mut self,
+        has: &HasConstraint,
+        answer: &ConceptMap,
+        statement: &ThingStatement,
+    ) -> Result<ThingId> {
+        let (label, value) = match has {
+            HasConstraint::HasConcept(_, variable) => {
+                return match self.bound_thing(answer, variable) {
+                    Some(attribute) if self.things[&attribute].value.is_some() => Ok(attribute),
+                    _ => Err(TypeQLError::InvalidInsertUnboundThing { statement: statement.clone() })?,
+                };
+            }
+            HasConstraint::HasValue(label, variable) => {
+                (label, answer.get_variable(&Variable::Value(variable.clone())).and_then(Concept::value).cloned())
+            }
+            HasConstraint::HasPredicate(label, predicate) => (label, self.inserted_value(predicate, answer)),
+        };
+        let info = self.schema.get(label)?;
+        if info.is_abstract {
+            Err(TypeQLError::InvalidInsertAbstractType { label: label.clone() })?
+        }
+        match value {
+            Some(value) => {
+                let value = self.schema.coerce_value(label, &value)?;
+                Ok(self.put_thing(label.clone(), Some(value)))
+            }
+            None => Err(TypeQLError::InvalidInsertAttributeValue { statement: statement.clone() })?,
+        }
+    }
+
+    fn inserted_value(&self, predicate: &Predicate, answer: &ConceptMap) -> Option<Constant> {
+        if predicate.predicate != token::Predicate::Eq || !predicate.fields.is_empty() || predicate.index.is_some() {
+            return None;
+        }
+        match &predicate.value {
+            Value::Constant(constant) => Some(constant.clone()),
+            Value::ThingVariable(variable) => concept_of(answer, variable).and_then(Concept::value).cloned(),
+            Value::ValueVariable(variable) => {
+                answer.get_variable(&Variable::Value(variable.clone())).and_then(Concept::value).cloned()
+            }
+        }
+    }
+
+    pub(crate) fn type_of(&self, reference: &TypeReference, answer: &ConceptMap) -> Result<Label> {
+        match reference {
+            TypeReference::Label(label) => Ok(label.clone()),
+            TypeReference::Variable(variable) => match concept_of(answer, variable) {
+                Some(Concept::Type(label)) => Ok(label.clone()),
+                _ => Err(TypeQLError::TypeVariableNotBound { variable: variable.clone() })?,
+            },
+        }
+    }
+
+    pub(crate) fn bound_thing(&self, answer: &ConceptMap, variable: &ConceptVariable) -> Option<ThingId> {
+        match concept_of(answer, variable) {
+            Some(Concept::Thing(thing)) if self.things.contains_key(&thing.iid) => Some(thing.iid),
+            _ => None,
+        }
+    }
+}
@@ -4,7 +4,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
 use pest::{
     pratt_parser::{Assoc, Op, PrattParser},
     Parser,
@@ -13,7 +13,9 @@ use pest_derive::Parser;
 
 use crate::{
     common::{
-        date_time,
+        date_time::{self, DateTimePrecision},
+        decimal::{self, Decimal},
+        duration::{self, Duration},
         error::{syntax_error, TypeQLError, TypeQLError::IllegalGrammar},
         string::{unescape_regex, unquote},
         token,
@@ -23,17 +25,19 @@ use crate::{
     },
     parser::Rule::clause_undefine,
     pattern::{
-        Annotation, ConceptStatement, ConceptStatementBuilder, Conjunction, Constant, Definable, Disjunction,
-        Expression, Function, HasConstraint, IsaConstraint, Label, Negation, Operation, OwnsConstraint, Pattern,
-        PlaysConstraint, Predicate, RelatesConstraint, RelationConstraint, RolePlayerConstraint, RuleLabel, Statement,
-        SubConstraint, ThingStatement, ThingStatementBuilder, TypeStatement, TypeStatementBuilder, Value,
-        ValueStatement, ValueStatementBuilder,
+        Annotation, Cardinality, ConceptStatement, ConceptStatementBuilder, Conjunction, Constant, Definable,
+        Disjunction, Expression, Function, FunctionArgument, FunctionCall, FunctionLabel, FunctionType, HasConstraint,
+        IsaConstraint, Label, Negation, Operation, Optional, OwnsConstraint, Pattern, PlaysConstraint, Predicate,
+        Range, ReduceConstraint, RelatesConstraint, RelationConstraint, RolePlayerConstraint, RuleLabel,
+        SchemaFunction, Statement, Struct, SubConstraint, ThingStatement, ThingStatementBuilder, TypeStatement,
+        TypeStatementBuilder, Value, ValueStatement, ValueStatementBuilder, ValueType, ValueTypeConstraint,
     },
     query::{
-        modifier::{sorting, Modifiers, Sorting},
-        AggregateQueryBuilder, Filter, Limit, MatchClause, Offset, Projection, ProjectionAttribute, ProjectionKeyLabel,
-        ProjectionKeyVar, ProjectionSubquery, Query, TypeQLDefine, TypeQLDelete, TypeQLFetch, TypeQLGet,
-        TypeQLGetAggregate, TypeQLGetGroup, TypeQLGetGroupAggregate, TypeQLInsert, TypeQLUndefine, TypeQLUpdate,
+        modifier::{sorting, Cursor, Distinct, Modifiers, Sorting},
+        AggregateQueryBuilder, Aggregation, Filter, Limit, MatchClause, Offset, Projection, ProjectionAttribute,
+        ProjectionKeyLabel, ProjectionKeyVar, ProjectionSubquery, Query, SelectVariable, Selection, Stage,
+        TypeQLDefine, TypeQLDelete, TypeQLFetch, TypeQLGet, TypeQLGetAggregate, TypeQLGetGroup,
+        TypeQLGetGroupAggregate, TypeQLInsert, TypeQLPipeline, TypeQLPut, TypeQLUndefine, TypeQLUpdate,
     },
     variable::{ConceptVariable, TypeReference, ValueVariable, Variable},
 };
@@ -118,8 +122,22 @@ fn parse_single(rule: Rule, string: &str) -> Result<Node<'_>> {
     Ok(parse(rule, string)?.consume_any())
 }
 
-pub(crate) fn visit_eof_query(query: &str) -> Result<Query> {
-    visit_query(parse_single(Rule::eof_query, query)?.into_children().consume_expected(Rule::query)).validated()
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ParseOptions {
+    pub datetime_precision: DateTimePrecision,
+}
+
+pub(crate) fn visit_eof_query(query: &str, options: &ParseOptions) -> Result<Query> {
+    let query = parse_single(Rule::eof_query, query)?.into_children().consume_any();
+    let query = match query.as_rule() {
+        Rule::query => visit_query(query),
+        Rule::query_pipeline => Query::from(visit_query_pipeline(query)),
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: query.to_string() }),
+    };
+    match query.validate() {
+        Ok(()) => Ok(query),
+        Err(error) => error.admitting_precision(options.datetime_precision).map(|_| query),
+    }
 }
 
 pub(crate) fn visit_eof_queries(queries: &str) -> Result<impl Iterator<Item = Result<Query>> + '_> {
@@ -209,6 +227,23 @@ fn get_date_time(date_time: Node<'_>) -> NaiveDateTime {
         .unwrap_or_else(|| panic!("{}", TypeQLError::IllegalGrammar { input: date_time.to_string() }))
 }
 
+fn get_date_time_tz(date_time: Node<'_>) -> DateTime<FixedOffset> {
+    debug_assert_eq!(date_time.as_rule(), Rule::DATETIME_TZ_);
+    date_time::parse_with_offset(date_time.as_str())
+        .unwrap_or_else(|| panic!("{}", TypeQLError::IllegalGrammar { input: date_time.to_string() }))
+}
+
+fn get_duration(duration: Node<'_>) -> Duration {
+    debug_assert_eq!(duration.as_rule(), Rule::DURATION_);
+    duration::parse(duration.as_str())
+        .unwrap_or_else(|| panic!("{}", TypeQLError::IllegalGrammar { input: duration.to_string() }))
+}
+
+fn decimal_from_string(string: &str) -> Decimal {
+    decimal::parse(&string.trim_end_matches("dec").replace(char::is_whitespace, ""))
+        .unwrap_or_else(|| panic!("{}", TypeQLError::IllegalGrammar { input: string.to_owned() }))
+}
+
 fn get_var(node: Node<'_>) -> Variable {
     debug_assert_eq!(node.as_rule(), Rule::VAR_);
     let name = node.as_str();
@@ -282,6 +317,7 @@ fn visit_query(node: Node<'_>) -> Query {
         Rule::query_insert => visit_query_insert(child).into(),
         Rule::query_delete => visit_query_delete(child).into(),
         Rule::query_update => visit_query_update(child).into(),
+        Rule::query_put => visit_query_put(child).into(),
         Rule::query_get => visit_query_get(child).into(),
         Rule::query_fetch => visit_query_fetch(child).into(),
         Rule::query_get_aggregate => visit_query_get_aggregate(child).into(),
@@ -327,6 +363,7 @@ fn visit_projection(node: Node<'_>) -> Projection {
                 Projection::Variable(key_var)
             }
         }
+        Rule::projection_field => visit_projection_field(child),
         Rule::projection_key_label => {
             let key_label = visit_projection_key_label(child);
             let subquery = visit_projection_subquery(children.consume_expected(Rule::projection_subquery));
@@ -336,6 +373,16 @@ fn visit_projection(node: Node<'_>) -> Projection {
     }
 }
 
+fn visit_projection_field(node: Node<'_>) -> Projection {
+    debug_assert_eq!(node.as_rule(), Rule::projection_field);
+    let mut children = node.into_children();
+    let variable = get_var(children.consume_expected(Rule::VAR_));
+    let fields = visit_field_path(children.consume_expected(Rule::field_path));
+    let label = children.try_consume_expected(Rule::projection_key_as_label).map(visit_projection_as_label);
+    debug_assert!(children.try_consume_any().is_none());
+    Projection::Field(ProjectionKeyVar { variable, label }, fields)
+}
+
 fn visit_projection_key_var(node: Node<'_>) -> ProjectionKeyVar {
     debug_assert_eq!(node.as_rule(), Rule::projection_key_var);
     let mut children = node.into_children();
@@ -385,7 +432,7 @@ fn visit_projection_subquery(node: Node<'_>) -> ProjectionSubquery {
     let child = children.consume_any();
     match child.as_rule() {
         Rule::query_fetch => ProjectionSubquery::Fetch(Box::new(visit_query_fetch(child))),
-        Rule::query_get_aggregate => ProjectionSubquery::GetAggregate(visit_query_get_aggregate(child)),
+        Rule::query_get_aggregate => ProjectionSubquery::GetAggregate(Box::new(visit_query_get_aggregate(child))),
         _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: child.to_string() }),
     }
 }
@@ -462,14 +509,60 @@ fn visit_query_update(node: Node<'_>) -> TypeQLUpdate {
     TypeQLUpdate { query_delete, insert_statements: clause_insert, modifiers }
 }
 
+fn visit_query_put(node: Node<'_>) -> TypeQLPut {
+    debug_assert_eq!(node.as_rule(), Rule::query_put);
+    let mut children = node.into_children();
+    let child = children.consume_any();
+    let query = match child.as_rule() {
+        Rule::clause_match => {
+            let clause_match = visit_clause_match(child);
+            let clause_put = visit_clause_put(children.consume_expected(Rule::clause_put));
+            TypeQLPut { match_clause: Some(clause_match), statements: clause_put }
+        }
+        Rule::clause_put => TypeQLPut::new(visit_clause_put(child)),
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: children.to_string() }),
+    };
+    debug_assert!(children.try_consume_any().is_none());
+    query
+}
+
+fn visit_query_pipeline(node: Node<'_>) -> TypeQLPipeline {
+    debug_assert_eq!(node.as_rule(), Rule::query_pipeline);
+    TypeQLPipeline::new(node.into_children().map(visit_pipeline_stage).collect())
+}
+
+fn visit_pipeline_stage(node: Node<'_>) -> Stage {
+    debug_assert_eq!(node.as_rule(), Rule::pipeline_stage);
+    let stage = node.into_child().unwrap();
+    match stage.as_rule() {
+        Rule::clause_match => Stage::Match(visit_clause_match(stage)),
+        Rule::clause_insert => Stage::Insert(visit_clause_insert(stage)),
+        Rule::clause_delete => Stage::Delete(visit_clause_delete(stage)),
+        Rule::clause_put => Stage::Put(visit_clause_put(stage)),
+        Rule::clause_get => Stage::Get(Filter { vars: visit_clause_get(stage) }),
+        Rule::clause_select => Stage::Select(visit_clause_select(stage)),
+        Rule::clause_fetch => Stage::Fetch(visit_clause_fetch(stage)),
+        Rule::distinct => Stage::Distinct(Distinct),
+        Rule::sort => Stage::Sort(visit_sort(stage)),
+        Rule::offset => Stage::Offset(visit_offset(stage)),
+        Rule::limit => Stage::Limit(visit_limit(stage)),
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: stage.to_string() }),
+    }
+}
+
 fn visit_query_get(node: Node<'_>) -> TypeQLGet {
     debug_assert_eq!(node.as_rule(), Rule::query_get);
     let mut children = node.into_children();
     let clause_match = visit_clause_match(children.consume_expected(Rule::clause_match));
-    let clause_get = visit_clause_get(children.consume_expected(Rule::clause_get));
+    let clause = children.consume_any();
+    let (filter, selection) = match clause.as_rule() {
+        Rule::clause_get => (Filter { vars: visit_clause_get(clause) }, None),
+        Rule::clause_select => (Filter::default(), Some(visit_clause_select(clause))),
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: clause.to_string() }),
+    };
     let modifiers = visit_modifiers(children.consume_expected(Rule::modifiers));
     debug_assert!(children.try_consume_any().is_none());
-    TypeQLGet { match_clause: clause_match, filter: Filter { vars: clause_get }, modifiers }
+    TypeQLGet { match_clause: clause_match, filter, selection, modifiers }
 }
 
 fn visit_clause_insert(node: Node<'_>) -> Vec<ThingStatement> {
@@ -490,6 +583,15 @@ fn visit_clause_delete(node: Node<'_>) -> Vec<ThingStatement> {
     statements
 }
 
+fn visit_clause_put(node: Node<'_>) -> Vec<ThingStatement> {
+    debug_assert_eq!(node.as_rule(), Rule::clause_put);
+    let mut children = node.into_children();
+    children.skip_expected(Rule::PUT);
+    let statements = visit_statement_things(children.consume_expected(Rule::statement_things));
+    debug_assert!(children.try_consume_any().is_none());
+    statements
+}
+
 fn visit_clause_match(node: Node<'_>) -> MatchClause {
     debug_assert_eq!(node.as_rule(), Rule::clause_match);
     let mut children = node.into_children();
@@ -504,24 +606,36 @@ fn visit_clause_get(node: Node<'_>) -> Vec<Variable> {
     node.into_children().skip_expected(Rule::GET).map(get_var).collect()
 }
 
+fn visit_clause_select(node: Node<'_>) -> Selection {
+    debug_assert_eq!(node.as_rule(), Rule::clause_select);
+    Selection::new(node.into_children().skip_expected(Rule::SELECT).map(visit_select_var).collect())
+}
+
+fn visit_select_var(node: Node<'_>) -> SelectVariable {
+    debug_assert_eq!(node.as_rule(), Rule::select_var);
+    let mut children = node.into_children();
+    let select_var = match children.try_consume_expected(Rule::VAR_) {
+        Some(var) => SelectVariable::from(get_var(var)),
+        None => {
+            let variable = get_var_value(children.consume_expected(Rule::VAR_VALUE_));
+            children.skip_expected(Rule::AS);
+            SelectVariable::from((variable, get_var_value(children.consume_expected(Rule::VAR_VALUE_))))
+        }
+    };
+    debug_assert!(children.try_consume_any().is_none());
+    select_var
+}
+
 fn visit_modifiers(node: Node<'_>) -> Modifiers {
     debug_assert_eq!(node.as_rule(), Rule::modifiers);
     let mut modifiers = Modifiers::default();
     for modifier in node.into_children() {
         match modifier.as_rule() {
+            Rule::distinct => modifiers.distinct = Some(Distinct),
             Rule::sort => modifiers.sorting = Some(visit_sort(modifier)),
-            Rule::offset => {
-                modifiers.offset = Some(Offset {
-                    offset: get_long(modifier.into_children().skip_expected(Rule::OFFSET).consume_expected(Rule::LONG_))
-                        as usize,
-                })
-            }
-            Rule::limit => {
-                modifiers.limit = Some(Limit {
-                    limit: get_long(modifier.into_children().skip_expected(Rule::LIMIT).consume_expected(Rule::LONG_))
-                        as usize,
-                })
-            }
+            Rule::cursor => modifiers.cursor = Some(visit_cursor(modifier)),
+            Rule::offset => modifiers.offset = Some(visit_offset(modifier)),
+            Rule::limit => modifiers.limit = Some(visit_limit(modifier)),
             _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: modifier.to_string() }),
         };
     }
@@ -532,52 +646,65 @@ fn visit_query_get_aggregate(node: Node<'_>) -> TypeQLGetAggregate {
     debug_assert_eq!(node.as_rule(), Rule::query_get_aggregate);
     let mut children = node.into_children();
     let query_get = visit_query_get(children.consume_expected(Rule::query_get));
-    let (method, var) = visit_clause_aggregate(children.consume_expected(Rule::clause_aggregate));
-    debug_assert!(children.try_consume_any().is_none());
-    match method {
-        Aggregate::Count => query_get.count(),
-        method => query_get.aggregate(method, var.unwrap()),
-    }
+    query_get.aggregations(children.map(visit_clause_aggregate))
 }
 
-fn visit_clause_aggregate(node: Node<'_>) -> (Aggregate, Option<Variable>) {
+fn visit_clause_aggregate(node: Node<'_>) -> Aggregation {
     debug_assert_eq!(node.as_rule(), Rule::clause_aggregate);
     let mut children = node.into_children();
-    let method = visit_aggregate_method(children.consume_expected(Rule::aggregate_method));
+    let child = children.consume_any();
     let var = children.try_consume_expected(Rule::VAR_).map(get_var);
     debug_assert!(children.try_consume_any().is_none());
-    (method, var)
+    match child.as_rule() {
+        Rule::aggregate_percentile => {
+            let percentile =
+                get_long(child.into_children().skip_expected(Rule::PERCENTILE).consume_expected(Rule::LONG_));
+            Aggregation { method: Aggregate::Percentile, var, percentile: Some(percentile) }
+        }
+        Rule::aggregate_method => Aggregation { method: visit_aggregate_method(child), var, percentile: None },
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: child.to_string() }),
+    }
 }
 
 fn visit_query_get_group(node: Node<'_>) -> TypeQLGetGroup {
     debug_assert_eq!(node.as_rule(), Rule::query_get_group);
     let mut children = node.into_children();
     let query = visit_query_get(children.consume_expected(Rule::query_get))
-        .group(visit_clause_group(children.consume_expected(Rule::clause_group)));
+        .group_by(visit_clause_group(children.consume_expected(Rule::clause_group)));
     debug_assert!(children.try_consume_any().is_none());
     query
 }
 
-fn visit_clause_group(node: Node<'_>) -> Variable {
+fn visit_clause_group(node: Node<'_>) -> Vec<Variable> {
     debug_assert_eq!(node.as_rule(), Rule::clause_group);
     let mut children = node.into_children();
     children.skip_expected(Rule::GROUP);
-    let var = get_var(children.consume_expected(Rule::VAR_));
-    debug_assert!(children.try_consume_any().is_none());
-    var
+    children.map(get_var).collect()
 }
 
 fn visit_query_get_group_agg(node: Node<'_>) -> TypeQLGetGroupAggregate {
     debug_assert_eq!(node.as_rule(), Rule::query_get_group_agg);
     let mut children = node.into_children();
     let query = visit_query_get(children.consume_expected(Rule::query_get))
-        .group(visit_clause_group(children.consume_expected(Rule::clause_group)));
-    let (method, var) = visit_clause_aggregate(children.consume_expected(Rule::clause_aggregate));
-    debug_assert!(children.try_consume_any().is_none());
-    match method {
-        Aggregate::Count => query.count(),
-        method => query.aggregate(method, var.unwrap()),
-    }
+        .group_by(visit_clause_group(children.consume_expected(Rule::clause_group)));
+    query.aggregations(children.map(visit_clause_aggregate))
+}
+
+fn visit_cursor(node: Node<'_>) -> Cursor {
+    debug_assert_eq!(node.as_rule(), Rule::cursor);
+    let (vars, values) =
+        node.into_children().skip_expected(Rule::AFTER).partition::<Vec<_>, _>(|child| child.as_rule() == Rule::VAR_);
+    Cursor::new(vars.into_iter().map(get_var).collect(), values.into_iter().map(visit_constant).collect())
+}
+
+fn visit_offset(node: Node<'_>) -> Offset {
+    debug_assert_eq!(node.as_rule(), Rule::offset);
+    Offset { offset: get_long(node.into_children().skip_expected(Rule::OFFSET).consume_expected(Rule::LONG_)) as usize }
+}
+
+fn visit_limit(node: Node<'_>) -> Limit {
+    debug_assert_eq!(node.as_rule(), Rule::limit);
+    Limit { limit: get_long(node.into_children().skip_expected(Rule::LIMIT).consume_expected(Rule::LONG_)) as usize }
 }
 
 fn visit_sort(node: Node<'_>) -> Sorting {
@@ -592,14 +719,39 @@ fn visit_sort_var(node: Node<'_>) -> sorting::SortVariable {
     let mut children = node.into_children();
     let var = get_var(children.consume_expected(Rule::VAR_));
     let order = children.try_consume_expected(Rule::ORDER_).map(|child| token::Order::from(child.as_str()));
-    let sorted_variable = (var, order).into();
+    let nulls = children.try_consume_expected(Rule::nulls_order).map(visit_nulls_order);
+    let collation = children.try_consume_expected(Rule::collation).map(visit_collation);
     debug_assert!(children.try_consume_any().is_none());
-    sorted_variable
+    sorting::SortVariable { variable: var, order, nulls, collation }
+}
+
+fn visit_nulls_order(node: Node<'_>) -> token::NullsOrder {
+    debug_assert_eq!(node.as_rule(), Rule::nulls_order);
+    let child = node.into_children().skip_expected(Rule::NULLS).consume_any();
+    match child.as_rule() {
+        Rule::FIRST => token::NullsOrder::First,
+        Rule::LAST => token::NullsOrder::Last,
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: child.to_string() }),
+    }
+}
+
+fn visit_collation(node: Node<'_>) -> sorting::Collation {
+    debug_assert_eq!(node.as_rule(), Rule::collation);
+    let child = node.into_children().skip_expected(Rule::COLLATE).consume_any();
+    match child.as_rule() {
+        Rule::NOCASE => sorting::Collation::CaseInsensitive,
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: child.to_string() }),
+    }
 }
 
 fn visit_aggregate_method(node: Node<'_>) -> token::Aggregate {
     debug_assert_eq!(node.as_rule(), Rule::aggregate_method);
-    token::Aggregate::from(node.as_str())
+    let mut children = node.into_children();
+    let method = token::Aggregate::from(children.consume_any().as_str());
+    match children.try_consume_expected(Rule::DISTINCT) {
+        Some(_) => token::Aggregate::CountDistinct,
+        None => method,
+    }
 }
 
 fn visit_definables(node: Node<'_>) -> Vec<Definable> {
@@ -615,6 +767,9 @@ fn visit_definable(node: Node<'_>) -> Definable {
         Rule::statement_type => visit_statement_type(child).into(),
         Rule::schema_rule => visit_schema_rule(child).into(),
         Rule::schema_rule_label => visit_schema_rule_label(child).into(),
+        Rule::schema_function => visit_schema_function(child).into(),
+        Rule::schema_function_label => visit_schema_function_label(child).into(),
+        Rule::schema_struct => visit_schema_struct(child).into(),
         _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: child.to_string() }),
     };
     debug_assert!(children.try_consume_any().is_none());
@@ -638,6 +793,7 @@ fn visit_pattern(node: Node<'_>) -> Pattern {
             visit_pattern_conjunction(children.consume_expected(Rule::pattern_conjunction)).into()
         }
         Rule::pattern_negation => visit_pattern_negation(children.consume_expected(Rule::pattern_negation)).into(),
+        Rule::pattern_optional => visit_pattern_optional(children.consume_expected(Rule::pattern_optional)).into(),
         _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: children.consume_any().to_string() }),
     };
     debug_assert!(children.try_consume_any().is_none());
@@ -679,6 +835,19 @@ fn visit_pattern_negation(node: Node<'_>) -> Negation {
     negation
 }
 
+fn visit_pattern_optional(node: Node<'_>) -> Optional {
+    debug_assert_eq!(node.as_rule(), Rule::pattern_optional);
+    let mut children = node.into_children();
+    children.skip_expected(Rule::TRY);
+    let mut patterns = visit_patterns(children.consume_expected(Rule::patterns));
+    let optional = match patterns.len() {
+        1 => Optional::new(patterns.pop().unwrap()),
+        _ => Optional::new(Conjunction::new(patterns).into()),
+    };
+    debug_assert!(children.try_consume_any().is_none());
+    optional
+}
+
 fn visit_statement(node: Node<'_>) -> Statement {
     debug_assert_eq!(node.as_rule(), Rule::statement);
     let mut children = node.into_children();
@@ -697,8 +866,16 @@ fn visit_statement(node: Node<'_>) -> Statement {
 fn visit_statement_concept(node: Node<'_>) -> ConceptStatement {
     debug_assert_eq!(node.as_rule(), Rule::statement_concept);
     let mut children = node.into_children();
-    let var = get_var_concept(children.consume_expected(Rule::VAR_CONCEPT_))
-        .is(get_var_concept(children.skip_expected(Rule::IS).consume_expected(Rule::VAR_CONCEPT_)));
+    let var_concept = get_var_concept(children.consume_expected(Rule::VAR_CONCEPT_));
+    let var = match children.peek_rule() {
+        Some(Rule::IS) => {
+            var_concept.is(get_var_concept(children.skip_expected(Rule::IS).consume_expected(Rule::VAR_CONCEPT_)))
+        }
+        Some(Rule::IN) => {
+            var_concept.in_(visit_function_call(children.skip_expected(Rule::IN).consume_expected(Rule::function_call)))
+        }
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: children.to_string() }),
+    };
     debug_assert!(children.try_consume_any().is_none());
     var
 }
@@ -706,6 +883,17 @@ fn visit_statement_concept(node: Node<'_>) -> ConceptStatement {
 fn visit_statement_value(node: Node<'_>) -> ValueStatement {
     debug_assert_eq!(node.as_rule(), Rule::statement_value);
     let mut children = node.into_children();
+    if children.try_consume_expected(Rule::REDUCE).is_some() {
+        let var_value = get_var_value(children.consume_expected(Rule::VAR_VALUE_));
+        let mut aggregate =
+            children.skip_expected(Rule::ASSIGN).consume_expected(Rule::reduce_aggregate).into_children();
+        let method = visit_aggregate_method(aggregate.consume_expected(Rule::aggregate_method));
+        let var = aggregate.try_consume_expected(Rule::VAR_).map(get_var);
+        debug_assert!(aggregate.try_consume_any().is_none());
+        children.try_consume_expected(Rule::WITHIN);
+        let within = children.map(get_var).collect();
+        return var_value.reduce(ReduceConstraint { method, var, within });
+    }
     let var_value = get_var_value(children.consume_expected(Rule::VAR_VALUE_));
     let var = match children.peek_rule() {
         Some(Rule::ASSIGN) => var_value.assign(visit_expression(children.skip_expected(Rule::ASSIGN).consume_any())),
@@ -726,18 +914,22 @@ fn visit_statement_type(node: Node<'_>) -> TypeStatement {
             Rule::ABSTRACT => var_type.abstract_(),
             Rule::OWNS => {
                 let type_ = visit_type_ref(constraint_nodes.consume_expected(Rule::type_ref));
+                let is_list = constraint_nodes.try_consume_expected(Rule::LIST_).is_some();
                 let overridden = constraint_nodes
                     .try_consume_expected(Rule::AS)
                     .map(|_| visit_type_ref(constraint_nodes.consume_expected(Rule::type_ref)));
                 let annotations = visit_annotations_owns(constraint_nodes.consume_expected(Rule::annotations_owns));
-                var_type.constrain_owns(OwnsConstraint::new(type_, overridden, annotations))
+                let owns = OwnsConstraint::new(type_, overridden, annotations);
+                var_type.constrain_owns(if is_list { owns.list() } else { owns })
             }
             Rule::PLAYS => {
                 let type_ = visit_type_ref_scoped(constraint_nodes.consume_expected(Rule::type_ref_scoped));
                 let overridden = constraint_nodes
                     .try_consume_expected(Rule::AS)
                     .map(|_| visit_type_ref(constraint_nodes.consume_expected(Rule::type_ref)));
-                var_type.constrain_plays(PlaysConstraint::new(type_, overridden))
+                let annotations =
+                    constraint_nodes.try_consume_expected(Rule::annotation_card).map(visit_annotation_card);
+                var_type.constrain_plays(PlaysConstraint::new(type_, overridden, annotations.into_iter().collect()))
             }
             Rule::REGEX => var_type.regex(get_regex(constraint_nodes.consume_expected(Rule::QUOTED_STRING))),
             Rule::RELATES => {
@@ -745,7 +937,9 @@ fn visit_statement_type(node: Node<'_>) -> TypeStatement {
                 let overridden = constraint_nodes
                     .try_consume_expected(Rule::AS)
                     .map(|_| visit_type_ref(constraint_nodes.consume_expected(Rule::type_ref)));
-                var_type.constrain_relates(RelatesConstraint::from((type_, overridden)))
+                let annotations =
+                    constraint_nodes.try_consume_expected(Rule::annotation_card).map(visit_annotation_card);
+                var_type.constrain_relates(RelatesConstraint::new(type_, overridden, annotations.into_iter().collect()))
             }
             Rule::SUB_ => var_type.constrain_sub(SubConstraint::from((
                 visit_type_ref_any(constraint_nodes.consume_expected(Rule::type_ref_any)),
@@ -753,7 +947,9 @@ fn visit_statement_type(node: Node<'_>) -> TypeStatement {
             ))),
             Rule::TYPE => var_type.type_(visit_label_any(constraint_nodes.consume_expected(Rule::label_any))),
             Rule::VALUE => {
-                var_type.value(token::ValueType::from(constraint_nodes.consume_expected(Rule::value_type).as_str()))
+                let value_type = visit_value_type(constraint_nodes.consume_expected(Rule::value_type));
+                let annotations = constraint_nodes.by_ref().map(visit_annotation_value).collect();
+                var_type.constrain_value_type(ValueTypeConstraint::new(value_type, annotations))
             }
             _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: constraint_nodes.to_string() }),
         };
@@ -769,11 +965,49 @@ fn visit_annotations_owns(node: Node<'_>) -> Vec<Annotation> {
         .map(|annotation| match annotation.as_rule() {
             Rule::ANNOTATION_KEY => Annotation::Key,
             Rule::ANNOTATION_UNIQUE => Annotation::Unique,
+            Rule::annotation_card => visit_annotation_card(annotation),
+            Rule::annotation_value => visit_annotation_value(annotation),
             _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: annotation.to_string() }),
         })
         .collect()
 }
 
+fn visit_annotation_card(node: Node<'_>) -> Annotation {
+    debug_assert_eq!(node.as_rule(), Rule::annotation_card);
+    let mut children = node.into_children();
+    children.skip_expected(Rule::ANNOTATION_CARD);
+    let min = get_long(children.consume_expected(Rule::LONG_)) as u64;
+    let max = children.try_consume_expected(Rule::LONG_).map(|max| get_long(max) as u64);
+    debug_assert!(children.try_consume_any().is_none());
+    Cardinality::new(min, max).into()
+}
+
+fn visit_annotation_value(node: Node<'_>) -> Annotation {
+    debug_assert_eq!(node.as_rule(), Rule::annotation_value);
+    let annotation = node.into_child().unwrap();
+    let mut children = annotation.clone().into_children();
+    let annotation = match annotation.as_rule() {
+        Rule::annotation_range => {
+            children.skip_expected(Rule::ANNOTATION_RANGE);
+            let min = children.try_consume_expected(Rule::constant).map(visit_constant);
+            children.skip_expected(Rule::RANGE_);
+            let max = children.try_consume_expected(Rule::constant).map(visit_constant);
+            Annotation::Range(Range::new(min, max))
+        }
+        Rule::annotation_values => {
+            children.skip_expected(Rule::ANNOTATION_VALUES);
+            Annotation::Values(children.by_ref().map(visit_constant).collect())
+        }
+        Rule::annotation_regex => {
+            children.skip_expected(Rule::ANNOTATION_REGEX);
+            Annotation::Regex(get_regex(children.consume_expected(Rule::QUOTED_STRING)))
+        }
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: annotation.to_string() }),
+    };
+    debug_assert!(children.try_consume_any().is_none());
+    annotation
+}
+
 fn visit_statement_things(node: Node<'_>) -> Vec<ThingStatement> {
     debug_assert_eq!(node.as_rule(), Rule::statement_things);
     node.into_children().map(visit_statement_thing_any).collect()
@@ -897,21 +1131,17 @@ fn visit_attribute(node: Node<'_>) -> HasConstraint {
 fn visit_predicate(node: Node<'_>) -> Predicate {
     debug_assert_eq!(node.as_rule(), Rule::predicate);
     let mut children = node.into_children();
+    let fields = children.try_consume_expected(Rule::field_path).map(visit_field_path).unwrap_or_default();
+    let index = children
+        .try_consume_expected(Rule::predicate_index)
+        .map(|index| long_from_string(index.into_children().consume_expected(Rule::signed_long).as_str()));
     let constraint = match children.peek_rule() {
         Some(Rule::constant) => {
             Predicate::new(token::Predicate::Eq, visit_constant(children.consume_expected(Rule::constant)).into())
         }
         Some(Rule::predicate_equality) => {
-            Predicate::new(token::Predicate::from(children.consume_expected(Rule::predicate_equality).as_str()), {
-                let res_predicate_value = children.consume_expected(Rule::value).into_child();
-                debug_assert!(res_predicate_value.is_ok());
-                let predicate_value = res_predicate_value.unwrap();
-                match predicate_value.as_rule() {
-                    Rule::constant => visit_constant(predicate_value).into(),
-                    Rule::VAR_ => Value::from(get_var(predicate_value)),
-                    _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: predicate_value.to_string() }),
-                }
-            })
+            let predicate = token::Predicate::from(children.consume_expected(Rule::predicate_equality).as_str());
+            Predicate::new(predicate, visit_value(children.consume_expected(Rule::value)))
         }
         Some(Rule::predicate_substring) => {
             let predicate = token::Predicate::from(children.consume_expected(Rule::predicate_substring).as_str());
@@ -929,10 +1159,33 @@ fn visit_predicate(node: Node<'_>) -> Predicate {
                 .into(),
             )
         }
+        Some(Rule::predicate_membership) => {
+            children.consume_expected(Rule::predicate_membership);
+            Predicate::new(token::Predicate::Contains, visit_value(children.consume_expected(Rule::value)))
+        }
         _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: children.to_string() }),
     };
     debug_assert!(children.try_consume_any().is_none());
-    constraint
+    let constraint = fields.into_iter().fold(constraint, Predicate::at_field);
+    match index {
+        Some(index) => constraint.at_index(index),
+        None => constraint,
+    }
+}
+
+fn visit_field_path(node: Node<'_>) -> Vec<String> {
+    debug_assert_eq!(node.as_rule(), Rule::field_path);
+    node.into_children().map(|field| field.as_str().to_owned()).collect()
+}
+
+fn visit_value(node: Node<'_>) -> Value {
+    debug_assert_eq!(node.as_rule(), Rule::value);
+    let value = node.into_child().unwrap();
+    match value.as_rule() {
+        Rule::constant => visit_constant(value).into(),
+        Rule::VAR_ => Value::from(get_var(value)),
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: value.to_string() }),
+    }
 }
 
 fn visit_expression(node: Node<'_>) -> Expression {
@@ -949,6 +1202,7 @@ fn visit_expression(node: Node<'_>) -> Expression {
             Rule::VAR_ => get_var(primary).into(),
             Rule::constant => Expression::Constant(visit_constant(primary)),
             Rule::expression_function => Expression::Function(visit_function(primary)),
+            Rule::function_call => Expression::Call(visit_function_call(primary)),
             Rule::expression_parenthesis => visit_expression(primary.into_children().consume_any()),
             _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: primary.to_string() }),
         })
@@ -976,6 +1230,15 @@ fn visit_function(node: Node<'_>) -> Function {
     }
 }
 
+fn visit_function_call(node: Node<'_>) -> FunctionCall {
+    debug_assert_eq!(node.as_rule(), Rule::function_call);
+    let mut children = node.into_children();
+    FunctionCall::new(
+        Label::from(children.consume_expected(Rule::label).as_str()),
+        children.map(visit_expression).collect(),
+    )
+}
+
 fn visit_function_name(node: Node<'_>) -> token::Function {
     debug_assert_eq!(node.as_rule(), Rule::expression_function_name);
     let mut children = node.into_children();
@@ -987,6 +1250,16 @@ fn visit_function_name(node: Node<'_>) -> token::Function {
         Rule::MAX => token::Function::Max,
         Rule::MIN => token::Function::Min,
         Rule::ROUND => token::Function::Round,
+        Rule::LENGTH => token::Function::Length,
+        Rule::LOWERCASE => token::Function::Lowercase,
+        Rule::UPPERCASE => token::Function::Uppercase,
+        Rule::SUBSTRING => token::Function::Substring,
+        Rule::CONCAT => token::Function::Concat,
+        Rule::YEAR => token::Function::Year,
+        Rule::MONTH => token::Function::Month,
+        Rule::DAY => token::Function::Day,
+        Rule::ADD_DURATION => token::Function::AddDuration,
+        Rule::SUBTRACT_DURATION => token::Function::SubtractDuration,
         _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: child.to_string() }),
     };
     debug_assert!(children.try_consume_any().is_none());
@@ -1002,6 +1275,76 @@ fn visit_schema_rule_label(node: Node<'_>) -> RuleLabel {
     rule
 }
 
+fn visit_schema_function_label(node: Node<'_>) -> FunctionLabel {
+    debug_assert_eq!(node.as_rule(), Rule::schema_function_label);
+    let mut children = node.into_children();
+    children.skip_expected(Rule::FUN);
+    let function = FunctionLabel::new(Label::from(children.consume_expected(Rule::label).as_str()));
+    debug_assert!(children.try_consume_any().is_none());
+    function
+}
+
+fn visit_schema_function(node: Node<'_>) -> SchemaFunction {
+    debug_assert_eq!(node.as_rule(), Rule::schema_function);
+    let mut children = node.into_children();
+    let label = Label::from(children.skip_expected(Rule::FUN).consume_expected(Rule::label).as_str());
+    let mut arguments = Vec::new();
+    while children.peek_rule() == Some(Rule::function_argument) {
+        let mut argument = children.consume_expected(Rule::function_argument).into_children();
+        let variable = get_var(argument.consume_expected(Rule::VAR_));
+        arguments.push(FunctionArgument::from((
+            variable,
+            visit_function_type(argument.consume_expected(Rule::function_type)),
+        )));
+        debug_assert!(argument.try_consume_any().is_none());
+    }
+    let function = FunctionLabel::new(label)
+        .args(arguments, visit_function_type(children.consume_expected(Rule::function_type)))
+        .match_(visit_clause_match(children.consume_expected(Rule::clause_match)))
+        .return_(get_var(children.skip_expected(Rule::RETURN).consume_expected(Rule::VAR_)));
+    debug_assert!(children.try_consume_any().is_none());
+    function
+}
+
+fn visit_function_type(node: Node<'_>) -> FunctionType {
+    debug_assert_eq!(node.as_rule(), Rule::function_type);
+    let child = node.into_child().unwrap();
+    match child.as_rule() {
+        Rule::value_type_builtin => token::ValueType::from(child.as_str()).into(),
+        Rule::label => Label::from(child.as_str()).into(),
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: child.to_string() }),
+    }
+}
+
+fn visit_schema_struct(node: Node<'_>) -> Struct {
+    debug_assert_eq!(node.as_rule(), Rule::schema_struct);
+    let mut children = node.into_children();
+    let struct_ = Struct::from(children.skip_expected(Rule::STRUCT).consume_expected(Rule::label).as_str());
+    children.fold(struct_, |struct_, field| {
+        let mut field_nodes = field.into_children();
+        let name = field_nodes.consume_expected(Rule::label).as_str().to_owned();
+        let value_type = visit_value_type(field_nodes.skip_expected(Rule::VALUE).consume_expected(Rule::value_type));
+        let is_optional = field_nodes.try_consume_expected(Rule::OPTIONAL_).is_some();
+        debug_assert!(field_nodes.try_consume_any().is_none());
+        if is_optional {
+            struct_.optional_field(name, value_type)
+        } else {
+            struct_.field(name, value_type)
+        }
+    })
+}
+
+fn visit_value_type(node: Node<'_>) -> ValueType {
+    debug_assert_eq!(node.as_rule(), Rule::value_type);
+    let child = node.into_child().unwrap();
+    match child.as_rule() {
+        Rule::value_type_builtin => token::ValueType::from(child.as_str()).into(),
+        // any other label names a struct, which may be defined elsewhere in the schema, so it is resolved by the server
+        Rule::label => child.as_str().into(),
+        _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: child.to_string() }),
+    }
+}
+
 fn visit_schema_rule(node: Node<'_>) -> crate::pattern::Rule {
     debug_assert_eq!(node.as_rule(), Rule::schema_rule);
     let mut children = node.into_children();
@@ -1079,12 +1422,27 @@ fn visit_constant(node: Node<'_>) -> Constant {
     let mut children = node.into_children();
     let child = children.consume_any();
     let constant = match child.as_rule() {
+        Rule::constant_list => Constant::List(child.into_children().map(visit_constant).collect()),
+        Rule::constant_struct => Constant::Struct(
+            child
+                .into_children()
+                .map(|field| {
+                    let mut field_nodes = field.into_children();
+                    let name = field_nodes.consume_expected(Rule::label).as_str().to_owned();
+                    (name, visit_constant(field_nodes.consume_expected(Rule::constant)))
+                })
+                .collect(),
+        ),
         Rule::QUOTED_STRING => Constant::from(get_string_from_quoted(child)),
         Rule::signed_long => Constant::from(long_from_string(child.as_str())),
         Rule::signed_double => Constant::from(double_from_string(child.as_str())),
         Rule::BOOLEAN_ => Constant::from(get_boolean(child)),
-        Rule::DATE_ => Constant::from(get_date(child).and_hms_opt(0, 0, 0).unwrap()),
+        Rule::signed_decimal => Constant::from(decimal_from_string(child.as_str())),
+        // a bare date used to be read as a datetime at midnight; it remains comparable and assignable to datetimes
+        Rule::DATE_ => Constant::from(get_date(child)),
         Rule::DATETIME_ => Constant::from(get_date_time(child)),
+        Rule::DATETIME_TZ_ => Constant::from(get_date_time_tz(child)),
+        Rule::DURATION_ => Constant::from(get_duration(child)),
         _ => unreachable!("{}", TypeQLError::IllegalGrammar { input: child.to_string() }),
     };
     debug_assert!(children.try_consume_any().is_none());
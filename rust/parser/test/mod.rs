@@ -3,27 +3,39 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
 
 use crate::{
     and,
-    builder::{abs, ceil, constant, cvar, eq, floor, label, round, vvar},
+    builder::{
+        abs, add_duration, call, ceil, concat, constant, cvar, day, eq, floor, fun, label, length, lowercase, month,
+        round, substring, subtract_duration, uppercase, vvar, year,
+    },
     common::{
+        date_time::DateTimePrecision,
+        decimal::Decimal,
+        duration::Duration,
         token::{
-            self,
+            self, NullsOrder,
             Order::{Asc, Desc},
             ValueType,
         },
         validatable::Validatable,
     },
-    gte, lt, lte, min, not, or, parse_definables, parse_label, parse_pattern, parse_patterns, parse_queries,
-    parse_query, parse_statement,
+    contains, gt, gte, lt, lte, min, not, or, parse_definables, parse_label, parse_pattern, parse_patterns,
+    parse_queries, parse_query, parse_query_with, parse_statement,
+    parser::ParseOptions,
     pattern::{
-        Annotation::Key, ConceptStatementBuilder, ExpressionBuilder, Label, Statement, ThingStatementBuilder,
-        TypeStatementBuilder, ValueStatementBuilder,
+        Annotation::{Key, Unique},
+        ConceptStatementBuilder, Constant, Expression, ExpressionBuilder, Label, OwnsConstraint, PlaysConstraint,
+        Range, ReduceConstraint, RelatesConstraint, Statement, ThingStatementBuilder, TypeStatementBuilder,
+        ValueStatementBuilder, ValueTypeConstraint,
+    },
+    query::{
+        sorting::{Collation, SortVariableBuilder},
+        AggregateQueryBuilder, Aggregation, Projection, ProjectionBuilder, ProjectionKeyVarBuilder, TypeQLPipeline,
     },
-    query::{AggregateQueryBuilder, Projection, ProjectionBuilder, ProjectionKeyVarBuilder},
-    rel, rule, sort_vars, type_, typeql_insert, typeql_match,
+    rel, rule, sort_vars, struct_, try_, type_, typeql_insert, typeql_match,
     variable::Variable,
     Query,
 };
@@ -411,7 +423,7 @@ get;"#;
 #[test]
 fn when_parsing_date_error_when_parsing_overly_precise_decimal_seconds() {
     let query = r#"match
-$x has release-date 1000-11-12T13:14:15.000123456;
+$x has release-date 1000-11-12T13:14:15.0001234567;
 get;"#;
 
     let parsed = parse_query(query);
@@ -419,6 +431,51 @@ get;"#;
     assert!(parsed.unwrap_err().to_string().contains("line 2"));
 }
 
+#[test]
+fn when_parsing_date_handle_nanos_under_configured_precision() {
+    let micros = r#"match
+$x has logged-at 2024-03-01T09:30:15.123456+01:00;
+get;"#;
+    let nanos = r#"match
+$x has release-date 1000-11-12T13:14:15.123456789;
+get;"#;
+    let options = |datetime_precision| ParseOptions { datetime_precision };
+
+    assert!(parse_query(micros).unwrap_err().to_string().contains("more precise than 1 millisecond"));
+    assert!(parse_query_with(micros, &options(DateTimePrecision::Micros)).is_ok());
+    assert!(parse_query_with(nanos, &options(DateTimePrecision::Micros))
+        .unwrap_err()
+        .to_string()
+        .contains("more precise than 1 microsecond"));
+
+    let parsed = parse_query_with(nanos, &options(DateTimePrecision::Nanos)).unwrap().into_get();
+    let expected = typeql_match!(cvar("x").has((
+        "release-date",
+        NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(1000, 11, 12).unwrap(),
+            NaiveTime::from_hms_nano_opt(13, 14, 15, 123456789).unwrap(),
+        ),
+    )))
+    .get();
+    assert_eq!(expected, parsed);
+    assert_eq!(parsed.to_string(), nanos);
+
+    let parsed = parse_query_with(micros, &options(DateTimePrecision::Nanos)).unwrap().into_get();
+    let expected = typeql_match!(cvar("x").has((
+        "logged-at",
+        FixedOffset::east_opt(3600)
+            .unwrap()
+            .from_local_datetime(
+                &NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_micro_opt(9, 30, 15, 123456).unwrap()
+            )
+            .unwrap(),
+    )))
+    .get();
+    assert_eq!(expected, parsed);
+    assert_eq!(parsed.to_string(), micros);
+    assert!(parsed.validate().unwrap_err().to_string().contains("more precise than 1 millisecond"));
+}
+
 #[test]
 fn when_parsing_date_error_when_handling_overly_precise_nanos() {
     let validated = typeql_match!(cvar("x").has((
@@ -693,6 +750,138 @@ get;"#;
     assert_valid_eq_repr!(expected, parsed, query);
 }
 
+#[test]
+fn test_function_string() {
+    let query = r#"match
+$x isa person,
+    has first-name $f,
+    has last-name $l;
+?name = concat($f, " ", uppercase($l));
+?initial = substring(lowercase($f), 0, 1);
+?size = length(?name);
+get;"#;
+
+    let parsed = parse_query(query).unwrap().into_get();
+    let expected = typeql_match!(
+        cvar("x").isa("person").has(("first-name", cvar("f"))).has(("last-name", cvar("l"))),
+        vvar("name").assign(concat::<Expression>([cvar("f").into(), " ".into(), uppercase(cvar("l")).into()])),
+        vvar("initial").assign(substring(lowercase(cvar("f")), 0, 1)),
+        vvar("size").assign(length(vvar("name")))
+    )
+    .get();
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_function_datetime() {
+    let query = r#"match
+$e isa employment,
+    has start-date $s;
+?end = add_duration($s, P1Y6M);
+?review = subtract_duration(?end, P14DT12H);
+?year = year(?end);
+?month = month(?end) + 12 * (year(?end) - year($s));
+?day = day($s);
+get;"#;
+
+    let parsed = parse_query(query).unwrap().into_get();
+    let expected = typeql_match!(
+        cvar("e").isa("employment").has(("start-date", cvar("s"))),
+        vvar("end").assign(add_duration(cvar("s"), "P1Y6M".parse::<Duration>().unwrap())),
+        vvar("review").assign(subtract_duration(vvar("end"), "P14DT12H".parse::<Duration>().unwrap())),
+        vvar("year").assign(year(vvar("end"))),
+        vvar("month")
+            .assign(month(vvar("end")).add(Expression::from(12).multiply(year(vvar("end")).subtract(year(cvar("s")))))),
+        vvar("day").assign(day(cvar("s")))
+    )
+    .get();
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_ill_typed_function_argument_throw() {
+    let parsed = parse_query(
+        "match
+$x isa person;
+?x = length(3);
+get;",
+    );
+    assert!(parsed.unwrap_err().to_string().contains(
+        "The function 'length' expects an argument of type 'string', but was given an argument of type 'long'."
+    ));
+
+    let parsed = parse_query(
+        "match
+$x has age $a;
+?d = year(\"2020\");
+get;",
+    );
+    assert!(parsed
+        .unwrap_err()
+        .to_string()
+        .contains("expects an argument of type 'date', 'datetime' or 'datetime-tz'"));
+    assert!(parse_query("match\n$x isa person;\n?d = year(2020-01-01T10:00+01:00) + day(2020-01-01);\nget;").is_ok());
+
+    let parsed = parse_query(
+        "match
+$x has name $n;
+?s = uppercase($n);
+?t = ?s + 1;
+get;",
+    );
+    assert!(parsed.unwrap_err().to_string().contains("the operand '?s' is not numeric"));
+
+    let parsed = parse_query(
+        "match
+$x has name $n;
+?s = substring($n, 1);
+get;",
+    );
+    assert!(parsed.unwrap_err().to_string().contains("The function 'substring' cannot be applied to 2 argument(s)."));
+}
+
+#[test]
+fn when_parsing_ill_typed_expression_throw() {
+    let parsed = parse_query("match\n$x isa person;\n?x = \"abc\" + 3;\nget;");
+    assert!(parsed.unwrap_err().to_string().contains(
+        r#"The expression '"abc" + 3' can only be applied to numeric operands, but the operand '"abc"' is not numeric."#
+    ));
+
+    let parsed = parse_query("match\n$x isa person;\n?x = round(true);\nget;");
+    assert!(parsed.unwrap_err().to_string().contains("the operand 'true' is not numeric"));
+
+    let parsed = parse_query("match\n$x has age $a;\n?s = \"abc\";\n?x = $a + ?s;\nget;");
+    assert!(parsed
+        .unwrap_err()
+        .to_string()
+        .contains("The expression '$a + ?s' can only be applied to numeric operands"));
+
+    assert!(parse_query("match\n$x has age $a;\n?x = round($a) + max(?y, 1.5);\nget;").is_ok());
+
+    let parsed = parse_query("match\n$x has name $n;\n?s = \"abc\";\nnot { $x has age $a; ?t = $a + ?s; };\nget;");
+    assert!(parsed
+        .unwrap_err()
+        .to_string()
+        .contains("The expression '$a + ?s' can only be applied to numeric operands"));
+
+    let parsed = parse_query("match\n$x has age $a;\n{ ?s = \"abc\"; ?t = $a * ?s; } or { ?t = $a; };\nget;");
+    assert!(parsed
+        .unwrap_err()
+        .to_string()
+        .contains("The expression '$a * ?s' can only be applied to numeric operands"));
+}
+
+#[test]
+fn when_parsing_function_with_wrong_arity_throw() {
+    let parsed = parse_query("match\n$x has age $a;\n?x = abs($a, 2);\nget;");
+    assert!(parsed.unwrap_err().to_string().contains("The function 'abs' cannot be applied to 2 argument(s)."));
+
+    let parsed = parse_query("match\n$x has age $a;\n?x = max();\nget;");
+    assert!(parsed.unwrap_err().to_string().contains("The function 'max' cannot be applied to 0 argument(s)."));
+}
+
 #[test]
 fn test_schema_query() {
     let query = r#"match
@@ -793,6 +982,104 @@ sort $r desc; offset 10; limit 10;"#;
     assert_valid_eq_repr!(expected, parsed, query);
 }
 
+#[test]
+fn test_get_sort_nulls_and_collation() {
+    let query = r#"match
+$x isa movie,
+    has title $t;
+try { $x has rating $r; };
+get;
+sort $r desc nulls last, $t collate nocase, $x asc;"#;
+
+    let parsed = parse_query(query).unwrap().into_get();
+    let expected =
+        typeql_match!(cvar("x").isa("movie").has(("title", cvar("t"))), try_(cvar("x").has(("rating", cvar("r")))),)
+            .get()
+            .sort(sort_vars!(
+                (cvar("r"), Desc).nulls(NullsOrder::Last),
+                cvar("t").collate(Collation::CaseInsensitive),
+                (cvar("x"), Asc)
+            ));
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_collation_locale_throw() {
+    let parsed = parse_query(r#"match $x has title $t; get; sort $t collate "de-CH";"#);
+    assert!(parsed.unwrap_err().to_string().contains("syntax error"));
+}
+
+#[test]
+fn test_get_sort_after_limit() {
+    let query = r#"match
+$x isa movie,
+    has title $t,
+    has rating $r;
+get;
+sort $r desc, $t; after ($r, $t) (7.5, "Heat"); limit 10;"#;
+
+    let parsed = parse_query(query).unwrap().into_get();
+    let cursor: [Variable; 2] = [cvar("r").into(), cvar("t").into()];
+    let expected = typeql_match!(cvar("x").isa("movie").has(("title", cvar("t"))).has(("rating", cvar("r"))))
+        .get()
+        .sort(sort_vars!((cvar("r"), Desc), cvar("t")))
+        .after(cursor, [Constant::from(7.5), Constant::from("Heat")])
+        .limit(10);
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_cursor_not_matching_sorting_throw() {
+    let parsed = parse_query("match $x has title $t, has rating $r; get; after ($t) (\"Heat\");");
+    assert!(parsed.unwrap_err().to_string().contains("A cursor requires the query to be sorted"));
+
+    let parsed = parse_query("match $x has title $t, has rating $r; get; sort $r, $t; after ($t, $r) (\"Heat\", 7.5);");
+    assert!(parsed.unwrap_err().to_string().contains("must list exactly the variables of 'sort $r, $t'"));
+
+    let parsed = parse_query("match $x has title $t, has rating $r; get; sort $r, $t; after ($r, $t) (7.5);");
+    assert!(parsed.unwrap_err().to_string().contains("must give exactly one value for each of its variables"));
+
+    let parsed = parse_query("match $x has title $t; get; sort $t; after ($t) ([\"Heat\"]);");
+    assert!(parsed.unwrap_err().to_string().contains("cursor value '[\"Heat\"]' cannot be ordered"));
+}
+
+#[test]
+fn test_select_distinct_with_aliases() {
+    let query = r#"match
+$x isa movie,
+    has rating $r;
+?score = $r * 2;
+select $x, ?score as ?rank;
+distinct; sort ?rank desc; limit 10;"#;
+
+    let parsed = parse_query(query).unwrap().into_get();
+    let expected =
+        typeql_match!(cvar("x").isa("movie").has(("rating", cvar("r"))), vvar("score").assign(cvar("r").multiply(2)),)
+            .select(select_vars!(cvar("x"), (vvar("score"), vvar("rank"))))
+            .distinct()
+            .sort(sort_vars!((vvar("rank"), Desc)))
+            .limit(10);
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_invalid_select_throw() {
+    let parsed = parse_query("match $x has rating $r; ?s = $r * 2; select $x, ?s as ?t; sort ?s;");
+    assert!(parsed.unwrap_err().to_string().contains("The sort variable '?s' is not bound"));
+
+    let parsed = parse_query("match $x has rating $r; ?s = $r * 2; select ?s, $r as ?s;");
+    assert!(parsed.is_err());
+
+    let parsed = parse_query("match $x has rating $r; ?s = $r * 2; ?t = $r; select ?s, ?t as ?s;");
+    assert!(parsed.unwrap_err().to_string().contains("The variable '?s' occurred more than once in the select clause"));
+
+    let parsed = parse_query("match $x has rating $r; select $x, ?s as ?t;");
+    assert!(parsed.unwrap_err().to_string().contains("The select variable '?s' is not bound"));
+}
+
 #[test]
 fn test_get_offset_limit() {
     let query = r#"match
@@ -973,6 +1260,40 @@ get;"#;
     assert!(parsed.is_err());
 }
 
+#[test]
+fn test_optional_pattern() {
+    let query = r#"match
+$x isa person;
+try {
+    $x has email $e;
+    $e contains "@vaticle.com";
+};
+try { $x has nickname $n; };
+get $x, $e, $n;"#;
+
+    let parsed = parse_query(query).unwrap().into_get();
+    let expected = typeql_match!(
+        cvar("x").isa("person"),
+        try_(and!(cvar("x").has(("email", cvar("e"))), cvar("e").contains("@vaticle.com"))),
+        try_(cvar("x").has(("nickname", cvar("n"))))
+    )
+    .get_fixed([cvar("x"), cvar("e"), cvar("n")]);
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_unbounded_optional_pattern_throw() {
+    let parsed = parse_query("match $x isa person; try { $y has email $e; }; get;");
+    assert!(parsed.unwrap_err().to_string().contains("unbounded nested pattern"));
+}
+
+#[test]
+fn when_parsing_nested_optional_pattern_throw() {
+    let parsed = parse_query("match $x isa person; try { try { $x has email $e; }; }; get;");
+    assert!(parsed.unwrap_err().to_string().contains("redundant nested optional patterns"));
+}
+
 #[test]
 fn test_aggregate_count_query() {
     let query = r#"match
@@ -1071,6 +1392,108 @@ group $x; sum ?t;"#;
     assert_valid_eq_repr!(expected, parsed, query);
 }
 
+#[test]
+fn test_group_by_several_variables_with_several_aggregates() {
+    let query = r#"match
+$s isa sale,
+    has country $country,
+    has year $year,
+    has amount $amount;
+get;
+group $country, $year; count; sum $amount; max $amount;"#;
+
+    let parsed = parse_query(query).unwrap().into_get_group_aggregate();
+    let expected = typeql_match!(cvar("s")
+        .isa("sale")
+        .has(("country", cvar("country")))
+        .has(("year", cvar("year")))
+        .has(("amount", cvar("amount"))))
+    .get()
+    .group_by([cvar("country"), cvar("year")])
+    .aggregations([
+        Aggregation::count(),
+        Aggregation::new(token::Aggregate::Sum, cvar("amount")),
+        Aggregation::new(token::Aggregate::Max, cvar("amount")),
+    ]);
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_several_aggregates_query() {
+    let query = r#"match
+$x has age $a;
+get;
+count;
+mean $a;"#;
+
+    let parsed = parse_query(query).unwrap().into_get_aggregate();
+    let expected = typeql_match!(cvar("x").has(("age", cvar("a"))))
+        .get()
+        .aggregations([Aggregation::count(), Aggregation::new(token::Aggregate::Mean, cvar("a"))]);
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_count_distinct_list_and_percentile_aggregates() {
+    let query = r#"match
+$x has age $a;
+get;
+group $x; count distinct $a; list $a; percentile 90 $a;"#;
+
+    let parsed = parse_query(query).unwrap().into_get_group_aggregate();
+    let expected = typeql_match!(cvar("x").has(("age", cvar("a")))).get().group(cvar("x")).aggregations([
+        Aggregation::new(token::Aggregate::CountDistinct, cvar("a")),
+        Aggregation::new(token::Aggregate::List, cvar("a")),
+        Aggregation::percentile(90, cvar("a")),
+    ]);
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_first_and_last_aggregates_under_sorting() {
+    let query = r#"match
+$x has age $a;
+get;
+sort $a;
+first $x;
+last $x;"#;
+
+    let parsed = parse_query(query).unwrap().into_get_aggregate();
+    let expected = typeql_match!(cvar("x").has(("age", cvar("a")))).get().sort(sort_vars!(cvar("a"))).aggregations([
+        Aggregation::new(token::Aggregate::First, cvar("x")),
+        Aggregation::new(token::Aggregate::Last, cvar("x")),
+    ]);
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_invalid_aggregates_throw() {
+    let parsed = parse_query("match $x has age $a; get; first $a;");
+    assert!(parsed.unwrap_err().to_string().contains("the get query must be sorted"));
+
+    let parsed = parse_query("match $x has age $a; get; max;");
+    assert!(parsed.unwrap_err().to_string().contains("Aggregate 'max' requires a variable"));
+
+    let parsed = parse_query("match $x has age $a; get; count $a;");
+    assert!(parsed.unwrap_err().to_string().contains("Aggregate COUNT does not accept a Variable"));
+
+    let parsed = parse_query("match $x has age $a; get; percentile 101 $a;");
+    assert!(parsed.unwrap_err().to_string().contains("percentile '101' must be between 0 and 100"));
+}
+
+#[test]
+fn when_grouping_by_several_variables_each_must_be_bound() {
+    let parsed = parse_query("match $x has age $a; get $x, $a; group $x, $y; count;");
+    assert!(parsed.unwrap_err().to_string().contains("get-group variable '$y' is not bound"));
+
+    let parsed = parse_query("match $x has age $a; get; group $x; count; sum $b;");
+    assert!(parsed.unwrap_err().to_string().contains("get-aggregate variable '$b' is not bound"));
+}
+
 #[test]
 fn when_comparing_count_query_using_typeql_and_rust_typeql_they_are_equivalent() {
     let query = r#"match
@@ -1145,6 +1568,149 @@ $x has age 25;"#;
     assert_valid_eq_repr!(expected, parsed, query);
 }
 
+#[test]
+fn test_put_query() {
+    let query = r#"put
+$x isa person,
+    has name "alice";"#;
+
+    let parsed = parse_query(query).unwrap().into_put();
+    let expected = typeql_put!(cvar("x").isa("person").has(("name", "alice")));
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_match_put_query() {
+    let query = r#"match
+$x isa person,
+    has name "alice";
+$c isa company,
+    has name "vaticle";
+put
+(employee: $x, employer: $c) isa employment;"#;
+
+    let parsed = parse_query(query).unwrap().into_put();
+    let expected = typeql_match!(
+        cvar("x").isa("person").has(("name", "alice")),
+        cvar("c").isa("company").has(("name", "vaticle"))
+    )
+    .put(rel(("employee", "x")).rel(("employer", "c")).isa("employment"));
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_match_put_builder_with_several_statements() {
+    let query = r#"match
+$x isa person;
+put
+$x has name "alice";
+(employee: $x) isa employment;"#;
+
+    let parsed = parse_query(query).unwrap().into_put();
+    let expected = typeql_match!(cvar("x").isa("person"))
+        .put([cvar("x").has(("name", "alice")), rel(("employee", "x")).isa("employment")]);
+
+    assert_valid_eq_repr!(expected, parsed, query);
+
+    let unbound = typeql_match!(cvar("x").isa("person")).put(cvar("y").isa("person"));
+    assert!(unbound.validate().unwrap_err().to_string().contains("None of the variables in 'put'"));
+}
+
+#[test]
+fn when_parsing_put_not_bound_by_match_throw() {
+    let parsed = parse_query(r#"match $x isa person; put $y isa person, has name "alice";"#);
+    assert!(parsed.unwrap_err().to_string().contains("None of the variables in 'put'"));
+}
+
+#[test]
+fn test_pipeline_query() {
+    let query = r#"match
+$p isa person,
+    has name "alice";
+insert
+$e isa email,
+    has address "alice@vaticle.com";
+(owner: $p, email: $e) isa email-ownership;
+match
+$e has address $a;
+sort $a desc;
+limit 10;
+fetch
+$p: name;
+$a;"#;
+
+    let parsed = parse_query(query).unwrap().into_pipeline();
+    let expected = TypeQLPipeline::from(typeql_match!(cvar("p").isa("person").has(("name", "alice"))))
+        .insert(vec![
+            cvar("e").isa("email").has(("address", "alice@vaticle.com")),
+            rel(("owner", "p")).rel(("email", "e")).isa("email-ownership"),
+        ])
+        .match_(typeql_match!(cvar("e").has(("address", cvar("a")))))
+        .sort(sort_vars!((cvar("a"), Desc)))
+        .limit(10)
+        .fetch(vec![cvar("p").map_attribute("name"), cvar("a").into()]);
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_pipeline_query_with_delete_and_get() {
+    let query = r#"match
+$x isa person,
+    has nickname $n;
+get $x, $n;
+offset 5;
+delete
+$x has $n;"#;
+
+    let parsed = parse_query(query).unwrap().into_pipeline();
+    let expected = TypeQLPipeline::from(typeql_match!(cvar("x").isa("person").has(("nickname", cvar("n")))))
+        .get_fixed([cvar("x"), cvar("n")])
+        .offset(5)
+        .delete(cvar("x").has(cvar("n")));
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_pipeline_query_with_select_and_distinct() {
+    let query = r#"match
+$x isa person,
+    has age $a;
+?years = $a;
+select $x, ?years as ?age;
+distinct;
+match
+$x has name $n;
+sort ?age;"#;
+
+    let parsed = parse_query(query).unwrap().into_pipeline();
+    let expected = TypeQLPipeline::from(typeql_match!(
+        cvar("x").isa("person").has(("age", cvar("a"))),
+        vvar("years").assign(cvar("a")),
+    ))
+    .select(select_vars!(cvar("x"), (vvar("years"), vvar("age"))))
+    .distinct()
+    .match_(typeql_match!(cvar("x").has(("name", cvar("n")))))
+    .sort([vvar("age")]);
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_pipeline_with_variable_from_later_stage_throw() {
+    let parsed = parse_query("match $x isa person; get $x; delete $x has $n; match $x has name $n;");
+    assert!(parsed.unwrap_err().to_string().contains("The delete variable '$n' is not bound"));
+
+    let parsed = parse_query("match $x isa person, has name $n; get $x; sort $n; match $x has age $a;");
+    assert!(parsed.is_err());
+
+    let parsed = parse_query("match $x isa person; fetch $x; match $x has name $n;");
+    assert!(parsed.unwrap_err().to_string().contains("cannot follow a fetch stage"));
+}
+
 #[test]
 fn when_parsing_as_in_define_result_is_same_as_sub() {
     let query = r#"define
@@ -1310,6 +1876,138 @@ rule r;"#;
     assert_valid_eq_repr!(expected, parsed, query);
 }
 
+#[test]
+fn test_define_functions() {
+    let query = r#"define
+fun names($p: person) -> name:
+match
+$p has name $n;
+return $n;
+fun age-in-days($p: person) -> long:
+match
+$p has age $a;
+?d = $a * 365;
+return ?d;"#;
+
+    let parsed = parse_query(query).unwrap().into_define();
+    let expected = typeql_define!(
+        fun("names")
+            .args([(cvar("p"), "person")], "name")
+            .match_(typeql_match!(cvar("p").has(("name", cvar("n")))))
+            .return_(cvar("n")),
+        fun("age-in-days")
+            .args([(cvar("p"), "person")], ValueType::Long)
+            .match_(typeql_match!(cvar("p").has(("age", cvar("a"))), vvar("d").assign(cvar("a").multiply(365))))
+            .return_(vvar("d"))
+    );
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_undefine_function() {
+    let query = r#"undefine
+fun names;"#;
+
+    let parsed = parse_query(query).unwrap().into_undefine();
+    let expected = typeql_undefine!(fun("names"));
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_function_calls() {
+    let query = r#"match
+$p isa person;
+$n in names($p);
+?d = age-in-days($p) + 1;
+get;"#;
+
+    let parsed = parse_query(query).unwrap().into_get();
+    let expected = typeql_match!(
+        cvar("p").isa("person"),
+        cvar("n").in_(call("names", [cvar("p")])),
+        vvar("d").assign(call("age-in-days", [cvar("p")]).add(1)),
+    )
+    .get();
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_function_with_unused_argument_throw() {
+    let parsed = parse_query("define fun names($p: person, $q: person) -> name: match $p has name $n; return $n;");
+    assert!(parsed.unwrap_err().to_string().contains("argument '$q' is not used"));
+}
+
+#[test]
+fn when_parsing_function_with_unbound_return_throw() {
+    let parsed = parse_query("define fun names($p: person) -> name: match $p has name $n; return $m;");
+    assert!(parsed.unwrap_err().to_string().contains("returns '$m', which is not bound"));
+}
+
+#[test]
+fn when_parsing_function_returning_value_type_in_concept_variable_throw() {
+    let parsed = parse_query("define fun names($p: person) -> string: match $p has name $n; return $n;");
+    assert!(parsed.unwrap_err().to_string().contains("variable '$n' cannot hold 'string'"));
+}
+
+#[test]
+fn when_parsing_function_without_body_throw() {
+    let parsed = parse_query("define fun names;");
+    assert!(parsed.unwrap_err().to_string().contains("Function 'names' has not been provided with a signature"));
+}
+
+#[test]
+fn when_parsing_recursive_functions_throw() {
+    let parsed = parse_query("define fun loop($p: person) -> person: match $f in loop($p); return $f;");
+    assert!(parsed.unwrap_err().to_string().contains("Function 'loop' calls itself"));
+
+    let query = r#"define
+fun ping($p: person) -> long: match ?n = pong($p) + 1; return ?n;
+fun pong($p: person) -> long: match ?n = ping($p); return ?n;"#;
+    let error = parse_query(query).unwrap_err().to_string();
+    assert!(error.contains("Function 'ping' calls itself") && error.contains("Function 'pong' calls itself"));
+}
+
+#[test]
+fn test_reduce_statements() {
+    let query = r#"match
+$s isa sale,
+    has country $country,
+    has amount $amount;
+reduce ?total = sum($amount) within $country;
+reduce ?sales = count();
+?total > 1000;
+get $country, ?total, ?sales;
+sort ?total desc;"#;
+
+    let parsed = parse_query(query).unwrap().into_get();
+    let filter: [Variable; 3] = [cvar("country").into(), vvar("total").into(), vvar("sales").into()];
+    let expected = typeql_match!(
+        cvar("s").isa("sale").has(("country", cvar("country"))).has(("amount", cvar("amount"))),
+        vvar("total").reduce(ReduceConstraint::new(token::Aggregate::Sum, cvar("amount")).within([cvar("country")])),
+        vvar("sales").reduce(ReduceConstraint::count()),
+        vvar("total").predicate(gt(1000)),
+    )
+    .get_fixed(filter)
+    .sort(sort_vars!((vvar("total"), Desc)));
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_invalid_reduce_statements_throw() {
+    let parsed = parse_query("match $s isa sale; reduce ?total = sum($amount) within $s; get;");
+    assert!(parsed.unwrap_err().to_string().contains("reduce variable '$amount' is not bound"));
+
+    let parsed = parse_query("match $s has amount $a; reduce ?first = first($a); get;");
+    assert!(parsed.unwrap_err().to_string().contains("Aggregate 'first' cannot be reduced"));
+
+    let parsed = parse_query("match $s has amount $a; reduce ?t = count(); reduce ?t = max($a); get;");
+    assert!(parsed.unwrap_err().to_string().contains("value variable '?t' is assigned by more than one"));
+
+    let parsed = parse_query("match $s has amount $a; ?t = 1; reduce ?t = count(); get;");
+    assert!(parsed.unwrap_err().to_string().contains("value variable '?t' is assigned by more than one"));
+}
+
 #[test]
 fn test_match_insert_query() {
     let query = r#"match
@@ -1385,6 +2083,369 @@ my-type sub attribute,
     assert_valid_eq_repr!(expected, parsed, query);
 }
 
+#[test]
+fn test_define_temporal_and_decimal_value_types() {
+    let query = r#"define
+birth-date sub attribute,
+    value date;
+departure sub attribute,
+    value datetime-tz;
+price sub attribute,
+    value decimal;
+tenure sub attribute,
+    value duration;"#;
+
+    let parsed = parse_query(query).unwrap().into_define();
+    let expected = typeql_define!(
+        type_("birth-date").sub("attribute").value(ValueType::Date),
+        type_("departure").sub("attribute").value(ValueType::DateTimeTZ),
+        type_("price").sub("attribute").value(ValueType::Decimal),
+        type_("tenure").sub("attribute").value(ValueType::Duration)
+    );
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_bare_date_literal_keep_date_and_accept_for_datetime() {
+    let query = r#"match
+$x has founded 2020-01-01;
+get;"#;
+
+    let parsed = parse_query(query).unwrap().into_get();
+    let expected = typeql_match!(cvar("x").has(("founded", NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()))).get();
+    assert_valid_eq_repr!(expected, parsed, query);
+
+    let date = Constant::Date(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap());
+    assert!(date.is_assignable_to(token::ValueType::DateTime));
+    assert_eq!(date.to_string(), "2020-01-01");
+}
+
+#[test]
+fn test_insert_temporal_and_decimal_literals() {
+    let query = r#"insert
+$x isa flight,
+    has birth-date 1970-01-01,
+    has departure 2024-03-01T09:30+05:30,
+    has arrival 2024-03-01T18:45:30.250Z,
+    has price 1234.5dec,
+    has discount -0.05dec,
+    has tenure P1Y2M3DT4H5M6.5S;"#;
+
+    let parsed = parse_query(query).unwrap().into_insert();
+    let expected = typeql_insert!(cvar("x")
+        .isa("flight")
+        .has(("birth-date", NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()))
+        .has((
+            "departure",
+            FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap().with_ymd_and_hms(2024, 3, 1, 9, 30, 0).unwrap(),
+        ))
+        .has((
+            "arrival",
+            FixedOffset::east_opt(0)
+                .unwrap()
+                .from_local_datetime(
+                    &NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_milli_opt(18, 45, 30, 250).unwrap()
+                )
+                .unwrap(),
+        ))
+        .has(("price", "1234.5".parse::<Decimal>().unwrap()))
+        .has(("discount", "-0.05".parse::<Decimal>().unwrap()))
+        .has(("tenure", "P1Y2M3DT4H5M6.5S".parse::<Duration>().unwrap())));
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_durations_use_canonical_form() {
+    let query = r#"match
+$x has tenure P2W;
+$y has tenure P18MT90M;
+get;"#;
+
+    let parsed = parse_query(query).unwrap().into_get();
+    assert_eq!(parsed.to_string(), "match\n$x has tenure P14D;\n$y has tenure P1Y6MT1H30M;\nget;");
+    assert!(parse_query("match\n$x has tenure P1H;\nget;").is_err());
+    assert!("P1D2Y".parse::<Duration>().is_err());
+    assert!("1.23456789012345678901".parse::<Decimal>().is_err());
+}
+
+#[test]
+fn test_define_list_value_types_and_ownership() {
+    let query = r#"define
+tags sub attribute,
+    value string[];
+score sub attribute,
+    value long;
+post sub entity,
+    owns tags,
+    owns score[] @unique;"#;
+
+    let parsed = parse_query(query).unwrap().into_define();
+    let expected = typeql_define!(
+        type_("tags").sub("attribute").value(ValueType::StringList),
+        type_("score").sub("attribute").value(ValueType::Long),
+        type_("post").sub("entity").owns("tags").owns(OwnsConstraint::from(("score", Unique)).list())
+    );
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_define_cardinality_annotations() {
+    let query = r#"define
+marriage sub relation,
+    relates spouse @card(0..2),
+    relates witness @card(1..);
+person sub entity,
+    plays marriage:spouse @card(0..1),
+    owns email @key,
+    owns nickname @unique @card(0..3);"#;
+
+    let parsed = parse_query(query).unwrap().into_define();
+    let expected = typeql_define!(
+        type_("marriage")
+            .sub("relation")
+            .relates(RelatesConstraint::from("spouse").card(0, 2))
+            .relates(RelatesConstraint::from("witness").card(1, None)),
+        type_("person")
+            .sub("entity")
+            .plays(PlaysConstraint::from(("marriage", "spouse")).card(0, 1))
+            .owns(("email", Key))
+            .owns(OwnsConstraint::from(("nickname", Unique)).card(0, 3))
+    );
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_define_value_restriction_annotations() {
+    let query = r#"define
+username sub attribute,
+    value string @regex("^[a-z]+$");
+status sub attribute,
+    value string @values("active", "closed");
+age sub attribute,
+    value long @range(0..150);
+score sub attribute,
+    value double @range(..10.5);
+account sub entity,
+    owns username @key @regex("^[a-z]{3,}$"),
+    owns status @values("active"),
+    owns age @range(18..);"#;
+
+    let parsed = parse_query(query).unwrap().into_define();
+    let expected = typeql_define!(
+        type_("username").sub("attribute").value(ValueTypeConstraint::from(ValueType::String).regex("^[a-z]+$")),
+        type_("status")
+            .sub("attribute")
+            .value(ValueTypeConstraint::from(ValueType::String).values(["active", "closed"])),
+        type_("age").sub("attribute").value(ValueTypeConstraint::from(ValueType::Long).range((0, 150))),
+        type_("score")
+            .sub("attribute")
+            .value(ValueTypeConstraint::from(ValueType::Double).range(Range::new(None, Some(10.5.into())))),
+        type_("account")
+            .sub("entity")
+            .owns(OwnsConstraint::from(("username", Key)).regex("^[a-z]{3,}$"))
+            .owns(OwnsConstraint::from("status").values(["active"]))
+            .owns(OwnsConstraint::from("age").range(Range::new(Some(18.into()), None)))
+    );
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_value_restriction_of_wrong_value_type_throw() {
+    let parsed = parse_query(
+        "define
+age sub attribute, value long @regex(\"^[0-9]+$\");",
+    );
+    assert!(parsed.unwrap_err().to_string().contains("cannot restrict values of type 'long'"));
+
+    let parsed = parse_query(
+        "define
+age sub attribute, value long;
+person owns age @values(\"young\", \"old\");",
+    );
+    assert!(parsed.unwrap_err().to_string().contains("cannot restrict values of type 'long'"));
+
+    let parsed = parse_query(
+        "define
+age sub attribute, value long @range(10..1);",
+    );
+    assert!(parsed.unwrap_err().to_string().contains("The range '10..1' is not valid"));
+
+    let parsed = parse_query(
+        "define
+person owns status @values(\"a\", \"a\");",
+    );
+    assert!(parsed.unwrap_err().to_string().contains("The value '\"a\"' is repeated"));
+
+    let parsed = parse_query(
+        "define
+person plays employment:employee @values(1);",
+    );
+    assert!(parsed.is_err());
+}
+
+#[test]
+fn when_parsing_invalid_cardinality_throw() {
+    let parsed = parse_query(
+        "define
+person owns nickname @card(3..1);",
+    );
+    assert!(parsed.unwrap_err().to_string().contains("The cardinality '3..1' is not valid"));
+
+    let parsed = parse_query(
+        "define
+marriage relates spouse @card(0..0);",
+    );
+    assert!(parsed.unwrap_err().to_string().contains("The cardinality '0..0' is not valid"));
+
+    let parsed = parse_query(
+        "define
+person plays marriage:spouse @key;",
+    );
+    assert!(parsed.is_err());
+}
+
+#[test]
+fn test_insert_list_literals() {
+    let query = r#"insert
+$p isa post,
+    has tags ["rust", "typeql"],
+    has score [1, 2, 3],
+    has history [];"#;
+
+    let parsed = parse_query(query).unwrap().into_insert();
+    let expected = typeql_insert!(cvar("p")
+        .isa("post")
+        .has(("tags", vec!["rust", "typeql"]))
+        .has(("score", vec![1, 2, 3]))
+        .has(("history", Vec::<Constant>::new())));
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_match_list_membership_and_index_predicates() {
+    let query = r#"match
+$p isa post,
+    has tags contains "rust",
+    has score[0] > 1;
+$s[-1] == 3;
+$t contains ?x;
+get;"#;
+
+    let parsed = parse_query(query).unwrap().into_get();
+    let expected = typeql_match!(
+        cvar("p").isa("post").has(("tags", contains("rust"))).has(("score", gt(1).at_index(0))),
+        cvar("s").predicate(eq(3).at_index(-1)),
+        cvar("t").contains(vvar("x"))
+    )
+    .get();
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_heterogeneous_list_throw() {
+    let parsed = parse_query("insert\n$p isa post,\n    has score [1, \"two\"];");
+    assert!(parsed.unwrap_err().to_string().contains(
+        r#"The list '[1, "two"]' must contain values of a single value type, which cannot itself be a list."#
+    ));
+
+    let parsed = parse_query("insert\n$p isa post,\n    has score [[1], [2]];");
+    assert!(parsed.unwrap_err().to_string().contains("which cannot itself be a list"));
+
+    assert!(parse_query("match\n$p has score like 1;\nget;").is_err());
+}
+
+#[test]
+fn test_define_struct_value_types() {
+    let query = r#"define
+struct coordinates: latitude value double, longitude value double;
+struct address: street value string, postcode value string?, location value coordinates?;
+home-address sub attribute,
+    value address;"#;
+
+    let parsed = parse_query(query).unwrap().into_define();
+    let expected = typeql_define!(
+        struct_("coordinates").field("latitude", ValueType::Double).field("longitude", ValueType::Double),
+        struct_("address")
+            .field("street", ValueType::String)
+            .optional_field("postcode", ValueType::String)
+            .optional_field("location", "coordinates"),
+        type_("home-address").sub("attribute").value("address")
+    );
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_undefine_struct() {
+    let query = r#"undefine
+struct address;"#;
+
+    let parsed = parse_query(query).unwrap().into_undefine();
+    let expected = typeql_undefine!(struct_("address"));
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_insert_struct_literals() {
+    let query = r#"insert
+$p isa person,
+    has home-address {street: "Main Street", location: {latitude: 51.5, longitude: -0.1}};"#;
+
+    let parsed = parse_query(query).unwrap().into_insert();
+    let location = Constant::from(vec![("latitude", 51.5), ("longitude", -0.1)]);
+    let expected = typeql_insert!(cvar("p")
+        .isa("person")
+        .has(("home-address", vec![("street", Constant::from("Main Street")), ("location", location)])));
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn test_match_and_fetch_struct_fields() {
+    let query = r#"match
+$p isa person,
+    has home-address.location.latitude > 50.0;
+$a.street contains "Main";
+fetch
+$a.street;
+$a.location.longitude as longitude;"#;
+
+    let parsed = parse_query(query).unwrap().into_fetch();
+    let expected = typeql_match!(
+        cvar("p").isa("person").has(("home-address", gt(50.0).at_field("location").at_field("latitude"))),
+        cvar("a").predicate(contains("Main").at_field("street"))
+    )
+    .fetch(vec![
+        cvar("a").map_field("street"),
+        Projection::Field(cvar("a").label("longitude"), vec!["location".to_owned(), "longitude".to_owned()]),
+    ]);
+
+    assert_valid_eq_repr!(expected, parsed, query);
+}
+
+#[test]
+fn when_parsing_struct_with_repeating_fields_throw() {
+    let parsed = parse_query("define\nstruct address: street value string, street value string?;");
+    assert!(parsed.unwrap_err().to_string().contains("The struct field 'street' is declared more than once."));
+
+    let parsed = parse_query("insert\n$a {street: \"Main\", street: \"High\"} isa home-address;");
+    assert!(parsed.unwrap_err().to_string().contains("The struct field 'street' is declared more than once."));
+}
+
+#[test]
+fn when_parsing_struct_without_fields_throw() {
+    let parsed = parse_query("define struct address;");
+    assert!(parsed.unwrap_err().to_string().contains("Struct 'address' has not been provided with any fields."));
+}
+
 #[test]
 fn test_escape_string() {
     let input = r#"This has \"double quotes\" and a single-quoted backslash: '\\'"#;
@@ -4,7 +4,10 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use std::{collections::HashSet, fmt, iter};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, iter,
+};
 
 use itertools::Itertools;
 
@@ -16,8 +19,8 @@ use crate::{
         validatable::Validatable,
         Result,
     },
-    pattern::{Disjunction, Normalisable, Pattern, VariablesRetrieved},
-    variable::variable::VariableRef,
+    pattern::{infer_value_types, Disjunction, Normalisable, Pattern, Simplifiable, VariablesRetrieved},
+    variable::{variable::VariableRef, ValueVariable},
 };
 
 #[derive(Debug, Clone, Eq)]
@@ -82,6 +85,20 @@ impl Validatable for Conjunction {
     }
 }
 
+impl Conjunction {
+    pub(crate) fn simplify_with(&mut self, outer_types: &HashMap<ValueVariable, token::ValueType>) {
+        let variable_types = infer_value_types(&self.patterns, outer_types);
+        self.patterns.iter_mut().for_each(|pattern| pattern.simplify_with(&variable_types));
+        self.normalised = None;
+    }
+}
+
+impl Simplifiable for Conjunction {
+    fn simplify(&mut self) {
+        self.simplify_with(&HashMap::new());
+    }
+}
+
 impl Normalisable for Conjunction {
     fn normalise(&mut self) -> Pattern {
         if self.normalised.is_none() {
@@ -101,6 +118,7 @@ impl Normalisable for Conjunction {
                 disjunctions.push(disjunction.compute_normalised().into_disjunction().patterns)
             }
             Pattern::Negation(negation) => conjunctables.push(negation.compute_normalised()),
+            Pattern::Optional(optional) => conjunctables.push(optional.compute_normalised()),
             Pattern::Statement(variable) => conjunctables.push(variable.compute_normalised()),
         });
         disjunctions.push(vec![Conjunction::new(conjunctables).into()]);
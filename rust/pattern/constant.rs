@@ -4,19 +4,23 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use std::fmt;
+use std::{collections::HashSet, fmt};
 
-use chrono::{NaiveDateTime, Timelike};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
 
 use crate::{
     common::{
-        date_time,
-        error::TypeQLError,
+        date_time::{self, DateTimePrecision},
+        decimal::Decimal,
+        duration::Duration,
+        error::{collect_err, TypeQLError},
         string::{format_double, quote},
+        token,
         validatable::Validatable,
         Result,
     },
     pattern::LeftOperand,
+    write_joined,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -25,27 +29,88 @@ pub enum Constant {
     Double(f64),
     Boolean(bool),
     String(String),
+    Date(NaiveDate),
     DateTime(NaiveDateTime),
+    DateTimeTZ(DateTime<FixedOffset>),
+    Decimal(Decimal),
+    Duration(Duration),
+    List(Vec<Constant>),
+    Struct(Vec<(String, Constant)>),
 }
 
 impl Eq for Constant {} // can't derive, because floating point types do not implement Eq
 
+impl Constant {
+    pub fn value_type(&self) -> Option<token::ValueType> {
+        match self {
+            Constant::Long(_) => Some(token::ValueType::Long),
+            Constant::Double(_) => Some(token::ValueType::Double),
+            Constant::Boolean(_) => Some(token::ValueType::Boolean),
+            Constant::String(_) => Some(token::ValueType::String),
+            Constant::Date(_) => Some(token::ValueType::Date),
+            Constant::DateTime(_) => Some(token::ValueType::DateTime),
+            Constant::DateTimeTZ(_) => Some(token::ValueType::DateTimeTZ),
+            Constant::Decimal(_) => Some(token::ValueType::Decimal),
+            Constant::Duration(_) => Some(token::ValueType::Duration),
+            Constant::List(values) => values.first()?.value_type()?.list_type(),
+            Constant::Struct(_) => None,
+        }
+    }
+
+    pub(crate) fn is_assignable_to(&self, value_type: token::ValueType) -> bool {
+        let Some(own_type) = self.value_type() else { return false };
+        own_type == value_type
+            || matches!(
+                (own_type, value_type),
+                (token::ValueType::Long, token::ValueType::Double | token::ValueType::Decimal)
+                    | (token::ValueType::Date, token::ValueType::DateTime)
+            )
+    }
+}
+
 impl LeftOperand for Constant {}
 
 impl Validatable for Constant {
     fn validate(&self) -> Result {
         match self {
-            &Self::DateTime(date_time) => {
-                if date_time.nanosecond() % 1000000 > 0 {
-                    Err(TypeQLError::InvalidConstraintDatetimePrecision { date_time })?
-                }
-                Ok(())
+            &Self::DateTime(date_time) => validate_precision(date_time),
+            Self::DateTimeTZ(date_time) => validate_precision(date_time.naive_local()),
+            Self::List(values) => {
+                collect_err(values.iter().map(Validatable::validate).chain([validate_list_elements(self, values)]))
             }
+            Self::Struct(fields) => collect_err(
+                fields.iter().map(|(_, value)| value.validate()).chain([validate_struct_fields_are_unique(fields)]),
+            ),
             _ => Ok(()),
         }
     }
 }
 
+fn validate_list_elements(list: &Constant, values: &[Constant]) -> Result {
+    let element_type = values.first().and_then(Constant::value_type);
+    let is_homogeneous = values.iter().all(|value| value.value_type() == element_type);
+    if !is_homogeneous || element_type.is_some_and(|value_type| value_type.element_type().is_some()) {
+        Err(TypeQLError::InvalidConstraintList { list: list.clone() })?
+    }
+    Ok(())
+}
+
+fn validate_struct_fields_are_unique(fields: &[(String, Constant)]) -> Result {
+    let mut names = HashSet::new();
+    match fields.iter().find(|(name, _)| !names.insert(name)) {
+        Some((name, _)) => Err(TypeQLError::RepeatingStructField { field: name.clone() })?,
+        None => Ok(()),
+    }
+}
+
+fn validate_precision(date_time: NaiveDateTime) -> Result {
+    let precision = DateTimePrecision::default();
+    if !precision.admits(&date_time) {
+        Err(TypeQLError::InvalidConstraintDatetimePrecision { date_time, precision })?
+    }
+    Ok(())
+}
+
 impl From<i64> for Constant {
     fn from(long: i64) -> Self {
         Constant::Long(long)
@@ -76,12 +141,48 @@ impl From<String> for Constant {
     }
 }
 
+impl From<NaiveDate> for Constant {
+    fn from(date: NaiveDate) -> Self {
+        Constant::Date(date)
+    }
+}
+
 impl From<NaiveDateTime> for Constant {
     fn from(date_time: NaiveDateTime) -> Self {
         Constant::DateTime(date_time)
     }
 }
 
+impl From<DateTime<FixedOffset>> for Constant {
+    fn from(date_time: DateTime<FixedOffset>) -> Self {
+        Constant::DateTimeTZ(date_time)
+    }
+}
+
+impl From<Decimal> for Constant {
+    fn from(decimal: Decimal) -> Self {
+        Constant::Decimal(decimal)
+    }
+}
+
+impl From<Duration> for Constant {
+    fn from(duration: Duration) -> Self {
+        Constant::Duration(duration)
+    }
+}
+
+impl<T: Into<Constant>> From<Vec<T>> for Constant {
+    fn from(values: Vec<T>) -> Self {
+        Constant::List(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<String>, V: Into<Constant>> From<Vec<(K, V)>> for Constant {
+    fn from(fields: Vec<(K, V)>) -> Self {
+        Constant::Struct(fields.into_iter().map(|(name, value)| (name.into(), value.into())).collect())
+    }
+}
+
 impl fmt::Display for Constant {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -89,7 +190,20 @@ impl fmt::Display for Constant {
             Constant::Double(double) => write!(f, "{}", format_double(*double)),
             Constant::Boolean(boolean) => write!(f, "{boolean}"),
             Constant::String(string) => write!(f, "{}", quote(string)),
+            Constant::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
             Constant::DateTime(date_time) => write!(f, "{}", date_time::format(date_time)),
+            Constant::DateTimeTZ(date_time) => write!(f, "{}", date_time::format_with_offset(date_time)),
+            Constant::Decimal(decimal) => write!(f, "{decimal}dec"),
+            Constant::Duration(duration) => write!(f, "{duration}"),
+            Constant::List(values) => {
+                write!(f, "[")?;
+                write_joined!(f, ", ", values)?;
+                write!(f, "]")
+            }
+            Constant::Struct(fields) => {
+                let fields = fields.iter().map(|(name, value)| format!("{name}: {value}"));
+                write!(f, "{{{}}}", fields.collect::<Vec<_>>().join(", "))
+            }
         }
     }
 }
@@ -0,0 +1,42 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    common::{error::collect_err, token, validatable::Validatable, Result},
+    pattern::FunctionCall,
+    variable::variable::VariableRef,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InConstraint {
+    pub call: FunctionCall,
+}
+
+impl InConstraint {
+    pub fn variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
+        self.call.variables()
+    }
+}
+
+impl Validatable for InConstraint {
+    fn validate(&self) -> Result {
+        collect_err(self.call.args.iter().map(|arg| arg.value_type(&HashMap::new()).map(|_| ())))
+    }
+}
+
+impl From<FunctionCall> for InConstraint {
+    fn from(call: FunctionCall) -> Self {
+        Self { call }
+    }
+}
+
+impl fmt::Display for InConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", token::Constraint::In, self.call)
+    }
+}
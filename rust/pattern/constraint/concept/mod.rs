@@ -4,6 +4,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
+mod in_;
 mod is;
 
+pub use in_::InConstraint;
 pub use is::IsConstraint;
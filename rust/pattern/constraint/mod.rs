@@ -10,14 +10,16 @@ mod thing;
 mod type_;
 mod value;
 
-pub use concept::IsConstraint;
+pub use concept::{InConstraint, IsConstraint};
 pub use predicate::{Predicate, Value};
 pub use thing::{HasConstraint, IIDConstraint, IsaConstraint, RelationConstraint, RolePlayerConstraint};
 pub use type_::{
-    AbstractConstraint, Annotation, LabelConstraint, OwnsConstraint, PlaysConstraint, RegexConstraint,
-    RelatesConstraint, SubConstraint, ValueTypeConstraint,
+    AbstractConstraint, Annotation, Cardinality, LabelConstraint, OwnsConstraint, PlaysConstraint, Range,
+    RegexConstraint, RelatesConstraint, SubConstraint, ValueType, ValueTypeConstraint,
 };
-pub use value::AssignConstraint;
+pub use value::{AssignConstraint, ReduceConstraint};
+
+pub(crate) use self::type_::validate_value_annotations;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum IsExplicit {
@@ -22,16 +22,34 @@ use crate::{
 pub struct Predicate {
     pub predicate: token::Predicate,
     pub value: Value,
+    pub fields: Vec<String>,
+    pub index: Option<i64>,
 }
 
 impl Predicate {
     pub fn new(predicate: token::Predicate, value: Value) -> Self {
         match predicate {
-            token::Predicate::EqLegacy => Predicate { predicate: token::Predicate::Eq, value }, // TODO: Deprecate '=' as equality in 3.0
-            predicate => Predicate { predicate, value },
+            token::Predicate::EqLegacy => {
+                Predicate { predicate: token::Predicate::Eq, value, fields: Vec::new(), index: None }
+            } // TODO: Deprecate '=' as equality in 3.0
+            predicate => Predicate { predicate, value, fields: Vec::new(), index: None },
         }
     }
 
+    pub fn at_field(mut self, field: impl Into<String>) -> Self {
+        self.fields.push(field.into());
+        self
+    }
+
+    pub fn at_index(self, index: i64) -> Self {
+        Predicate { index: Some(index), ..self }
+    }
+
+    /// Whether the predicate applies to a part of the value, written directly after the value's variable or label.
+    pub(crate) fn has_accessor(&self) -> bool {
+        !self.fields.is_empty() || self.index.is_some()
+    }
+
     pub fn variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
         match &self.value {
             Value::ThingVariable(var) => Box::new(iter::once(VariableRef::Concept(var))),
@@ -51,7 +69,8 @@ impl Validatable for Predicate {
 }
 
 fn validate_string_value_with_substring_predicate(predicate: token::Predicate, value: &Value) -> Result {
-    if predicate.is_substring() && !matches!(value, Value::Constant(Constant::String(_))) {
+    // `contains` also tests the membership of any value in a list, so only `like` requires a string
+    if predicate == token::Predicate::Like && !matches!(value, Value::Constant(Constant::String(_))) {
         Err(TypeQLError::InvalidConstraintPredicate { predicate, value: value.clone() })?
     }
     Ok(())
@@ -59,10 +78,20 @@ fn validate_string_value_with_substring_predicate(predicate: token::Predicate, v
 
 impl fmt::Display for Predicate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.fields.is_empty() {
+            write!(f, ".{}", self.fields.join("."))?;
+        }
+        if let Some(index) = self.index {
+            write!(f, "[{index}]")?;
+        }
+        if self.has_accessor() {
+            f.write_str(" ")?;
+        }
         if self.predicate == token::Predicate::Like {
             assert!(matches!(self.value, Value::Constant(Constant::String(_))));
             write!(f, "{} {}", self.predicate, escape_regex(&self.value.to_string()))
         } else if self.predicate == token::Predicate::Eq
+            && !self.has_accessor()
             && !(matches!(self.value, Value::ThingVariable(_)) || matches!(self.value, Value::ValueVariable(_)))
         {
             write!(f, "{}", self.value)
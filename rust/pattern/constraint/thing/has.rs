@@ -89,6 +89,9 @@ impl fmt::Display for HasConstraint {
             HasConstraint::HasValue(label, var) => {
                 write!(f, " {} {}", label, var)
             }
+            HasConstraint::HasPredicate(label, predicate) if predicate.has_accessor() => {
+                write!(f, " {}{}", label, predicate)
+            }
             HasConstraint::HasPredicate(label, predicate) => {
                 write!(f, " {} {}", label, predicate)
             }
@@ -0,0 +1,219 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{cmp::Ordering, fmt};
+
+use regex::Regex;
+
+use crate::{
+    common::{
+        error::{collect_err, TypeQLError},
+        string::escape_regex,
+        token,
+        validatable::Validatable,
+        Result,
+    },
+    pattern::{Constant, ValueType},
+    write_joined,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Annotation {
+    Key,
+    Unique,
+    Cardinality(Cardinality),
+    Range(Range),
+    Values(Vec<Constant>),
+    Regex(String),
+}
+
+impl Annotation {
+    fn arguments(&self) -> Vec<&Constant> {
+        match self {
+            Self::Range(range) => range.min.iter().chain(range.max.iter()).collect(),
+            Self::Values(values) => values.iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Validatable for Annotation {
+    fn validate(&self) -> Result {
+        match self {
+            Self::Cardinality(cardinality) => cardinality.validate(),
+            Self::Range(range) => range.validate(),
+            Self::Values(values) => validate_values(values),
+            Self::Regex(regex) => match Regex::new(regex) {
+                Ok(_) => Ok(()),
+                Err(_) => Err(TypeQLError::InvalidAttributeTypeRegex { regex: regex.clone() })?,
+            },
+            Self::Key | Self::Unique => Ok(()),
+        }
+    }
+}
+
+fn validate_values(values: &[Constant]) -> Result {
+    if values.is_empty() {
+        Err(TypeQLError::MissingAnnotationValues)?
+    }
+    let repeated = values.iter().enumerate().find(|(i, value)| values[..*i].contains(value));
+    collect_err(
+        values
+            .iter()
+            .map(Validatable::validate)
+            .chain(repeated.map(|(_, value)| Err(TypeQLError::RepeatingAnnotationValue { value: value.clone() })?)),
+    )
+}
+
+impl From<Cardinality> for Annotation {
+    fn from(cardinality: Cardinality) -> Self {
+        Annotation::Cardinality(cardinality)
+    }
+}
+
+impl From<Range> for Annotation {
+    fn from(range: Range) -> Self {
+        Annotation::Range(range)
+    }
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "@")?;
+        match self {
+            Self::Key => write!(f, "{}", token::Annotation::Key),
+            Self::Unique => write!(f, "{}", token::Annotation::Unique),
+            Self::Cardinality(cardinality) => write!(f, "{}({cardinality})", token::Annotation::Cardinality),
+            Self::Range(range) => write!(f, "{}({range})", token::Annotation::Range),
+            Self::Values(values) => {
+                write!(f, "{}(", token::Annotation::Values)?;
+                write_joined!(f, ", ", values)?;
+                write!(f, ")")
+            }
+            Self::Regex(regex) => write!(f, r#"{}("{}")"#, token::Annotation::Regex, escape_regex(regex)),
+        }
+    }
+}
+
+pub(crate) fn validate_cardinality_annotations(annotations: &[Annotation], constraint: token::Constraint) -> Result {
+    collect_err(annotations.iter().map(|annotation| match annotation {
+        Annotation::Cardinality(cardinality) => cardinality.validate(),
+        _ => Err(TypeQLError::InvalidConstraintAnnotation { annotation: annotation.clone(), constraint })?,
+    }))
+}
+
+pub(crate) fn validate_value_annotations(annotations: &[Annotation], value_type: &ValueType) -> Result {
+    collect_err(annotations.iter().map(|annotation| {
+        let is_valid = match (annotation, value_type) {
+            (Annotation::Regex(_), ValueType::Builtin(value_type)) => *value_type == token::ValueType::String,
+            (Annotation::Range(_) | Annotation::Values(_), ValueType::Builtin(value_type)) => {
+                annotation.arguments().iter().all(|argument| argument.is_assignable_to(*value_type))
+            }
+            (Annotation::Range(_) | Annotation::Values(_) | Annotation::Regex(_), ValueType::Struct(_)) => false,
+            _ => true,
+        };
+        match is_valid {
+            true => Ok(()),
+            false => Err(TypeQLError::InvalidAnnotationValueType {
+                annotation: annotation.clone(),
+                value_type: value_type.clone(),
+            })?,
+        }
+    }))
+}
+
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
+pub struct Cardinality {
+    pub min: u64,
+    pub max: Option<u64>,
+}
+
+impl Cardinality {
+    pub fn new(min: u64, max: impl Into<Option<u64>>) -> Self {
+        Cardinality { min, max: max.into() }
+    }
+}
+
+impl Validatable for Cardinality {
+    fn validate(&self) -> Result {
+        match self.max {
+            Some(max) if max == 0 || max < self.min => Err(TypeQLError::InvalidCardinality { cardinality: *self })?,
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Cardinality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..", self.min)?;
+        if let Some(max) = self.max {
+            write!(f, "{max}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Range {
+    pub min: Option<Constant>,
+    pub max: Option<Constant>,
+}
+
+impl Range {
+    pub fn new(min: Option<Constant>, max: Option<Constant>) -> Self {
+        Range { min, max }
+    }
+}
+
+impl Validatable for Range {
+    fn validate(&self) -> Result {
+        let is_valid = match (&self.min, &self.max) {
+            (None, None) => false,
+            (Some(min), Some(max)) => {
+                min.value_type().is_some()
+                    && min.value_type() == max.value_type()
+                    && compare(min, max) != Some(Ordering::Greater)
+            }
+            (Some(bound), None) | (None, Some(bound)) => bound.value_type().is_some(),
+        };
+        if !is_valid {
+            Err(TypeQLError::InvalidValueRange { range: self.clone() })?
+        }
+        collect_err(self.min.iter().chain(self.max.iter()).map(Validatable::validate))
+    }
+}
+
+fn compare(lhs: &Constant, rhs: &Constant) -> Option<Ordering> {
+    match (lhs, rhs) {
+        (Constant::Long(lhs), Constant::Long(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::Double(lhs), Constant::Double(rhs)) => lhs.partial_cmp(rhs),
+        (Constant::String(lhs), Constant::String(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::Date(lhs), Constant::Date(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::DateTime(lhs), Constant::DateTime(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::DateTimeTZ(lhs), Constant::DateTimeTZ(rhs)) => Some(lhs.cmp(rhs)),
+        (Constant::Decimal(lhs), Constant::Decimal(rhs)) => Some(lhs.cmp(rhs)),
+        _ => None,
+    }
+}
+
+impl<T: Into<Constant>, U: Into<Constant>> From<(T, U)> for Range {
+    fn from((min, max): (T, U)) -> Self {
+        Range::new(Some(min.into()), Some(max.into()))
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(min) = &self.min {
+            write!(f, "{min}")?;
+        }
+        write!(f, "..")?;
+        if let Some(max) = &self.max {
+            write!(f, "{max}")?;
+        }
+        Ok(())
+    }
+}
@@ -5,6 +5,7 @@
  */
 
 mod abstract_;
+mod annotation;
 mod label;
 mod owns;
 mod plays;
@@ -14,11 +15,13 @@ mod sub;
 mod value_type;
 
 pub use abstract_::AbstractConstraint;
+pub(crate) use annotation::validate_value_annotations;
+pub use annotation::{Annotation, Cardinality, Range};
 pub use label::LabelConstraint;
-pub use owns::{Annotation, OwnsConstraint};
+pub use owns::OwnsConstraint;
 pub use plays::PlaysConstraint;
 pub use relates::RelatesConstraint;
 pub use sub::SubConstraint;
-pub use value_type::ValueTypeConstraint;
+pub use value_type::{ValueType, ValueTypeConstraint};
 
 pub use self::regex::RegexConstraint;
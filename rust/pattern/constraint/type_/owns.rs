@@ -8,31 +8,17 @@ use std::{fmt, iter};
 
 use crate::{
     common::{error::collect_err, token, validatable::Validatable, Result},
+    pattern::{Annotation, Cardinality, Constant, Range},
     variable::{variable::VariableRef, ConceptVariable, TypeReference},
     Label,
 };
 
-#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq)]
-pub enum Annotation {
-    Key,
-    Unique,
-}
-
-impl fmt::Display for Annotation {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "@")?;
-        match self {
-            Self::Key => write!(f, "{}", token::Annotation::Key),
-            Self::Unique => write!(f, "{}", token::Annotation::Unique),
-        }
-    }
-}
-
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct OwnsConstraint {
     pub attribute_type: TypeReference,
     pub overridden_attribute_type: Option<TypeReference>,
     pub annotations: Vec<Annotation>,
+    pub is_list: bool,
 }
 
 impl OwnsConstraint {
@@ -41,7 +27,31 @@ impl OwnsConstraint {
         overridden_attribute_type: Option<TypeReference>,
         annotations: Vec<Annotation>,
     ) -> Self {
-        OwnsConstraint { attribute_type, overridden_attribute_type, annotations }
+        OwnsConstraint { attribute_type, overridden_attribute_type, annotations, is_list: false }
+    }
+
+    pub fn list(self) -> Self {
+        OwnsConstraint { is_list: true, ..self }
+    }
+
+    pub fn card(mut self, min: u64, max: impl Into<Option<u64>>) -> Self {
+        self.annotations.push(Cardinality::new(min, max).into());
+        self
+    }
+
+    pub fn range(mut self, range: impl Into<Range>) -> Self {
+        self.annotations.push(Annotation::Range(range.into()));
+        self
+    }
+
+    pub fn values<T: Into<Constant>>(mut self, values: impl IntoIterator<Item = T>) -> Self {
+        self.annotations.push(Annotation::Values(values.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    pub fn regex(mut self, regex: impl Into<String>) -> Self {
+        self.annotations.push(Annotation::Regex(regex.into()));
+        self
     }
 
     pub fn variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
@@ -57,7 +67,8 @@ impl Validatable for OwnsConstraint {
     fn validate(&self) -> Result {
         collect_err(
             iter::once(self.attribute_type.validate())
-                .chain(self.overridden_attribute_type.iter().map(Validatable::validate)),
+                .chain(self.overridden_attribute_type.iter().map(Validatable::validate))
+                .chain(self.annotations.iter().map(Validatable::validate)),
         )
     }
 }
@@ -122,6 +133,7 @@ impl<T: Into<Label>> From<(T, Annotation)> for OwnsConstraint {
             attribute_type: TypeReference::Label(attribute_type.into()),
             annotations: vec![annotation],
             overridden_attribute_type: None,
+            is_list: false,
         }
     }
 }
@@ -243,6 +255,9 @@ impl<const N: usize> From<(TypeReference, TypeReference, [Annotation; N])> for O
 impl fmt::Display for OwnsConstraint {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{} {}", token::Constraint::Owns, self.attribute_type)?;
+        if self.is_list {
+            write!(f, "[]")?;
+        }
         if let Some(overridden) = &self.overridden_attribute_type {
             write!(f, " {} {}", token::Constraint::As, overridden)?;
         }
@@ -8,6 +8,7 @@ use std::{fmt, iter};
 
 use crate::{
     common::{error::collect_err, token, validatable::Validatable, Result},
+    pattern::{constraint::type_::annotation::validate_cardinality_annotations, Annotation, Cardinality},
     variable::{variable::VariableRef, ConceptVariable, TypeReference},
     Label,
 };
@@ -16,11 +17,21 @@ use crate::{
 pub struct PlaysConstraint {
     pub role_type: TypeReference,
     pub overridden_role_type: Option<TypeReference>,
+    pub annotations: Vec<Annotation>,
 }
 
 impl PlaysConstraint {
-    pub(crate) fn new(role_type: TypeReference, overridden_role_type: Option<TypeReference>) -> Self {
-        PlaysConstraint { role_type, overridden_role_type }
+    pub(crate) fn new(
+        role_type: TypeReference,
+        overridden_role_type: Option<TypeReference>,
+        annotations: Vec<Annotation>,
+    ) -> Self {
+        PlaysConstraint { role_type, overridden_role_type, annotations }
+    }
+
+    pub fn card(mut self, min: u64, max: impl Into<Option<u64>>) -> Self {
+        self.annotations.push(Cardinality::new(min, max).into());
+        self
     }
 
     pub fn variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
@@ -31,7 +42,9 @@ impl PlaysConstraint {
 impl Validatable for PlaysConstraint {
     fn validate(&self) -> Result {
         collect_err(
-            iter::once(self.role_type.validate()).chain(self.overridden_role_type.iter().map(Validatable::validate)),
+            iter::once(self.role_type.validate())
+                .chain(self.overridden_role_type.iter().map(Validatable::validate))
+                .chain(iter::once(validate_cardinality_annotations(&self.annotations, token::Constraint::Plays))),
         )
     }
 }
@@ -50,7 +63,7 @@ impl From<ConceptVariable> for PlaysConstraint {
 
 impl From<TypeReference> for PlaysConstraint {
     fn from(role_type: TypeReference) -> Self {
-        PlaysConstraint::new(role_type, None)
+        PlaysConstraint::new(role_type, None, vec![])
     }
 }
 
@@ -90,7 +103,7 @@ where
 
 impl From<(TypeReference, TypeReference)> for PlaysConstraint {
     fn from((role_type, overridden_role_type): (TypeReference, TypeReference)) -> Self {
-        PlaysConstraint::new(role_type, Some(overridden_role_type))
+        PlaysConstraint::new(role_type, Some(overridden_role_type), vec![])
     }
 }
 
@@ -100,6 +113,9 @@ impl fmt::Display for PlaysConstraint {
         if let Some(overridden) = &self.overridden_role_type {
             write!(f, " {} {}", token::Constraint::As, overridden)?;
         }
+        for annotation in &self.annotations {
+            write!(f, " {annotation}")?;
+        }
         Ok(())
     }
 }
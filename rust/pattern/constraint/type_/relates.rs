@@ -8,6 +8,7 @@ use std::{fmt, iter};
 
 use crate::{
     common::{error::collect_err, token, validatable::Validatable, Result},
+    pattern::{constraint::type_::annotation::validate_cardinality_annotations, Annotation, Cardinality},
     variable::{variable::VariableRef, ConceptVariable, TypeReference},
     Label,
 };
@@ -16,9 +17,23 @@ use crate::{
 pub struct RelatesConstraint {
     pub role_type: TypeReference,
     pub overridden_role_type: Option<TypeReference>,
+    pub annotations: Vec<Annotation>,
 }
 
 impl RelatesConstraint {
+    pub(crate) fn new(
+        role_type: TypeReference,
+        overridden_role_type: Option<TypeReference>,
+        annotations: Vec<Annotation>,
+    ) -> Self {
+        RelatesConstraint { role_type, overridden_role_type, annotations }
+    }
+
+    pub fn card(mut self, min: u64, max: impl Into<Option<u64>>) -> Self {
+        self.annotations.push(Cardinality::new(min, max).into());
+        self
+    }
+
     pub fn variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
         Box::new(self.role_type.variables().chain(self.overridden_role_type.iter().flat_map(|v| v.variables())))
     }
@@ -27,7 +42,9 @@ impl RelatesConstraint {
 impl Validatable for RelatesConstraint {
     fn validate(&self) -> Result {
         collect_err(
-            iter::once(self.role_type.validate()).chain(self.overridden_role_type.iter().map(Validatable::validate)),
+            iter::once(self.role_type.validate())
+                .chain(self.overridden_role_type.iter().map(Validatable::validate))
+                .chain(iter::once(validate_cardinality_annotations(&self.annotations, token::Constraint::Relates))),
         )
     }
 }
@@ -58,7 +75,7 @@ impl From<ConceptVariable> for RelatesConstraint {
 
 impl From<TypeReference> for RelatesConstraint {
     fn from(role_type: TypeReference) -> Self {
-        RelatesConstraint { role_type, overridden_role_type: None }
+        RelatesConstraint::new(role_type, None, vec![])
     }
 }
 
@@ -88,13 +105,13 @@ impl From<(ConceptVariable, ConceptVariable)> for RelatesConstraint {
 
 impl From<(TypeReference, TypeReference)> for RelatesConstraint {
     fn from((role_type, overridden_role_name): (TypeReference, TypeReference)) -> Self {
-        RelatesConstraint { role_type, overridden_role_type: Some(overridden_role_name) }
+        RelatesConstraint::new(role_type, Some(overridden_role_name), vec![])
     }
 }
 
 impl From<(TypeReference, Option<TypeReference>)> for RelatesConstraint {
     fn from((role_type, overridden_role_type): (TypeReference, Option<TypeReference>)) -> Self {
-        RelatesConstraint { role_type, overridden_role_type }
+        RelatesConstraint::new(role_type, overridden_role_type, vec![])
     }
 }
 
@@ -104,6 +121,9 @@ impl fmt::Display for RelatesConstraint {
         if let Some(overridden) = &self.overridden_role_type {
             write!(f, " {} {}", token::Constraint::As, overridden)?;
         }
+        for annotation in &self.annotations {
+            write!(f, " {annotation}")?;
+        }
         Ok(())
     }
 }
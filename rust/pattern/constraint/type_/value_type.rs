@@ -6,21 +6,126 @@
 
 use std::fmt;
 
-use crate::common::{token, validatable::Validatable, Result};
+use crate::{
+    common::{
+        error::{collect_err, TypeQLError},
+        token,
+        validatable::Validatable,
+        Result,
+    },
+    pattern::{
+        constraint::type_::annotation::{validate_value_annotations, Range},
+        Annotation, Constant,
+    },
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ValueType {
+    Builtin(token::ValueType),
+    Struct(String),
+}
+
+impl From<token::ValueType> for ValueType {
+    fn from(value_type: token::ValueType) -> Self {
+        ValueType::Builtin(value_type)
+    }
+}
+
+impl From<&str> for ValueType {
+    fn from(struct_name: &str) -> Self {
+        ValueType::Struct(struct_name.to_owned())
+    }
+}
+
+impl From<String> for ValueType {
+    fn from(struct_name: String) -> Self {
+        ValueType::Struct(struct_name)
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueType::Builtin(value_type) => write!(f, "{value_type}"),
+            ValueType::Struct(struct_name) => write!(f, "{struct_name}"),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ValueTypeConstraint {
-    pub value_type: token::ValueType,
+    pub value_type: ValueType,
+    pub annotations: Vec<Annotation>,
+}
+
+impl ValueTypeConstraint {
+    pub(crate) fn new(value_type: ValueType, annotations: Vec<Annotation>) -> Self {
+        ValueTypeConstraint { value_type, annotations }
+    }
+
+    pub fn range(mut self, range: impl Into<Range>) -> Self {
+        self.annotations.push(Annotation::Range(range.into()));
+        self
+    }
+
+    pub fn values<T: Into<Constant>>(mut self, values: impl IntoIterator<Item = T>) -> Self {
+        self.annotations.push(Annotation::Values(values.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    pub fn regex(mut self, regex: impl Into<String>) -> Self {
+        self.annotations.push(Annotation::Regex(regex.into()));
+        self
+    }
 }
 
 impl Validatable for ValueTypeConstraint {
     fn validate(&self) -> Result {
-        Ok(())
+        collect_err(
+            self.annotations
+                .iter()
+                .map(|annotation| match annotation {
+                    Annotation::Range(_) | Annotation::Values(_) | Annotation::Regex(_) => annotation.validate(),
+                    _ => Err(TypeQLError::InvalidConstraintAnnotation {
+                        annotation: annotation.clone(),
+                        constraint: token::Constraint::ValueType,
+                    })?,
+                })
+                .chain([validate_value_annotations(&self.annotations, &self.value_type)]),
+        )
+    }
+}
+
+impl From<token::ValueType> for ValueTypeConstraint {
+    fn from(value_type: token::ValueType) -> Self {
+        ValueTypeConstraint::from(ValueType::from(value_type))
+    }
+}
+
+impl From<&str> for ValueTypeConstraint {
+    fn from(struct_name: &str) -> Self {
+        ValueTypeConstraint::from(ValueType::from(struct_name))
+    }
+}
+
+impl From<String> for ValueTypeConstraint {
+    fn from(struct_name: String) -> Self {
+        ValueTypeConstraint::from(ValueType::from(struct_name))
+    }
+}
+
+impl From<ValueType> for ValueTypeConstraint {
+    fn from(value_type: ValueType) -> Self {
+        ValueTypeConstraint::new(value_type, vec![])
     }
 }
 
 impl fmt::Display for ValueTypeConstraint {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} {}", token::Constraint::ValueType, self.value_type)
+        write!(f, "{} {}", token::Constraint::ValueType, self.value_type)?;
+        for annotation in &self.annotations {
+            write!(f, " {annotation}")?;
+        }
+        Ok(())
     }
 }
@@ -4,7 +4,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use std::fmt;
+use std::{collections::HashMap, fmt};
 
 use crate::{
     common::{token, validatable::Validatable, Result},
@@ -25,7 +25,7 @@ impl AssignConstraint {
 
 impl Validatable for AssignConstraint {
     fn validate(&self) -> Result {
-        Ok(())
+        self.expression.value_type(&HashMap::new()).map(|_| ())
     }
 }
 
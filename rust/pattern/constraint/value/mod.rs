@@ -5,5 +5,7 @@
  */
 
 mod assign;
+mod reduce;
 
 pub use assign::AssignConstraint;
+pub use reduce::ReduceConstraint;
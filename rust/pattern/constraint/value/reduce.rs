@@ -0,0 +1,77 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt;
+
+use crate::{
+    common::{
+        error::{collect_err, TypeQLError},
+        token,
+        validatable::Validatable,
+        Result,
+    },
+    variable::{variable::VariableRef, Variable},
+    write_joined,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReduceConstraint {
+    pub method: token::Aggregate,
+    pub var: Option<Variable>,
+    pub within: Vec<Variable>,
+}
+
+impl ReduceConstraint {
+    pub fn count() -> Self {
+        ReduceConstraint { method: token::Aggregate::Count, var: None, within: Vec::new() }
+    }
+
+    pub fn new(method: token::Aggregate, var: impl Into<Variable>) -> Self {
+        ReduceConstraint { method, var: Some(var.into()), within: Vec::new() }
+    }
+
+    pub fn within(self, vars: impl IntoIterator<Item = impl Into<Variable>>) -> Self {
+        ReduceConstraint { within: vars.into_iter().map(Into::into).collect(), ..self }
+    }
+
+    pub fn variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
+        Box::new(self.var.iter().chain(self.within.iter()).map(Variable::as_ref))
+    }
+
+    fn validate_method(&self) -> Result {
+        match (self.method, &self.var) {
+            (token::Aggregate::First | token::Aggregate::Last | token::Aggregate::Percentile, _) => {
+                Err(TypeQLError::InvalidReduceMethod { method: self.method })?
+            }
+            (token::Aggregate::Count, Some(_)) => Err(TypeQLError::InvalidCountVariableArgument)?,
+            (token::Aggregate::Count, None) | (_, Some(_)) => Ok(()),
+            (method, None) => Err(TypeQLError::MissingAggregateVariable { method })?,
+        }
+    }
+}
+
+impl Validatable for ReduceConstraint {
+    fn validate(&self) -> Result {
+        collect_err(
+            [self.validate_method()].into_iter().chain(self.var.iter().chain(&self.within).map(Validatable::validate)),
+        )
+    }
+}
+
+impl fmt::Display for ReduceConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}(", token::Constraint::Assign, self.method)?;
+        if let Some(var) = &self.var {
+            write!(f, "{var}")?;
+        }
+        f.write_str(")")?;
+        if !self.within.is_empty() {
+            write!(f, " {} ", token::Constraint::Within)?;
+            write_joined!(f, ", ", self.within)?;
+        }
+        Ok(())
+    }
+}
@@ -4,12 +4,15 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use std::{collections::HashSet, fmt};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
 
 use crate::{
     common::{error::collect_err, string::indent, token, validatable::Validatable, Result},
-    pattern::{Conjunction, Normalisable, Pattern},
-    variable::variable::VariableRef,
+    pattern::{Conjunction, Normalisable, Pattern, Simplifiable},
+    variable::{variable::VariableRef, ValueVariable},
 };
 
 #[derive(Debug, Clone, Eq)]
@@ -44,6 +47,19 @@ impl Validatable for Disjunction {
     }
 }
 
+impl Disjunction {
+    pub(crate) fn simplify_with(&mut self, variable_types: &HashMap<ValueVariable, token::ValueType>) {
+        self.patterns.iter_mut().for_each(|pattern| pattern.simplify_with(variable_types));
+        self.normalised = None;
+    }
+}
+
+impl Simplifiable for Disjunction {
+    fn simplify(&mut self) {
+        self.simplify_with(&HashMap::new());
+    }
+}
+
 impl Normalisable for Disjunction {
     fn normalise(&mut self) -> Pattern {
         if self.normalised.is_none() {
@@ -66,6 +82,9 @@ impl Normalisable for Disjunction {
                     Pattern::Negation(negation) => {
                         vec![Conjunction::new(vec![negation.compute_normalised()]).into()].into_iter()
                     }
+                    Pattern::Optional(optional) => {
+                        vec![Conjunction::new(vec![optional.compute_normalised()]).into()].into_iter()
+                    }
                     Pattern::Statement(variable) => {
                         vec![Conjunction::new(vec![variable.clone().into()]).into()].into_iter()
                     }
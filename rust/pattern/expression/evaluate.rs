@@ -0,0 +1,260 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDateTime, TimeZone};
+
+use super::{value_type::format_types, Expression, Function, Operation};
+use crate::{
+    common::{decimal::Decimal, error::TypeQLError, token, Result},
+    pattern::Constant,
+    variable::Variable,
+};
+
+pub fn evaluate(expression: &Expression, bindings: &HashMap<Variable, Constant>) -> Result<Constant> {
+    match expression {
+        Expression::Constant(constant) => Ok(constant.clone()),
+        Expression::ThingVariable(variable) => bound_value(Variable::Concept(variable.clone()), bindings),
+        Expression::ValueVariable(variable) => bound_value(Variable::Value(variable.clone()), bindings),
+        Expression::Operation(operation) => evaluate_operation(operation, bindings),
+        Expression::Function(function) => evaluate_function(function, bindings),
+        Expression::Call(call) => Err(TypeQLError::FunctionCallNotEvaluable { call: call.clone() })?,
+    }
+}
+
+fn bound_value(variable: Variable, bindings: &HashMap<Variable, Constant>) -> Result<Constant> {
+    match bindings.get(&variable) {
+        Some(value) => Ok(value.clone()),
+        None => Err(TypeQLError::ExpressionVariableNotBound { variable })?,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Long(i64),
+    Double(f64),
+    Decimal(Decimal),
+}
+
+impl Number {
+    fn as_double(self) -> f64 {
+        match self {
+            Number::Long(long) => long as f64,
+            Number::Double(double) => double,
+            Number::Decimal(decimal) => decimal.as_double(),
+        }
+    }
+
+    fn as_decimal(self) -> Option<Decimal> {
+        match self {
+            Number::Long(long) => Some(Decimal::from(long)),
+            Number::Double(_) => None,
+            Number::Decimal(decimal) => Some(decimal),
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        match self {
+            Number::Long(long) => long == 0,
+            Number::Double(double) => double == 0.0,
+            Number::Decimal(decimal) => decimal == Decimal::from(0),
+        }
+    }
+}
+
+impl From<Number> for Constant {
+    fn from(number: Number) -> Self {
+        match number {
+            Number::Long(long) => Constant::Long(long),
+            Number::Double(double) => Constant::Double(double),
+            Number::Decimal(decimal) => Constant::Decimal(decimal),
+        }
+    }
+}
+
+fn number(operand: &Expression, expression: &Expression, bindings: &HashMap<Variable, Constant>) -> Result<Number> {
+    match evaluate(operand, bindings)? {
+        Constant::Long(long) => Ok(Number::Long(long)),
+        Constant::Double(double) => Ok(Number::Double(double)),
+        Constant::Decimal(decimal) => Ok(Number::Decimal(decimal)),
+        _ => {
+            Err(TypeQLError::InvalidExpressionOperandType { expression: expression.clone(), operand: operand.clone() })?
+        }
+    }
+}
+
+fn evaluate_operation(operation: &Operation, bindings: &HashMap<Variable, Constant>) -> Result<Constant> {
+    let expression = Expression::Operation(operation.clone());
+    let left = number(operation.left().unwrap(), &expression, bindings)?;
+    let right = number(operation.right().unwrap(), &expression, bindings)?;
+    let overflow = || TypeQLError::ExpressionOverflow { expression: expression.clone() };
+    let is_division = matches!(operation, Operation::Division { .. } | Operation::Modulo { .. });
+    if is_division && right.is_zero() {
+        Err(TypeQLError::ExpressionDivisionByZero { expression: expression.clone() })?
+    }
+
+    let result = match (left, right) {
+        (Number::Long(left), Number::Long(right)) => {
+            let result = match operation {
+                Operation::Addition { .. } => left.checked_add(right),
+                Operation::Subtraction { .. } => left.checked_sub(right),
+                Operation::Multiplication { .. } => left.checked_mul(right),
+                Operation::Division { .. } => left.checked_div(right),
+                Operation::Modulo { .. } => left.checked_rem(right),
+                Operation::Power { .. } if right < 0 => {
+                    return Ok(Constant::Double((left as f64).powf(right as f64)));
+                }
+                Operation::Power { .. } => u32::try_from(right).ok().and_then(|right| left.checked_pow(right)),
+            };
+            Number::Long(result.ok_or_else(overflow)?)
+        }
+        (left, right)
+            if left.as_decimal().is_some() && right.as_decimal().is_some() && is_decimal_closed(operation) =>
+        {
+            let (left, right) = (left.as_decimal().unwrap(), right.as_decimal().unwrap());
+            let result = match operation {
+                Operation::Addition { .. } => left.checked_add(right),
+                Operation::Subtraction { .. } => left.checked_sub(right),
+                _ => left.checked_mul(right),
+            };
+            Number::Decimal(result.ok_or_else(overflow)?)
+        }
+        (left, right) => {
+            let (left, right) = (left.as_double(), right.as_double());
+            Number::Double(match operation {
+                Operation::Addition { .. } => left + right,
+                Operation::Subtraction { .. } => left - right,
+                Operation::Multiplication { .. } => left * right,
+                Operation::Division { .. } => left / right,
+                Operation::Modulo { .. } => left % right,
+                Operation::Power { .. } => left.powf(right),
+            })
+        }
+    };
+    Ok(result.into())
+}
+
+// division, modulo and power of decimals are not exact, so they are computed as doubles
+fn is_decimal_closed(operation: &Operation) -> bool {
+    matches!(operation, Operation::Addition { .. } | Operation::Subtraction { .. } | Operation::Multiplication { .. })
+}
+
+fn evaluate_function(function: &Function, bindings: &HashMap<Variable, Constant>) -> Result<Constant> {
+    use token::Function::*;
+    function.validate_arity()?;
+    if function.function_name.argument_types(0).is_some() {
+        return evaluate_typed_function(function, bindings);
+    }
+    let expression = Expression::Function(function.clone());
+    let args = function.args.iter().map(|arg| number(arg, &expression, bindings)).collect::<Result<Vec<Number>>>()?;
+
+    let overflow = || TypeQLError::ExpressionOverflow { expression: expression.clone() };
+    let to_long = |double: f64| {
+        let is_long = double.is_finite() && double >= i64::MIN as f64 && double < i64::MAX as f64;
+        is_long.then_some(Number::Long(double as i64))
+    };
+    let result = match (function.function_name, args[0]) {
+        (Abs, Number::Long(long)) => Number::Long(long.checked_abs().ok_or_else(overflow)?),
+        (Abs, Number::Double(double)) => Number::Double(double.abs()),
+        (Abs, Number::Decimal(decimal)) if decimal < Decimal::from(0) => {
+            Number::Decimal(Decimal::from(0).checked_sub(decimal).ok_or_else(overflow)?)
+        }
+        (Abs, Number::Decimal(decimal)) => Number::Decimal(decimal),
+        (Ceil | Floor | Round, Number::Long(long)) => Number::Long(long),
+        (Ceil, Number::Double(double)) => to_long(double.ceil()).ok_or_else(overflow)?,
+        (Floor, Number::Double(double)) => to_long(double.floor()).ok_or_else(overflow)?,
+        (Round, Number::Double(double)) => to_long((double + 0.5).floor()).ok_or_else(overflow)?,
+        (Ceil, Number::Decimal(decimal)) => Number::Long(decimal.ceil().ok_or_else(overflow)?),
+        (Floor, Number::Decimal(decimal)) => Number::Long(decimal.floor()),
+        (Round, Number::Decimal(decimal)) => Number::Long(decimal.round().ok_or_else(overflow)?),
+        (Max | Min, _) => {
+            let is_max = function.function_name == Max;
+            let longs: Option<Vec<i64>> = args
+                .iter()
+                .map(|arg| match arg {
+                    Number::Long(long) => Some(*long),
+                    Number::Double(_) | Number::Decimal(_) => None,
+                })
+                .collect();
+            let decimals: Option<Vec<Decimal>> = args.iter().map(|arg| arg.as_decimal()).collect();
+            if let Some(longs) = longs {
+                let longs = longs.into_iter();
+                Number::Long(if is_max { longs.max() } else { longs.min() }.unwrap())
+            } else if let Some(decimals) = decimals {
+                let decimals = decimals.into_iter();
+                Number::Decimal(if is_max { decimals.max() } else { decimals.min() }.unwrap())
+            } else {
+                let doubles = args.iter().map(|arg| arg.as_double());
+                Number::Double(if is_max { doubles.fold(f64::MIN, f64::max) } else { doubles.fold(f64::MAX, f64::min) })
+            }
+        }
+        _ => unreachable!(),
+    };
+    Ok(result.into())
+}
+
+fn evaluate_typed_function(function: &Function, bindings: &HashMap<Variable, Constant>) -> Result<Constant> {
+    use token::Function::*;
+    let function_name = function.function_name;
+    let args = function.args.iter().map(|arg| evaluate(arg, bindings)).collect::<Result<Vec<Constant>>>()?;
+    for (index, value) in args.iter().enumerate() {
+        let expected = function_name.argument_types(index).unwrap();
+        if !value.value_type().is_some_and(|value_type| expected.contains(&value_type)) {
+            Err(TypeQLError::InvalidFunctionArgument {
+                function: function_name,
+                expected: format_types(expected),
+                value: value.clone(),
+            })?
+        }
+    }
+    let string = |index: usize| match &args[index] {
+        Constant::String(string) => string.as_str(),
+        _ => unreachable!(),
+    };
+    let long = |index: usize| match &args[index] {
+        Constant::Long(long) => *long,
+        _ => unreachable!(),
+    };
+    let date_time = match &args[0] {
+        Constant::Date(date) => Some(NaiveDateTime::from(*date)),
+        Constant::DateTime(date_time) => Some(*date_time),
+        Constant::DateTimeTZ(date_time) => Some(date_time.naive_local()),
+        _ => None,
+    };
+
+    let result = match function_name {
+        Length => Constant::Long(string(0).chars().count() as i64),
+        Lowercase => Constant::String(string(0).to_lowercase()),
+        Uppercase => Constant::String(string(0).to_uppercase()),
+        Substring => {
+            let start = usize::try_from(long(1)).unwrap_or(0);
+            let length = usize::try_from(long(2)).unwrap_or(0);
+            Constant::String(string(0).chars().skip(start).take(length).collect())
+        }
+        Concat => Constant::String((0..args.len()).map(string).collect()),
+        Year => Constant::Long(date_time.unwrap().year() as i64),
+        Month => Constant::Long(date_time.unwrap().month() as i64),
+        Day => Constant::Long(date_time.unwrap().day() as i64),
+        AddDuration | SubtractDuration => {
+            let Constant::Duration(duration) = &args[1] else { unreachable!() };
+            let result = match function_name {
+                AddDuration => duration.add_to(date_time.unwrap()),
+                _ => duration.subtract_from(date_time.unwrap()),
+            };
+            let out_of_range = || TypeQLError::ExpressionDateTimeOutOfRange { expression: function.clone().into() };
+            let result = result.ok_or_else(out_of_range)?;
+            match &args[0] {
+                Constant::DateTimeTZ(date_time) => Constant::DateTimeTZ(
+                    date_time.offset().from_local_datetime(&result).single().ok_or_else(out_of_range)?,
+                ),
+                _ => Constant::DateTime(result),
+            }
+        }
+        Abs | Ceil | Floor | Max | Min | Round => unreachable!(),
+    };
+    Ok(result)
+}
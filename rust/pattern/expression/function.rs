@@ -7,7 +7,12 @@
 use std::fmt;
 
 use super::Expression;
-use crate::{common::token, pattern::LeftOperand, variable::variable::VariableRef, write_joined};
+use crate::{
+    common::{error::TypeQLError, token, Result},
+    pattern::{Label, LeftOperand},
+    variable::variable::VariableRef,
+    write_joined,
+};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Function {
@@ -16,6 +21,21 @@ pub struct Function {
 }
 
 impl Function {
+    pub(crate) fn validate_arity(&self) -> Result {
+        use token::Function::*;
+        let arity = self.args.len();
+        let is_valid_arity = match self.function_name {
+            Abs | Ceil | Floor | Round | Length | Lowercase | Uppercase | Year | Month | Day => arity == 1,
+            AddDuration | SubtractDuration => arity == 2,
+            Substring => arity == 3,
+            Max | Min | Concat => arity > 0,
+        };
+        if !is_valid_arity {
+            Err(TypeQLError::InvalidFunctionArity { function: self.function_name, arity })?
+        }
+        Ok(())
+    }
+
     pub fn variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
         Box::new(self.args.iter().flat_map(|expr| expr.variables()))
     }
@@ -30,3 +50,29 @@ impl fmt::Display for Function {
         write!(f, ")")
     }
 }
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FunctionCall {
+    pub(crate) label: Label,
+    pub(crate) args: Vec<Expression>,
+}
+
+impl FunctionCall {
+    pub fn new(label: Label, args: Vec<Expression>) -> Self {
+        FunctionCall { label, args }
+    }
+
+    pub fn variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
+        Box::new(self.args.iter().flat_map(|expr| expr.variables()))
+    }
+}
+
+impl LeftOperand for FunctionCall {}
+
+impl fmt::Display for FunctionCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}(", self.label)?;
+        write_joined!(f, ", ", self.args)?;
+        write!(f, ")")
+    }
+}
@@ -6,23 +6,31 @@
 
 use std::{fmt, iter};
 
-use chrono::NaiveDateTime;
-pub use function::Function;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+pub use evaluate::evaluate;
+pub use function::{Function, FunctionCall};
 pub use operation::Operation;
+pub use simplify::Simplifiable;
+pub(crate) use value_type::{assignments, infer_value_types};
 
 use crate::{
+    common::{decimal::Decimal, duration::Duration},
     pattern::Constant,
     variable::{variable::VariableRef, ConceptVariable, ValueVariable, Variable},
 };
 
 pub mod builder;
+mod evaluate;
 mod function;
 mod operation;
+mod simplify;
+mod value_type;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Expression {
     Operation(Operation),
     Function(Function),
+    Call(FunctionCall),
     Constant(Constant),
     ThingVariable(ConceptVariable),
     ValueVariable(ValueVariable),
@@ -33,6 +41,7 @@ impl fmt::Display for Expression {
         match self {
             Expression::Operation(operation) => write!(f, "{operation}"),
             Expression::Function(function) => write!(f, "{function}"),
+            Expression::Call(call) => write!(f, "{call}"),
             Expression::Constant(constant) => write!(f, "{constant}"),
             Expression::ThingVariable(variable) => write!(f, "{variable}"),
             Expression::ValueVariable(variable) => write!(f, "{variable}"),
@@ -45,6 +54,7 @@ impl Expression {
         match self {
             Expression::Operation(operation) => operation.variables(),
             Expression::Function(function) => function.variables(),
+            Expression::Call(call) => call.variables(),
             Expression::Constant(_constant) => Box::new(iter::empty()),
             Expression::ThingVariable(variable) => Box::new(iter::once(VariableRef::Concept(variable))),
             Expression::ValueVariable(variable) => Box::new(iter::once(VariableRef::Value(variable))),
@@ -64,6 +74,12 @@ impl From<Function> for Expression {
     }
 }
 
+impl From<FunctionCall> for Expression {
+    fn from(call: FunctionCall) -> Self {
+        Self::Call(call)
+    }
+}
+
 impl From<Constant> for Expression {
     fn from(constant: Constant) -> Self {
         Self::Constant(constant)
@@ -121,8 +137,32 @@ impl From<&str> for Expression {
     }
 }
 
+impl From<NaiveDate> for Expression {
+    fn from(value: NaiveDate) -> Self {
+        Self::Constant(value.into())
+    }
+}
+
 impl From<NaiveDateTime> for Expression {
     fn from(value: NaiveDateTime) -> Self {
         Self::Constant(value.into())
     }
 }
+
+impl From<DateTime<FixedOffset>> for Expression {
+    fn from(value: DateTime<FixedOffset>) -> Self {
+        Self::Constant(value.into())
+    }
+}
+
+impl From<Decimal> for Expression {
+    fn from(value: Decimal) -> Self {
+        Self::Constant(value.into())
+    }
+}
+
+impl From<Duration> for Expression {
+    fn from(value: Duration) -> Self {
+        Self::Constant(value.into())
+    }
+}
@@ -33,7 +33,7 @@ impl Operation {
         }
     }
 
-    fn left(&self) -> Option<&Expression> {
+    pub(crate) fn left(&self) -> Option<&Expression> {
         match self {
             Operation::Addition { left, right: _right }
             | Operation::Subtraction { left, right: _right }
@@ -44,7 +44,7 @@ impl Operation {
         }
     }
 
-    fn right(&self) -> Option<&Expression> {
+    pub(crate) fn right(&self) -> Option<&Expression> {
         match self {
             Operation::Addition { left: _left, right }
             | Operation::Subtraction { left: _left, right }
@@ -55,7 +55,7 @@ impl Operation {
         }
     }
 
-    fn op_token(&self) -> token::ArithmeticOperator {
+    pub(crate) fn op_token(&self) -> token::ArithmeticOperator {
         match self {
             Operation::Addition { .. } => token::ArithmeticOperator::Add,
             Operation::Subtraction { .. } => token::ArithmeticOperator::Subtract,
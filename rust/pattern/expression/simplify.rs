@@ -0,0 +1,86 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{collections::HashMap, mem};
+
+use super::{evaluate, Expression, Function, FunctionCall, Operation};
+use crate::{common::token, pattern::Constant, variable::ValueVariable};
+
+pub trait Simplifiable {
+    fn simplify(&mut self);
+}
+
+impl Expression {
+    pub(crate) fn simplify_with(&mut self, variable_types: &HashMap<ValueVariable, token::ValueType>) {
+        *self = simplified(mem::replace(self, Expression::Constant(Constant::Long(0))), variable_types);
+    }
+}
+
+impl Simplifiable for Expression {
+    fn simplify(&mut self) {
+        self.simplify_with(&HashMap::new());
+    }
+}
+
+fn simplified(expression: Expression, variable_types: &HashMap<ValueVariable, token::ValueType>) -> Expression {
+    match expression {
+        Expression::Operation(operation) => simplified_operation(operation, variable_types),
+        Expression::Function(function) => simplified_function(function, variable_types),
+        Expression::Call(FunctionCall { label, args }) => Expression::Call(FunctionCall {
+            label,
+            args: args.into_iter().map(|arg| simplified(arg, variable_types)).collect(),
+        }),
+        expression => expression,
+    }
+}
+
+fn simplified_operation(operation: Operation, variable_types: &HashMap<ValueVariable, token::ValueType>) -> Expression {
+    use Operation::*;
+    let simplify = |expression: Box<Expression>| Box::new(simplified(*expression, variable_types));
+    let operation = match operation {
+        Addition { left, right } => Addition { left: simplify(left), right: simplify(right) },
+        Subtraction { left, right } => Subtraction { left: simplify(left), right: simplify(right) },
+        Multiplication { left, right } => Multiplication { left: simplify(left), right: simplify(right) },
+        Division { left, right } => Division { left: simplify(left), right: simplify(right) },
+        Modulo { left, right } => Modulo { left: simplify(left), right: simplify(right) },
+        Power { left, right } => Power { left: simplify(left), right: simplify(right) },
+    };
+    let is_long = |expression: &Expression, long: i64| matches!(expression, Expression::Constant(Constant::Long(value)) if *value == long);
+    // an operand of unknown type is only dropped by identities that keep the type of any numeric operand
+    let is_identity = |operand: &Expression, keeps_any_type: bool| match operand.value_type(variable_types) {
+        Ok(Some(token::ValueType::Long | token::ValueType::Double)) => true,
+        Ok(None | Some(token::ValueType::Decimal)) => keeps_any_type,
+        _ => false,
+    };
+    match operation {
+        Addition { left, right } if is_long(&right, 0) && is_identity(&left, true) => *left,
+        Addition { left, right } if is_long(&left, 0) && is_identity(&right, true) => *right,
+        Subtraction { left, right } if is_long(&right, 0) && is_identity(&left, true) => *left,
+        Multiplication { left, right } if is_long(&right, 1) && is_identity(&left, true) => *left,
+        Multiplication { left, right } if is_long(&left, 1) && is_identity(&right, true) => *right,
+        Division { left, right } | Power { left, right } if is_long(&right, 1) && is_identity(&left, false) => *left,
+        operation => folded(Expression::Operation(operation)),
+    }
+}
+
+fn simplified_function(function: Function, variable_types: &HashMap<ValueVariable, token::ValueType>) -> Expression {
+    let Function { function_name, args } = function;
+    let mut args: Vec<Expression> = args.into_iter().map(|arg| simplified(arg, variable_types)).collect();
+    match function_name {
+        token::Function::Max | token::Function::Min if args.len() == 1 => args.pop().unwrap(),
+        _ => folded(Expression::Function(Function { function_name, args })),
+    }
+}
+
+fn folded(expression: Expression) -> Expression {
+    if expression.variables().next().is_some() {
+        return expression;
+    }
+    match evaluate(&expression, &HashMap::new()) {
+        Ok(value) => Expression::Constant(value),
+        Err(_) => expression,
+    }
+}
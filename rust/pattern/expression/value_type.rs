@@ -0,0 +1,184 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::HashMap;
+
+use super::{Expression, Function, Operation};
+use crate::{
+    common::{error::TypeQLError, token, Result},
+    pattern::{Constant, Pattern, Statement, ValueStatement},
+    variable::ValueVariable,
+};
+
+pub(crate) fn assignments(patterns: &[Pattern]) -> impl Iterator<Item = (&ValueVariable, &Expression)> {
+    patterns.iter().filter_map(|pattern| match pattern {
+        Pattern::Statement(Statement::Value(ValueStatement { variable, assign_constraint: Some(assign), .. })) => {
+            Some((variable, &assign.expression))
+        }
+        _ => None,
+    })
+}
+
+pub(crate) fn infer_value_types(
+    patterns: &[Pattern],
+    outer_types: &HashMap<ValueVariable, token::ValueType>,
+) -> HashMap<ValueVariable, token::ValueType> {
+    let mut variable_types = outer_types.clone();
+    loop {
+        let inferred: Vec<_> = assignments(patterns)
+            .filter(|(variable, _)| !variable_types.contains_key(*variable))
+            .filter_map(|(variable, expression)| match expression.value_type(&variable_types) {
+                Ok(Some(value_type)) => Some((variable.clone(), value_type)),
+                _ => None,
+            })
+            .collect();
+        if inferred.is_empty() {
+            return variable_types;
+        }
+        variable_types.extend(inferred);
+    }
+}
+
+impl Expression {
+    pub fn value_type(
+        &self,
+        variable_types: &HashMap<ValueVariable, token::ValueType>,
+    ) -> Result<Option<token::ValueType>> {
+        match self {
+            Expression::Constant(constant) => Ok(constant.value_type()),
+            Expression::ThingVariable(_) | Expression::Call(_) => Ok(None),
+            Expression::ValueVariable(variable) => Ok(variable_types.get(variable).copied()),
+            Expression::Operation(operation) => operation_type(operation, variable_types),
+            Expression::Function(function) => function_type(function, variable_types),
+        }
+    }
+}
+
+fn numeric_type(
+    operand: &Expression,
+    expression: &Expression,
+    variable_types: &HashMap<ValueVariable, token::ValueType>,
+) -> Result<Option<token::ValueType>> {
+    match operand.value_type(variable_types)? {
+        value_type @ (None | Some(token::ValueType::Long | token::ValueType::Double | token::ValueType::Decimal)) => {
+            Ok(value_type)
+        }
+        Some(_) => {
+            Err(TypeQLError::InvalidExpressionOperandType { expression: expression.clone(), operand: operand.clone() })?
+        }
+    }
+}
+
+fn promoted_type(operand_types: impl IntoIterator<Item = Option<token::ValueType>>) -> Option<token::ValueType> {
+    let mut promoted = Some(token::ValueType::Long);
+    for operand_type in operand_types {
+        match operand_type {
+            Some(token::ValueType::Double) => return Some(token::ValueType::Double),
+            None => promoted = None,
+            Some(token::ValueType::Decimal) if promoted.is_some() => promoted = Some(token::ValueType::Decimal),
+            Some(_) => (),
+        }
+    }
+    promoted
+}
+
+fn operation_type(
+    operation: &Operation,
+    variable_types: &HashMap<ValueVariable, token::ValueType>,
+) -> Result<Option<token::ValueType>> {
+    let expression = Expression::Operation(operation.clone());
+    let left = numeric_type(operation.left().unwrap(), &expression, variable_types)?;
+    let right = numeric_type(operation.right().unwrap(), &expression, variable_types)?;
+    match (operation, operation.right().unwrap(), promoted_type([left, right])) {
+        (Operation::Power { .. }, Expression::Constant(Constant::Long(exponent)), _) if *exponent < 0 => {
+            Ok(Some(token::ValueType::Double))
+        }
+        // a long raised to a long is a double only if the exponent is negative, which is known once it is evaluated
+        (Operation::Power { .. }, exponent, Some(token::ValueType::Long))
+            if !matches!(exponent, Expression::Constant(_)) =>
+        {
+            Ok(None)
+        }
+        (
+            Operation::Division { .. } | Operation::Modulo { .. } | Operation::Power { .. },
+            _,
+            Some(token::ValueType::Decimal),
+        ) => Ok(Some(token::ValueType::Double)),
+        (_, _, promoted) => Ok(promoted),
+    }
+}
+
+fn function_type(
+    function: &Function,
+    variable_types: &HashMap<ValueVariable, token::ValueType>,
+) -> Result<Option<token::ValueType>> {
+    use token::Function::*;
+    function.validate_arity()?;
+    if function.function_name.argument_types(0).is_some() {
+        let mut arg_types = Vec::new();
+        for (index, arg) in function.args.iter().enumerate() {
+            let expected = function.function_name.argument_types(index).unwrap();
+            let arg_type = arg.value_type(variable_types)?;
+            match arg_type {
+                Some(value_type) if !expected.contains(&value_type) => Err(TypeQLError::InvalidFunctionArgumentType {
+                    function: function.function_name,
+                    expected: format_types(expected),
+                    value_type,
+                })?,
+                _ => arg_types.push(arg_type),
+            }
+        }
+        return Ok(function.function_name.result_type(arg_types[0]));
+    }
+
+    let expression = Expression::Function(function.clone());
+    let arg_types = function
+        .args
+        .iter()
+        .map(|arg| numeric_type(arg, &expression, variable_types))
+        .collect::<Result<Vec<Option<token::ValueType>>>>()?;
+    match function.function_name {
+        Ceil | Floor | Round => Ok(Some(token::ValueType::Long)),
+        _ => Ok(promoted_type(arg_types)),
+    }
+}
+
+const DATE_TYPES: &[token::ValueType] =
+    &[token::ValueType::Date, token::ValueType::DateTime, token::ValueType::DateTimeTZ];
+
+impl token::Function {
+    pub(crate) fn argument_types(&self, index: usize) -> Option<&'static [token::ValueType]> {
+        use token::Function::*;
+        match (self, index) {
+            (Abs | Ceil | Floor | Round | Max | Min, _) => None,
+            (Length | Lowercase | Uppercase | Concat, _) | (Substring, 0) => Some(&[token::ValueType::String]),
+            (Substring, _) => Some(&[token::ValueType::Long]),
+            (Year | Month | Day, _) | (AddDuration | SubtractDuration, 0) => Some(DATE_TYPES),
+            (AddDuration | SubtractDuration, _) => Some(&[token::ValueType::Duration]),
+        }
+    }
+
+    fn result_type(&self, first_arg_type: Option<token::ValueType>) -> Option<token::ValueType> {
+        use token::Function::*;
+        match self {
+            Abs | Ceil | Floor | Round | Max | Min => None,
+            Length | Year | Month | Day => Some(token::ValueType::Long),
+            Lowercase | Uppercase | Substring | Concat => Some(token::ValueType::String),
+            AddDuration | SubtractDuration => match first_arg_type? {
+                token::ValueType::DateTimeTZ => Some(token::ValueType::DateTimeTZ),
+                _ => Some(token::ValueType::DateTime),
+            },
+        }
+    }
+}
+
+pub(super) fn format_types(value_types: &[token::ValueType]) -> String {
+    let quoted: Vec<String> = value_types.iter().map(|value_type| format!("'{value_type}'")).collect();
+    match quoted.split_last() {
+        Some((last, init)) if !init.is_empty() => format!("{} or {last}", init.join(", ")),
+        _ => quoted.concat(),
+    }
+}
@@ -10,7 +10,7 @@ use crate::common::{
     error::TypeQLError, identifier::is_valid_label_identifier, token, validatable::Validatable, Result,
 };
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, Hash, PartialEq)]
 pub struct Label {
     pub scope: Option<String>,
     pub name: String,
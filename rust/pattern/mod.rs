@@ -4,20 +4,31 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use std::{collections::HashSet, fmt};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
 
 pub use conjunction::Conjunction;
 pub use constant::Constant;
+pub(crate) use constraint::validate_value_annotations;
 pub use constraint::{
-    AbstractConstraint, Annotation, AssignConstraint, HasConstraint, IIDConstraint, IsConstraint, IsExplicit,
-    IsaConstraint, LabelConstraint, OwnsConstraint, PlaysConstraint, Predicate, RegexConstraint, RelatesConstraint,
-    RelationConstraint, RolePlayerConstraint, SubConstraint, Value, ValueTypeConstraint,
+    AbstractConstraint, Annotation, AssignConstraint, Cardinality, HasConstraint, IIDConstraint, InConstraint,
+    IsConstraint, IsExplicit, IsaConstraint, LabelConstraint, OwnsConstraint, PlaysConstraint, Predicate, Range,
+    ReduceConstraint, RegexConstraint, RelatesConstraint, RelationConstraint, RolePlayerConstraint, SubConstraint,
+    Value, ValueType, ValueTypeConstraint,
 };
 pub use disjunction::Disjunction;
-pub use expression::{Expression, Function, Operation};
+pub(crate) use expression::{assignments, infer_value_types};
+pub use expression::{evaluate, Expression, Function, FunctionCall, Operation, Simplifiable};
 pub use label::Label;
 pub use negation::Negation;
-pub use schema::{Rule, RuleLabel};
+pub use optional::Optional;
+pub(crate) use schema::validate_functions_are_not_recursive;
+pub use schema::{
+    Concludable, FunctionArgument, FunctionLabel, FunctionSignature, FunctionSignatureMatch, FunctionType, Rule,
+    RuleCycle, RuleDependency, RuleDependencyGraph, RuleLabel, RuleStratification, SchemaFunction, Struct, StructField,
+};
 pub(crate) use statement::LeftOperand;
 pub use statement::{
     ConceptConstrainable, ConceptStatement, ConceptStatementBuilder, ExpressionBuilder, Statement, ThingStatement,
@@ -26,9 +37,9 @@ pub use statement::{
 
 pub use crate::common::variables_retrieved::VariablesRetrieved;
 use crate::{
-    common::{validatable::Validatable, Result},
+    common::{token, validatable::Validatable, Result},
     enum_getter, enum_wrapper,
-    variable::variable::VariableRef,
+    variable::{variable::VariableRef, ValueVariable},
 };
 
 mod conjunction;
@@ -38,6 +49,7 @@ mod disjunction;
 mod expression;
 mod label;
 mod negation;
+mod optional;
 mod schema;
 pub(crate) mod statement;
 #[cfg(test)]
@@ -48,6 +60,7 @@ pub enum Pattern {
     Conjunction(Conjunction),
     Disjunction(Disjunction),
     Negation(Negation),
+    Optional(Optional),
     Statement(Statement),
 }
 
@@ -57,6 +70,7 @@ impl Pattern {
             Pattern::Conjunction(conjunction) => conjunction.variables_recursive(),
             Pattern::Disjunction(disjunction) => disjunction.variables_recursive(),
             Pattern::Negation(negation) => negation.variables_recursive(),
+            Pattern::Optional(optional) => optional.variables_recursive(),
             Pattern::Statement(statement) => statement.variables(),
         })
     }
@@ -66,6 +80,7 @@ impl Pattern {
             Pattern::Conjunction(conjunction) => conjunction.validate_is_bounded_by(bounds),
             Pattern::Disjunction(disjunction) => disjunction.validate_is_bounded_by(bounds),
             Pattern::Negation(negation) => negation.validate_is_bounded_by(bounds),
+            Pattern::Optional(optional) => optional.validate_is_bounded_by(bounds),
             Pattern::Statement(statement) => statement.validate_is_bounded_by(bounds),
         }
     }
@@ -75,6 +90,7 @@ enum_getter! { Pattern
     into_conjunction(Conjunction) => Conjunction,
     into_disjunction(Disjunction) => Disjunction,
     into_negation(Negation) => Negation,
+    into_optional(Optional) => Optional,
     into_statement(Statement) => Statement,
 }
 
@@ -82,6 +98,7 @@ enum_wrapper! { Pattern
     Conjunction => Conjunction,
     Disjunction => Disjunction,
     Negation => Negation,
+    Optional => Optional,
     Statement => Statement,
 }
 
@@ -91,6 +108,7 @@ impl Validatable for Pattern {
             Pattern::Conjunction(conjunction) => conjunction.validate(),
             Pattern::Disjunction(disjunction) => disjunction.validate(),
             Pattern::Negation(negation) => negation.validate(),
+            Pattern::Optional(optional) => optional.validate(),
             Pattern::Statement(statement) => statement.validate(),
         }
     }
@@ -102,6 +120,7 @@ impl VariablesRetrieved for Pattern {
             Pattern::Conjunction(conjunction) => conjunction.retrieved_variables(),
             Pattern::Disjunction(disjunction) => disjunction.variables_recursive(),
             Pattern::Negation(negation) => negation.variables_recursive(),
+            Pattern::Optional(optional) => optional.retrieved_variables(),
             Pattern::Statement(statement) => statement.variables(),
         }
     }
@@ -118,6 +137,7 @@ impl Normalisable for Pattern {
             Pattern::Conjunction(conjunction) => conjunction.normalise(),
             Pattern::Disjunction(disjunction) => disjunction.normalise(),
             Pattern::Negation(negation) => negation.normalise(),
+            Pattern::Optional(optional) => optional.normalise(),
             Pattern::Statement(statement) => statement.normalise(),
         }
     }
@@ -127,11 +147,30 @@ impl Normalisable for Pattern {
             Pattern::Conjunction(conjunction) => conjunction.compute_normalised(),
             Pattern::Disjunction(disjunction) => disjunction.compute_normalised(),
             Pattern::Negation(negation) => negation.compute_normalised(),
+            Pattern::Optional(optional) => optional.compute_normalised(),
             Pattern::Statement(statement) => statement.compute_normalised(),
         }
     }
 }
 
+impl Pattern {
+    pub(crate) fn simplify_with(&mut self, variable_types: &HashMap<ValueVariable, token::ValueType>) {
+        match self {
+            Pattern::Conjunction(conjunction) => conjunction.simplify_with(variable_types),
+            Pattern::Disjunction(disjunction) => disjunction.simplify_with(variable_types),
+            Pattern::Negation(negation) => negation.simplify_with(variable_types),
+            Pattern::Optional(optional) => optional.simplify_with(variable_types),
+            Pattern::Statement(statement) => statement.simplify_with(variable_types),
+        }
+    }
+}
+
+impl Simplifiable for Pattern {
+    fn simplify(&mut self) {
+        self.simplify_with(&HashMap::new());
+    }
+}
+
 impl From<ConceptStatement> for Pattern {
     fn from(statement: ConceptStatement) -> Self {
         Statement::from(statement).into()
@@ -163,6 +202,7 @@ impl fmt::Display for Pattern {
             Conjunction(conjunction) => write!(f, "{conjunction}"),
             Disjunction(disjunction) => write!(f, "{disjunction}"),
             Negation(negation) => write!(f, "{negation}"),
+            Optional(optional) => write!(f, "{optional}"),
             Statement(statement) => write!(f, "{statement}"),
         }
     }
@@ -172,19 +212,28 @@ impl fmt::Display for Pattern {
 pub enum Definable {
     RuleDeclaration(RuleLabel),
     RuleDefinition(Rule),
+    FunctionDeclaration(FunctionLabel),
+    FunctionDefinition(SchemaFunction),
     TypeStatement(TypeStatement),
+    Struct(Struct),
 }
 
 enum_getter! { Definable
     into_rule_declaration(RuleDeclaration) => RuleLabel,
     into_rule(RuleDefinition) => Rule,
+    into_function_declaration(FunctionDeclaration) => FunctionLabel,
+    into_function(FunctionDefinition) => SchemaFunction,
     into_type_statement(TypeStatement) => TypeStatement,
+    into_struct(Struct) => Struct,
 }
 
 enum_wrapper! { Definable
     RuleLabel => RuleDeclaration,
     Rule => RuleDefinition,
+    FunctionLabel => FunctionDeclaration,
+    SchemaFunction => FunctionDefinition,
     TypeStatement => TypeStatement,
+    Struct => Struct,
 }
 
 impl Validatable for Definable {
@@ -192,7 +241,10 @@ impl Validatable for Definable {
         match self {
             Definable::RuleDeclaration(rule) => rule.validate(),
             Definable::RuleDefinition(rule) => rule.validate(),
+            Definable::FunctionDeclaration(function) => function.validate(),
+            Definable::FunctionDefinition(function) => function.validate(),
             Definable::TypeStatement(statement) => statement.validate(),
+            Definable::Struct(struct_) => struct_.validate(),
         }
     }
 }
@@ -202,7 +254,10 @@ impl fmt::Display for Definable {
         match self {
             Definable::RuleDeclaration(rule_declaration) => write!(f, "{rule_declaration}"),
             Definable::RuleDefinition(rule) => write!(f, "{rule}"),
+            Definable::FunctionDeclaration(function_declaration) => write!(f, "{function_declaration}"),
+            Definable::FunctionDefinition(function) => write!(f, "{function}"),
             Definable::TypeStatement(statement) => write!(f, "{statement}"),
+            Definable::Struct(struct_) => write!(f, "{struct_}"),
         }
     }
 }
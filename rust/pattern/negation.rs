@@ -5,12 +5,12 @@
  */
 
 use core::fmt;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     common::{error::TypeQLError, string::indent, token, validatable::Validatable, Result},
-    pattern::{Conjunction, Disjunction, Normalisable, Pattern},
-    variable::variable::VariableRef,
+    pattern::{Conjunction, Disjunction, Normalisable, Pattern, Simplifiable},
+    variable::{variable::VariableRef, ValueVariable},
 };
 
 #[derive(Debug, Clone, Eq)]
@@ -48,6 +48,19 @@ impl Validatable for Negation {
     }
 }
 
+impl Negation {
+    pub(crate) fn simplify_with(&mut self, variable_types: &HashMap<ValueVariable, token::ValueType>) {
+        self.pattern.simplify_with(variable_types);
+        self.normalised = None;
+    }
+}
+
+impl Simplifiable for Negation {
+    fn simplify(&mut self) {
+        self.simplify_with(&HashMap::new());
+    }
+}
+
 impl Normalisable for Negation {
     fn normalise(&mut self) -> Pattern {
         if self.normalised.is_none() {
@@ -61,6 +74,9 @@ impl Normalisable for Negation {
             Pattern::Conjunction(conjunction) => conjunction.compute_normalised(),
             Pattern::Disjunction(disjunction) => disjunction.compute_normalised(),
             Pattern::Negation(_) => panic!("{}", TypeQLError::RedundantNestedNegation),
+            Pattern::Optional(optional) => {
+                Disjunction::new(vec![Conjunction::new(vec![optional.compute_normalised()]).into()]).into()
+            }
             Pattern::Statement(variable) => {
                 Disjunction::new(vec![Conjunction::new(vec![variable.clone().into()]).into()]).into()
             }
@@ -0,0 +1,128 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use core::fmt;
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    common::{error::TypeQLError, string::indent, token, validatable::Validatable, Result},
+    pattern::{Conjunction, Disjunction, Normalisable, Pattern, Simplifiable, VariablesRetrieved},
+    variable::{variable::VariableRef, ValueVariable},
+};
+
+#[derive(Debug, Clone, Eq)]
+pub struct Optional {
+    pub pattern: Box<Pattern>,
+    normalised: Option<Box<Optional>>,
+}
+
+impl PartialEq for Optional {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+    }
+}
+
+impl Optional {
+    pub fn new(pattern: Pattern) -> Self {
+        Self { pattern: Box::new(pattern), normalised: None }
+    }
+
+    pub fn variables_recursive(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
+        self.pattern.variables_recursive()
+    }
+
+    pub fn validate_is_bounded_by(&self, bounds: &HashSet<VariableRef<'_>>) -> Result {
+        if self.pattern.retrieved_variables().all(|variable| !variable.is_name() || !bounds.contains(&variable)) {
+            Err(TypeQLError::MatchHasUnboundedNestedPattern { pattern: self.clone().into() })?
+        }
+        self.pattern.validate_is_bounded_by(bounds)
+    }
+}
+
+impl VariablesRetrieved for Optional {
+    fn retrieved_variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
+        match self.pattern.as_ref() {
+            Pattern::Conjunction(conjunction) => {
+                Box::new(conjunction.retrieved_variables().chain(conjunction.patterns.iter().flat_map(|pattern| {
+                    match pattern {
+                        Pattern::Optional(optional) => optional.retrieved_variables(),
+                        _ => Box::new(std::iter::empty()),
+                    }
+                })))
+            }
+            pattern => pattern.retrieved_variables(),
+        }
+    }
+}
+
+impl Validatable for Optional {
+    fn validate(&self) -> Result {
+        match self.pattern.as_ref() {
+            Pattern::Optional(_) => Err(TypeQLError::RedundantNestedOptional)?,
+            pattern => pattern.validate(),
+        }
+    }
+}
+
+impl Optional {
+    pub(crate) fn simplify_with(&mut self, variable_types: &HashMap<ValueVariable, token::ValueType>) {
+        self.pattern.simplify_with(variable_types);
+        self.normalised = None;
+    }
+}
+
+impl Simplifiable for Optional {
+    fn simplify(&mut self) {
+        self.simplify_with(&HashMap::new());
+    }
+}
+
+// kept as a conjunct: splitting answers on whether it matches is not a disjunction
+impl Normalisable for Optional {
+    fn normalise(&mut self) -> Pattern {
+        if self.normalised.is_none() {
+            self.normalised = Some(Box::new(self.compute_normalised().into_optional()));
+        }
+        self.normalised.as_ref().unwrap().as_ref().clone().into()
+    }
+
+    fn compute_normalised(&self) -> Pattern {
+        Optional::new(match self.pattern.as_ref() {
+            Pattern::Conjunction(conjunction) => conjunction.compute_normalised(),
+            Pattern::Disjunction(disjunction) => disjunction.compute_normalised(),
+            Pattern::Optional(_) => panic!("{}", TypeQLError::RedundantNestedOptional),
+            pattern => Disjunction::new(vec![Conjunction::new(vec![pattern.compute_normalised()]).into()]).into(),
+        })
+        .into()
+    }
+}
+
+impl fmt::Display for Optional {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pattern_string = self.pattern.to_string();
+        if matches!(*self.pattern, Pattern::Conjunction(_)) {
+            write!(f, "{} {}", token::LogicOperator::Try, pattern_string)
+        } else if pattern_string.lines().count() > 1 {
+            write!(
+                f,
+                "{} {}\n{};\n{}",
+                token::LogicOperator::Try,
+                token::Char::CurlyLeft,
+                indent(&pattern_string),
+                token::Char::CurlyRight
+            )
+        } else {
+            write!(
+                f,
+                "{} {} {}; {}",
+                token::LogicOperator::Try,
+                token::Char::CurlyLeft,
+                pattern_string,
+                token::Char::CurlyRight
+            )
+        }
+    }
+}
@@ -0,0 +1,417 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    common::token,
+    pattern::{Conjunction, HasConstraint, Pattern, Rule, Statement, ThingStatement, TypeStatement},
+    variable::{ConceptVariable, TypeReference},
+    write_joined, Label,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RuleDependency {
+    pub from: Label,
+    pub to: Label,
+    pub concludes: Concludable,
+    pub is_negated: bool,
+}
+
+impl fmt::Display for RuleDependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {} [", self.from, self.to)?;
+        if self.is_negated {
+            write!(f, "{} ", token::LogicOperator::Not)?;
+        }
+        write!(f, "{}]", self.concludes)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Concludable {
+    Relation(Label),
+    Attribute(Label),
+    AnyRelation,
+    AnyAttribute,
+}
+
+impl Concludable {
+    fn is_relation(&self) -> bool {
+        matches!(self, Concludable::Relation(_) | Concludable::AnyRelation)
+    }
+
+    fn label(&self) -> Option<&Label> {
+        match self {
+            Concludable::Relation(label) | Concludable::Attribute(label) => Some(label),
+            Concludable::AnyRelation | Concludable::AnyAttribute => None,
+        }
+    }
+}
+
+impl fmt::Display for Concludable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Concludable::Relation(label) | Concludable::Attribute(label) => write!(f, "{label}"),
+            Concludable::AnyRelation => write!(f, "{}", token::Type::Relation),
+            Concludable::AnyAttribute => write!(f, "{}", token::Type::Attribute),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RuleCycle {
+    pub rules: Vec<Label>,
+    pub is_negated: bool,
+}
+
+impl fmt::Display for RuleCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_joined!(f, " -> ", self.rules)?;
+        write!(f, " -> {}", self.rules[0])
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RuleDependencyGraph {
+    rules: Vec<Label>,
+    dependencies: Vec<RuleDependency>,
+}
+
+impl RuleDependencyGraph {
+    pub fn new<'a>(rules: impl IntoIterator<Item = &'a Rule>) -> Self {
+        Self::with_types(rules, [])
+    }
+
+    pub fn with_types<'a>(
+        rules: impl IntoIterator<Item = &'a Rule>,
+        types: impl IntoIterator<Item = &'a TypeStatement>,
+    ) -> Self {
+        let hierarchy = TypeHierarchy::new(types);
+        let rules: Vec<&Rule> = rules.into_iter().collect();
+        let conclusions: Vec<Concludable> = rules.iter().map(|rule| concludable(rule)).collect();
+        let premises: Vec<Vec<(Premise, bool)>> = rules.iter().map(|rule| premises(&rule.when)).collect();
+
+        let mut dependencies = Vec::new();
+        for (producer, conclusion) in rules.iter().zip(&conclusions) {
+            for (consumer, premises) in rules.iter().zip(&premises) {
+                for is_negated in [false, true] {
+                    let feeds = premises
+                        .iter()
+                        .filter(|(_, negated)| *negated == is_negated)
+                        .any(|(premise, _)| hierarchy.matches(premise, conclusion));
+                    if feeds {
+                        dependencies.push(RuleDependency {
+                            from: producer.label.clone(),
+                            to: consumer.label.clone(),
+                            concludes: conclusion.clone(),
+                            is_negated,
+                        });
+                    }
+                }
+            }
+        }
+        RuleDependencyGraph { rules: rules.iter().map(|rule| rule.label.clone()).collect(), dependencies }
+    }
+
+    pub fn rules(&self) -> &[Label] {
+        &self.rules
+    }
+
+    pub fn dependencies(&self) -> &[RuleDependency] {
+        &self.dependencies
+    }
+
+    pub fn dependencies_of<'a>(&'a self, rule: &'a Label) -> impl Iterator<Item = &'a RuleDependency> {
+        self.dependencies.iter().filter(move |dependency| &dependency.to == rule)
+    }
+
+    pub fn dependents_of<'a>(&'a self, rule: &'a Label) -> impl Iterator<Item = &'a RuleDependency> {
+        self.dependencies.iter().filter(move |dependency| &dependency.from == rule)
+    }
+
+    pub fn is_recursive(&self, rule: &Label) -> bool {
+        self.cycles().iter().any(|cycle| cycle.rules.contains(rule))
+    }
+
+    pub fn is_stratifiable(&self) -> bool {
+        self.negated_cycles().is_empty()
+    }
+
+    pub fn components(&self) -> Vec<Vec<Label>> {
+        Tarjan::new(self)
+            .run()
+            .into_iter()
+            .map(|component| component.into_iter().map(|i| self.rules[i].clone()).collect())
+            .collect()
+    }
+
+    pub fn cycles(&self) -> Vec<RuleCycle> {
+        self.components()
+            .into_iter()
+            .filter(|component| component.len() > 1 || self.depends_on(&component[0], &component[0]))
+            .map(|rules| {
+                let is_negated = self.dependencies.iter().any(|dependency| {
+                    dependency.is_negated && rules.contains(&dependency.from) && rules.contains(&dependency.to)
+                });
+                RuleCycle { rules, is_negated }
+            })
+            .collect()
+    }
+
+    pub fn negated_cycles(&self) -> Vec<RuleCycle> {
+        self.cycles().into_iter().filter(|cycle| cycle.is_negated).collect()
+    }
+
+    fn depends_on(&self, consumer: &Label, producer: &Label) -> bool {
+        self.dependencies.iter().any(|dependency| &dependency.from == producer && &dependency.to == consumer)
+    }
+
+    fn index_of(&self, rule: &Label) -> usize {
+        self.rules.iter().position(|label| label == rule).unwrap()
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph rules {\n");
+        for rule in &self.rules {
+            dot.push_str(&format!("    \"{rule}\";\n"));
+        }
+        for dependency in &self.dependencies {
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"",
+                dependency.from, dependency.to, dependency.concludes
+            ));
+            if dependency.is_negated {
+                dot.push_str(", style=dashed");
+            }
+            dot.push_str("];\n");
+        }
+        dot.push('}');
+        dot
+    }
+}
+
+impl fmt::Display for RuleDependencyGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_joined!(f, "\n", self.dependencies)?;
+        for cycle in self.cycles() {
+            writeln!(f)?;
+            let kind = if cycle.is_negated { "cycle through negation" } else { "cycle" };
+            write!(f, "{kind}: {cycle}")?;
+        }
+        Ok(())
+    }
+}
+
+struct Tarjan<'a> {
+    graph: &'a RuleDependencyGraph,
+    index: usize,
+    indices: Vec<Option<usize>>,
+    low_links: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    components: Vec<Vec<usize>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn new(graph: &'a RuleDependencyGraph) -> Self {
+        let size = graph.rules.len();
+        Tarjan {
+            graph,
+            index: 0,
+            indices: vec![None; size],
+            low_links: vec![0; size],
+            on_stack: vec![false; size],
+            stack: Vec::new(),
+            components: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Vec<Vec<usize>> {
+        for node in 0..self.graph.rules.len() {
+            if self.indices[node].is_none() {
+                self.connect(node);
+            }
+        }
+        self.components
+    }
+
+    fn connect(&mut self, node: usize) {
+        self.indices[node] = Some(self.index);
+        self.low_links[node] = self.index;
+        self.index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+
+        let graph = self.graph;
+        let mut producers: Vec<usize> =
+            graph.dependencies_of(&graph.rules[node]).map(|dependency| graph.index_of(&dependency.from)).collect();
+        producers.sort();
+        producers.dedup();
+        for producer in producers {
+            match self.indices[producer] {
+                None => {
+                    self.connect(producer);
+                    self.low_links[node] = self.low_links[node].min(self.low_links[producer]);
+                }
+                Some(index) if self.on_stack[producer] => self.low_links[node] = self.low_links[node].min(index),
+                Some(_) => (),
+            }
+        }
+
+        if Some(self.low_links[node]) == self.indices[node] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            component.sort();
+            self.components.push(component);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Premise {
+    Type(Label),
+    AnyRelation,
+    AnyAttribute,
+    Any,
+}
+
+fn premises(when: &Conjunction) -> Vec<(Premise, bool)> {
+    let mut premises = Vec::new();
+    collect_premises(&when.patterns, false, &mut premises);
+    premises
+}
+
+fn collect_premises(patterns: &[Pattern], is_negated: bool, premises: &mut Vec<(Premise, bool)>) {
+    for pattern in patterns {
+        match pattern {
+            Pattern::Conjunction(conjunction) => collect_premises(&conjunction.patterns, is_negated, premises),
+            Pattern::Disjunction(disjunction) => collect_premises(&disjunction.patterns, is_negated, premises),
+            Pattern::Negation(negation) => {
+                collect_premises(std::slice::from_ref(negation.pattern.as_ref()), true, premises)
+            }
+            Pattern::Optional(optional) => {
+                collect_premises(std::slice::from_ref(optional.pattern.as_ref()), is_negated, premises)
+            }
+            Pattern::Statement(Statement::Thing(thing)) => {
+                premises.extend(thing_premises(thing).into_iter().map(|premise| (premise, is_negated)))
+            }
+            Pattern::Statement(_) => (),
+        }
+    }
+}
+
+fn thing_premises(thing: &ThingStatement) -> Vec<Premise> {
+    let mut premises = Vec::new();
+    match thing.isa.as_ref().map(|isa| &isa.type_reference) {
+        Some(TypeReference::Label(label)) => premises.push(Premise::Type(label.clone())),
+        Some(TypeReference::Variable(_)) => premises.push(Premise::Any),
+        None if thing.relation.is_some() => premises.push(Premise::AnyRelation),
+        None => (),
+    }
+    premises.extend(thing.has.iter().map(|has| match has {
+        HasConstraint::HasConcept(Some(label), _)
+        | HasConstraint::HasValue(label, _)
+        | HasConstraint::HasPredicate(label, _) => Premise::Type(label.clone()),
+        HasConstraint::HasConcept(None, _) => Premise::AnyAttribute,
+    }));
+    premises
+}
+
+fn concludable(rule: &Rule) -> Concludable {
+    let then = &rule.then;
+    if then.relation.is_some() {
+        match then.isa.as_ref().map(|isa| &isa.type_reference) {
+            Some(TypeReference::Label(label)) => Concludable::Relation(label.clone()),
+            _ => Concludable::AnyRelation,
+        }
+    } else {
+        match then.has.first() {
+            Some(HasConstraint::HasConcept(Some(label), _))
+            | Some(HasConstraint::HasValue(label, _))
+            | Some(HasConstraint::HasPredicate(label, _)) => Concludable::Attribute(label.clone()),
+            Some(HasConstraint::HasConcept(None, variable)) => {
+                type_in_when(&rule.when, variable).map_or(Concludable::AnyAttribute, Concludable::Attribute)
+            }
+            None => Concludable::AnyAttribute,
+        }
+    }
+}
+
+fn type_in_when(when: &Conjunction, variable: &ConceptVariable) -> Option<Label> {
+    when.patterns.iter().find_map(|pattern| match pattern {
+        Pattern::Statement(Statement::Thing(thing)) if &thing.variable == variable => {
+            match thing.isa.as_ref().map(|isa| &isa.type_reference) {
+                Some(TypeReference::Label(label)) => Some(label.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    })
+}
+
+struct TypeHierarchy {
+    supertypes: HashMap<String, Label>,
+}
+
+impl TypeHierarchy {
+    fn new<'a>(types: impl IntoIterator<Item = &'a TypeStatement>) -> Self {
+        let supertypes = types
+            .into_iter()
+            .filter_map(|statement| match (&statement.label, &statement.sub) {
+                (Some(label), Some(sub)) => match &sub.type_ {
+                    TypeReference::Label(supertype) => Some((label.label.to_string(), supertype.clone())),
+                    TypeReference::Variable(_) => None,
+                },
+                _ => None,
+            })
+            .collect();
+        TypeHierarchy { supertypes }
+    }
+
+    fn matches(&self, premise: &Premise, conclusion: &Concludable) -> bool {
+        match premise {
+            Premise::Any => true,
+            Premise::AnyRelation => conclusion.is_relation(),
+            Premise::AnyAttribute => !conclusion.is_relation(),
+            Premise::Type(label) => match self.root(label).name.as_str() {
+                root if root == token::Type::Thing.as_str() => true,
+                root if root == token::Type::Entity.as_str() => false,
+                root if root == token::Type::Relation.as_str() && !conclusion.is_relation() => false,
+                root if root == token::Type::Attribute.as_str() && conclusion.is_relation() => false,
+                _ => match conclusion.label() {
+                    Some(concluded) => self.is_subtype(concluded, label),
+                    None => true,
+                },
+            },
+        }
+    }
+
+    fn is_subtype(&self, subtype: &Label, supertype: &Label) -> bool {
+        self.ancestry(subtype).iter().any(|label| label == supertype)
+    }
+
+    fn root(&self, label: &Label) -> Label {
+        self.ancestry(label).pop().unwrap()
+    }
+
+    fn ancestry(&self, label: &Label) -> Vec<Label> {
+        let mut ancestry = vec![label.clone()];
+        while let Some(supertype) = self.supertypes.get(&ancestry.last().unwrap().to_string()) {
+            if ancestry.contains(supertype) {
+                break;
+            }
+            ancestry.push(supertype.clone());
+        }
+        ancestry
+    }
+}
@@ -0,0 +1,306 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, iter,
+};
+
+use crate::{
+    common::{
+        error::{collect_err, TypeQLError},
+        token,
+        validatable::Validatable,
+        Result,
+    },
+    pattern::{Expression, Pattern, Simplifiable, Statement, VariablesRetrieved},
+    query::MatchClause,
+    variable::{variable::VariableRef, Variable},
+    write_joined, Label,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FunctionLabel {
+    pub label: Label,
+}
+
+impl FunctionLabel {
+    pub fn new(label: Label) -> Self {
+        FunctionLabel { label }
+    }
+
+    pub fn args(
+        self,
+        arguments: impl IntoIterator<Item = impl Into<FunctionArgument>>,
+        return_type: impl Into<FunctionType>,
+    ) -> FunctionSignature {
+        FunctionSignature {
+            label: self.label,
+            arguments: arguments.into_iter().map(Into::into).collect(),
+            return_type: return_type.into(),
+        }
+    }
+}
+
+impl Validatable for FunctionLabel {
+    fn validate(&self) -> Result {
+        Ok(())
+    }
+}
+
+impl<T: Into<Label>> From<T> for FunctionLabel {
+    fn from(label: T) -> Self {
+        FunctionLabel::new(label.into())
+    }
+}
+
+impl fmt::Display for FunctionLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", token::Schema::Fun, self.label)
+    }
+}
+
+pub struct FunctionSignature {
+    pub label: Label,
+    pub arguments: Vec<FunctionArgument>,
+    pub return_type: FunctionType,
+}
+
+impl FunctionSignature {
+    pub fn match_(self, match_clause: MatchClause) -> FunctionSignatureMatch {
+        FunctionSignatureMatch { signature: self, match_clause }
+    }
+}
+
+pub struct FunctionSignatureMatch {
+    pub signature: FunctionSignature,
+    pub match_clause: MatchClause,
+}
+
+impl FunctionSignatureMatch {
+    pub fn return_(self, returned: impl Into<Variable>) -> SchemaFunction {
+        let FunctionSignature { label, arguments, return_type } = self.signature;
+        SchemaFunction { label, arguments, return_type, match_clause: self.match_clause, returned: returned.into() }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SchemaFunction {
+    pub label: Label,
+    pub arguments: Vec<FunctionArgument>,
+    pub return_type: FunctionType,
+    pub match_clause: MatchClause,
+    pub returned: Variable,
+}
+
+impl SchemaFunction {
+    fn validate_arguments_are_distinct(&self) -> Result {
+        let mut variables = HashSet::new();
+        collect_err(self.arguments.iter().filter(|argument| !variables.insert(&argument.variable)).map(|argument| {
+            Err(TypeQLError::RepeatingFunctionArgument {
+                function_label: self.label.clone(),
+                variable: argument.variable.clone(),
+            })?
+        }))
+    }
+
+    fn validate_variables_are_bound(&self) -> Result {
+        let bound: HashSet<VariableRef<'_>> = self.match_clause.retrieved_variables().collect();
+        let unused =
+            self.arguments.iter().filter(|argument| !bound.contains(&argument.variable.as_ref())).map(|argument| {
+                Err(TypeQLError::FunctionArgumentNotUsed {
+                    function_label: self.label.clone(),
+                    variable: argument.variable.clone(),
+                })?
+            });
+        let unbound = (!bound.contains(&self.returned.as_ref())).then(|| {
+            Err(TypeQLError::FunctionReturnNotBound {
+                function_label: self.label.clone(),
+                variable: self.returned.clone(),
+            })?
+        });
+        collect_err(unused.chain(unbound))
+    }
+
+    fn validate_variable_types(&self) -> Result {
+        collect_err(
+            self.arguments
+                .iter()
+                .map(|argument| (&argument.variable, &argument.type_))
+                .chain(iter::once((&self.returned, &self.return_type)))
+                .filter(|(variable, type_)| !type_.can_be_held_by(variable))
+                .map(|(variable, type_)| {
+                    Err(TypeQLError::InvalidFunctionVariableType {
+                        function_label: self.label.clone(),
+                        variable: variable.clone(),
+                        type_: type_.clone(),
+                    })?
+                }),
+        )
+    }
+}
+
+pub(crate) fn validate_functions_are_not_recursive(functions: &[SchemaFunction]) -> Result {
+    let calls: HashMap<&Label, Vec<&Label>> = functions
+        .iter()
+        .map(|function| {
+            let mut called = Vec::new();
+            function.match_clause.conjunction.patterns.iter().for_each(|pattern| pattern_calls(pattern, &mut called));
+            (&function.label, called)
+        })
+        .collect();
+    let is_recursive = |function: &SchemaFunction| {
+        let mut pending = calls[&function.label].clone();
+        let mut visited = HashSet::new();
+        while let Some(label) = pending.pop() {
+            if label == &function.label {
+                return true;
+            }
+            if visited.insert(label) {
+                pending.extend(calls.get(label).into_iter().flatten());
+            }
+        }
+        false
+    };
+    collect_err(
+        functions
+            .iter()
+            .filter(|function| is_recursive(function))
+            .map(|function| Err(TypeQLError::RecursiveFunction { function_label: function.label.clone() })?),
+    )
+}
+
+fn pattern_calls<'a>(pattern: &'a Pattern, calls: &mut Vec<&'a Label>) {
+    match pattern {
+        Pattern::Conjunction(conjunction) => {
+            conjunction.patterns.iter().for_each(|pattern| pattern_calls(pattern, calls))
+        }
+        Pattern::Disjunction(disjunction) => {
+            disjunction.patterns.iter().for_each(|pattern| pattern_calls(pattern, calls))
+        }
+        Pattern::Negation(negation) => pattern_calls(&negation.pattern, calls),
+        Pattern::Optional(optional) => pattern_calls(&optional.pattern, calls),
+        Pattern::Statement(Statement::Concept(concept)) => {
+            if let Some(in_) = &concept.in_constraint {
+                calls.push(&in_.call.label);
+                in_.call.args.iter().for_each(|arg| expression_calls(arg, calls));
+            }
+        }
+        Pattern::Statement(Statement::Value(value)) => {
+            if let Some(assign) = &value.assign_constraint {
+                expression_calls(&assign.expression, calls);
+            }
+        }
+        Pattern::Statement(_) => (),
+    }
+}
+
+fn expression_calls<'a>(expression: &'a Expression, calls: &mut Vec<&'a Label>) {
+    match expression {
+        Expression::Operation(operation) => {
+            operation.left().into_iter().chain(operation.right()).for_each(|operand| expression_calls(operand, calls))
+        }
+        Expression::Function(function) => function.args.iter().for_each(|arg| expression_calls(arg, calls)),
+        Expression::Call(call) => {
+            calls.push(&call.label);
+            call.args.iter().for_each(|arg| expression_calls(arg, calls));
+        }
+        _ => (),
+    }
+}
+
+impl Validatable for SchemaFunction {
+    fn validate(&self) -> Result {
+        collect_err([
+            self.validate_arguments_are_distinct(),
+            self.validate_variables_are_bound(),
+            self.validate_variable_types(),
+            self.match_clause.validate(),
+        ])
+    }
+}
+
+impl Simplifiable for SchemaFunction {
+    fn simplify(&mut self) {
+        self.match_clause.simplify();
+    }
+}
+
+impl fmt::Display for SchemaFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}(", token::Schema::Fun, self.label)?;
+        write_joined!(f, ", ", self.arguments)?;
+        write!(
+            f,
+            ") {} {}:\n{}\n{} {}",
+            token::Schema::Arrow,
+            self.return_type,
+            self.match_clause,
+            token::Schema::Return,
+            self.returned
+        )
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FunctionArgument {
+    pub variable: Variable,
+    pub type_: FunctionType,
+}
+
+impl<T: Into<Variable>, U: Into<FunctionType>> From<(T, U)> for FunctionArgument {
+    fn from((variable, type_): (T, U)) -> Self {
+        FunctionArgument { variable: variable.into(), type_: type_.into() }
+    }
+}
+
+impl fmt::Display for FunctionArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.variable, self.type_)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FunctionType {
+    Type(Label),
+    Value(token::ValueType),
+}
+
+impl FunctionType {
+    fn can_be_held_by(&self, variable: &Variable) -> bool {
+        match self {
+            FunctionType::Type(_) => matches!(variable, Variable::Concept(_)),
+            FunctionType::Value(_) => matches!(variable, Variable::Value(_)),
+        }
+    }
+}
+
+impl From<&str> for FunctionType {
+    fn from(label: &str) -> Self {
+        FunctionType::Type(label.into())
+    }
+}
+
+impl From<Label> for FunctionType {
+    fn from(label: Label) -> Self {
+        FunctionType::Type(label)
+    }
+}
+
+impl From<token::ValueType> for FunctionType {
+    fn from(value_type: token::ValueType) -> Self {
+        FunctionType::Value(value_type)
+    }
+}
+
+impl fmt::Display for FunctionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FunctionType::Type(label) => write!(f, "{label}"),
+            FunctionType::Value(value_type) => write!(f, "{value_type}"),
+        }
+    }
+}
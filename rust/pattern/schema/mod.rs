@@ -4,6 +4,17 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
+mod dependency;
+mod function;
 mod rule;
+mod stratification;
+mod struct_;
 
+pub use dependency::{Concludable, RuleCycle, RuleDependency, RuleDependencyGraph};
+pub(crate) use function::validate_functions_are_not_recursive;
+pub use function::{
+    FunctionArgument, FunctionLabel, FunctionSignature, FunctionSignatureMatch, FunctionType, SchemaFunction,
+};
 pub use rule::{Rule, RuleLabel};
+pub use stratification::RuleStratification;
+pub use struct_::{Struct, StructField};
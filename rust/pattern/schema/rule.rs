@@ -13,7 +13,7 @@ use crate::{
         validatable::Validatable,
         Result,
     },
-    pattern::{Conjunction, HasConstraint, Pattern, ThingStatement, VariablesRetrieved},
+    pattern::{Conjunction, HasConstraint, Pattern, Simplifiable, ThingStatement, VariablesRetrieved},
     variable::variable::VariableRef,
     Label,
 };
@@ -87,6 +87,7 @@ fn validate_no_nested_negations<'a>(patterns: impl Iterator<Item = &'a Pattern>,
             Pattern::Conjunction(c) => validate_no_nested_negations(c.patterns.iter(), rule_label),
             Pattern::Statement(_) => Ok(()),
             Pattern::Disjunction(d) => validate_no_nested_negations(d.patterns.iter(), rule_label),
+            Pattern::Optional(o) => validate_no_nested_negations(iter::once(o.pattern.as_ref()), rule_label),
             Pattern::Negation(n) => {
                 if contains_negations(iter::once(n.pattern.as_ref())) {
                     Err(TypeQLError::InvalidRuleWhenNestedNegation { rule_label: rule_label.clone() })?
@@ -103,6 +104,7 @@ fn contains_negations<'a>(mut patterns: impl Iterator<Item = &'a Pattern>) -> bo
         Pattern::Conjunction(c) => contains_negations(c.patterns.iter()),
         Pattern::Statement(_) => false,
         Pattern::Disjunction(d) => contains_negations(d.patterns.iter()),
+        Pattern::Optional(o) => contains_negations(iter::once(o.pattern.as_ref())),
         Pattern::Negation(_) => true,
     })
 }
@@ -149,6 +151,12 @@ fn validate_then_bounded_by_when(then: &ThingStatement, when: &Conjunction, rule
     Ok(())
 }
 
+impl Simplifiable for Rule {
+    fn simplify(&mut self) {
+        self.when.simplify();
+    }
+}
+
 impl fmt::Display for Rule {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -0,0 +1,98 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    common::{
+        error::{collect_err, TypeQLError},
+        Result,
+    },
+    pattern::{RuleCycle, RuleDependencyGraph},
+    write_joined, Label,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RuleStratification {
+    strata: Vec<Vec<Label>>,
+}
+
+impl RuleStratification {
+    pub fn new(graph: &RuleDependencyGraph) -> Result<Self> {
+        validate_stratifiable(graph)?;
+
+        let mut stratum_of: HashMap<&Label, usize> = HashMap::new();
+        let mut strata: Vec<Vec<Label>> = Vec::new();
+        for component in graph.components() {
+            let stratum = component
+                .iter()
+                .flat_map(|rule| graph.dependencies_of(rule))
+                .filter(|dependency| !component.contains(&dependency.from))
+                .map(|dependency| stratum_of[&dependency.from] + dependency.is_negated as usize)
+                .max()
+                .unwrap_or(0);
+            if strata.len() <= stratum {
+                strata.resize(stratum + 1, Vec::new());
+            }
+            for rule in graph.rules().iter().filter(|rule| component.contains(rule)) {
+                stratum_of.insert(rule, stratum);
+            }
+            strata[stratum].extend(component);
+        }
+        Ok(RuleStratification { strata })
+    }
+
+    pub fn strata(&self) -> &[Vec<Label>] {
+        &self.strata
+    }
+
+    pub fn stratum_of(&self, rule: &Label) -> Option<usize> {
+        self.strata.iter().position(|stratum| stratum.contains(rule))
+    }
+
+    pub fn evaluation_order(&self) -> impl Iterator<Item = &Label> {
+        self.strata.iter().flatten()
+    }
+}
+
+fn validate_stratifiable(graph: &RuleDependencyGraph) -> Result {
+    collect_err(graph.negated_cycles().iter().map(|cycle| -> Result {
+        let negation = graph
+            .dependencies()
+            .iter()
+            .find(|dep| dep.is_negated && cycle.rules.contains(&dep.from) && cycle.rules.contains(&dep.to))
+            .unwrap();
+        Err(TypeQLError::InvalidRuleNegationCycle {
+            rule_label: negation.to.clone(),
+            negated: negation.concludes.to_string(),
+            dependency_label: negation.from.clone(),
+            cycle: explain_cycle(graph, cycle),
+        })?
+    }))
+}
+
+fn explain_cycle(graph: &RuleDependencyGraph, cycle: &RuleCycle) -> String {
+    graph
+        .dependencies()
+        .iter()
+        .filter(|dep| cycle.rules.contains(&dep.from) && cycle.rules.contains(&dep.to))
+        .map(|dep| dep.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl fmt::Display for RuleStratification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stratum) in self.strata.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "stratum {i}: ")?;
+            write_joined!(f, ", ", stratum)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{collections::HashSet, fmt};
+
+use crate::{
+    common::{error::TypeQLError, token, validatable::Validatable, Result},
+    pattern::ValueType,
+    write_joined,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Struct {
+    pub name: String,
+    pub fields: Vec<StructField>,
+}
+
+impl Struct {
+    pub fn new(name: String) -> Self {
+        Struct { name, fields: Vec::new() }
+    }
+
+    pub fn field(self, name: impl Into<String>, value_type: impl Into<ValueType>) -> Self {
+        self.with_field(StructField { name: name.into(), value_type: value_type.into(), is_optional: false })
+    }
+
+    pub fn optional_field(self, name: impl Into<String>, value_type: impl Into<ValueType>) -> Self {
+        self.with_field(StructField { name: name.into(), value_type: value_type.into(), is_optional: true })
+    }
+
+    fn with_field(mut self, field: StructField) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    pub fn get_field(&self, name: &str) -> Option<&StructField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+impl Validatable for Struct {
+    fn validate(&self) -> Result {
+        let mut names = HashSet::new();
+        match self.fields.iter().find(|field| !names.insert(&field.name)) {
+            Some(field) => Err(TypeQLError::RepeatingStructField { field: field.name.clone() })?,
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<&str> for Struct {
+    fn from(name: &str) -> Self {
+        Struct::new(name.to_owned())
+    }
+}
+
+impl From<String> for Struct {
+    fn from(name: String) -> Self {
+        Struct::new(name)
+    }
+}
+
+impl fmt::Display for Struct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", token::Schema::Struct, self.name)?;
+        if !self.fields.is_empty() {
+            write!(f, ": ")?;
+            write_joined!(f, ", ", self.fields)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct StructField {
+    pub name: String,
+    pub value_type: ValueType,
+    pub is_optional: bool,
+}
+
+impl fmt::Display for StructField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.name, token::Constraint::ValueType, self.value_type)?;
+        if self.is_optional {
+            write!(f, "{}", token::Schema::Optional)?;
+        }
+        Ok(())
+    }
+}
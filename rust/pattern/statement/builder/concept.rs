@@ -4,18 +4,24 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use crate::pattern::{ConceptStatement, IsConstraint};
+use crate::pattern::{ConceptStatement, InConstraint, IsConstraint};
 
 pub trait ConceptConstrainable {
     fn constrain_is(self, is: IsConstraint) -> ConceptStatement;
+    fn constrain_in(self, in_: InConstraint) -> ConceptStatement;
 }
 
 pub trait ConceptStatementBuilder: Sized {
     fn is(self, is: impl Into<IsConstraint>) -> ConceptStatement;
+    fn in_(self, in_: impl Into<InConstraint>) -> ConceptStatement;
 }
 
 impl<U: Into<ConceptStatement>> ConceptStatementBuilder for U {
     fn is(self, is: impl Into<IsConstraint>) -> ConceptStatement {
         self.into().constrain_is(is.into())
     }
+
+    fn in_(self, in_: impl Into<InConstraint>) -> ConceptStatement {
+        self.into().constrain_in(in_.into())
+    }
 }
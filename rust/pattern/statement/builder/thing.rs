@@ -25,7 +25,7 @@ pub trait ThingStatementBuilder {
     fn gte(self, value: impl Into<Value>) -> ThingStatement;
     fn lt(self, value: impl Into<Value>) -> ThingStatement;
     fn lte(self, value: impl Into<Value>) -> ThingStatement;
-    fn contains(self, string: impl Into<String>) -> ThingStatement;
+    fn contains(self, value: impl Into<Value>) -> ThingStatement;
     fn like(self, string: impl Into<String>) -> ThingStatement;
 }
 
@@ -78,8 +78,8 @@ impl<U: Into<ThingStatement>> ThingStatementBuilder for U {
         self.into().constrain_predicate(Predicate::new(token::Predicate::Lte, value.into()))
     }
 
-    fn contains(self, string: impl Into<String>) -> ThingStatement {
-        self.into().constrain_predicate(Predicate::new(token::Predicate::Contains, Value::from(string.into())))
+    fn contains(self, value: impl Into<Value>) -> ThingStatement {
+        self.into().constrain_predicate(Predicate::new(token::Predicate::Contains, value.into()))
     }
 
     fn like(self, string: impl Into<String>) -> ThingStatement {
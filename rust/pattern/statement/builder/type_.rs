@@ -5,7 +5,6 @@
  */
 
 use crate::{
-    common::token,
     pattern::{
         LabelConstraint, OwnsConstraint, PlaysConstraint, RegexConstraint, RelatesConstraint, SubConstraint,
         TypeStatement, ValueTypeConstraint,
@@ -21,7 +20,7 @@ pub trait TypeStatementBuilder: Sized {
     fn relates(self, relates: impl Into<RelatesConstraint>) -> TypeStatement;
     fn sub(self, sub: impl Into<SubConstraint>) -> TypeStatement;
     fn type_(self, type_name: impl Into<Label>) -> TypeStatement;
-    fn value(self, value_type: token::ValueType) -> TypeStatement;
+    fn value(self, value_type: impl Into<ValueTypeConstraint>) -> TypeStatement;
 }
 
 impl<U: Into<TypeStatement>> TypeStatementBuilder for U {
@@ -53,7 +52,7 @@ impl<U: Into<TypeStatement>> TypeStatementBuilder for U {
         self.into().constrain_label(LabelConstraint { label: type_name.into() })
     }
 
-    fn value(self, value_type: token::ValueType) -> TypeStatement {
-        self.into().constrain_value_type(ValueTypeConstraint { value_type })
+    fn value(self, value_type: impl Into<ValueTypeConstraint>) -> TypeStatement {
+        self.into().constrain_value_type(value_type.into())
     }
 }
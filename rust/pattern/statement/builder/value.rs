@@ -4,11 +4,12 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use crate::pattern::{AssignConstraint, Predicate, ValueStatement};
+use crate::pattern::{AssignConstraint, Predicate, ReduceConstraint, ValueStatement};
 
 pub trait ValueStatementBuilder: Sized {
     fn assign(self, assign: impl Into<AssignConstraint>) -> ValueStatement;
     fn predicate(self, predicate: impl Into<Predicate>) -> ValueStatement;
+    fn reduce(self, reduce: impl Into<ReduceConstraint>) -> ValueStatement;
 }
 
 impl<U: Into<ValueStatement>> ValueStatementBuilder for U {
@@ -19,4 +20,8 @@ impl<U: Into<ValueStatement>> ValueStatementBuilder for U {
     fn predicate(self, predicate: impl Into<Predicate>) -> ValueStatement {
         self.into().constrain_predicate(predicate.into())
     }
+
+    fn reduce(self, reduce: impl Into<ReduceConstraint>) -> ValueStatement {
+        self.into().constrain_reduce(reduce.into())
+    }
 }
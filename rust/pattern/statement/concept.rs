@@ -8,7 +8,7 @@ use std::{fmt, iter};
 
 use crate::{
     common::{error::collect_err, validatable::Validatable, Result},
-    pattern::constraint::IsConstraint,
+    pattern::constraint::{InConstraint, IsConstraint},
     variable::{variable::VariableRef, ConceptVariable},
 };
 
@@ -16,11 +16,12 @@ use crate::{
 pub struct ConceptStatement {
     pub variable: ConceptVariable,
     pub is_constraint: Option<IsConstraint>,
+    pub in_constraint: Option<InConstraint>,
 }
 
 impl ConceptStatement {
     pub fn new(variable: ConceptVariable) -> ConceptStatement {
-        ConceptStatement { variable, is_constraint: None }
+        ConceptStatement { variable, is_constraint: None, in_constraint: None }
     }
 
     pub fn owner(&self) -> VariableRef<'_> {
@@ -28,17 +29,29 @@ impl ConceptStatement {
     }
 
     pub fn variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
-        Box::new(iter::once(self.owner()).chain(self.is_constraint.iter().map(|is| VariableRef::Concept(&is.variable))))
+        Box::new(
+            iter::once(self.owner())
+                .chain(self.is_constraint.iter().map(|is| VariableRef::Concept(&is.variable)))
+                .chain(self.in_constraint.iter().flat_map(InConstraint::variables)),
+        )
     }
 
     pub fn constrain_is(self, is: IsConstraint) -> ConceptStatement {
         Self { is_constraint: Some(is), ..self }
     }
+
+    pub fn constrain_in(self, in_: InConstraint) -> ConceptStatement {
+        Self { in_constraint: Some(in_), ..self }
+    }
 }
 
 impl Validatable for ConceptStatement {
     fn validate(&self) -> Result {
-        collect_err(iter::once(self.variable.validate()).chain(self.is_constraint.iter().map(Validatable::validate)))
+        collect_err(
+            iter::once(self.variable.validate())
+                .chain(self.is_constraint.iter().map(Validatable::validate))
+                .chain(self.in_constraint.iter().map(Validatable::validate)),
+        )
     }
 }
 
@@ -54,6 +67,9 @@ impl fmt::Display for ConceptStatement {
         if let Some(is) = &self.is_constraint {
             write!(f, " {is}")?;
         }
+        if let Some(in_) = &self.in_constraint {
+            write!(f, " {in_}")?;
+        }
         Ok(())
     }
 }
@@ -4,7 +4,10 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use std::{collections::HashSet, fmt};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
 
 pub(crate) use builder::LeftOperand;
 pub use builder::{
@@ -18,9 +21,9 @@ pub use value::ValueStatement;
 
 pub use crate::variable::{variable::Variable, variable_concept::ConceptVariable, variable_value::ValueVariable};
 use crate::{
-    common::{error::TypeQLError, validatable::Validatable, Result},
+    common::{error::TypeQLError, token, validatable::Validatable, Result},
     enum_wrapper,
-    pattern::{Normalisable, Pattern},
+    pattern::{Normalisable, Pattern, Simplifiable},
     variable::variable::VariableRef,
 };
 
@@ -93,6 +96,20 @@ impl Normalisable for Statement {
     }
 }
 
+impl Statement {
+    pub(crate) fn simplify_with(&mut self, variable_types: &HashMap<ValueVariable, token::ValueType>) {
+        if let Statement::Value(value) = self {
+            value.simplify_with(variable_types);
+        }
+    }
+}
+
+impl Simplifiable for Statement {
+    fn simplify(&mut self) {
+        self.simplify_with(&HashMap::new());
+    }
+}
+
 impl fmt::Display for Statement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use Statement::*;
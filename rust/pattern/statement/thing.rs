@@ -20,7 +20,7 @@ pub struct ThingStatement {
     pub isa: Option<IsaConstraint>,
     pub has: Vec<HasConstraint>,
     pub predicate: Option<Predicate>,
-    pub relation: Option<RelationConstraint>,
+    pub relation: Option<Box<RelationConstraint>>,
 }
 
 impl ThingStatement {
@@ -60,12 +60,12 @@ impl ThingStatement {
     }
 
     pub fn constrain_relation(self, relation: RelationConstraint) -> ThingStatement {
-        ThingStatement { relation: Some(relation), ..self }
+        ThingStatement { relation: Some(Box::new(relation)), ..self }
     }
 
     pub(crate) fn constrain_role_player(mut self, constraint: RolePlayerConstraint) -> ThingStatement {
         match &mut self.relation {
-            None => self.relation = Some(RelationConstraint::from(constraint)),
+            None => self.relation = Some(Box::new(RelationConstraint::from(constraint))),
             Some(relation) => relation.add(constraint),
         }
         self
@@ -83,7 +83,7 @@ impl Validatable for ThingStatement {
                 .chain(self.iid.iter().map(Validatable::validate))
                 .chain(self.isa.iter().map(Validatable::validate))
                 .chain(self.has.iter().map(Validatable::validate))
-                .chain(self.relation.iter().map(Validatable::validate))
+                .chain(self.relation.iter().map(|relation| relation.validate()))
                 .chain(self.predicate.iter().map(Validatable::validate)),
         )
     }
@@ -99,7 +99,7 @@ impl fmt::Display for ThingStatement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.variable.is_visible() {
             write!(f, "{}", self.variable)?;
-            if self.predicate.is_some() || self.relation.is_some() {
+            if self.predicate.as_ref().is_some_and(|predicate| !predicate.has_accessor()) || self.relation.is_some() {
                 f.write_str(" ")?;
             }
         }
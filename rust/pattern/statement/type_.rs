@@ -29,7 +29,7 @@ pub struct TypeStatement {
     pub regex: Option<RegexConstraint>,
     pub relates: Vec<RelatesConstraint>,
     pub sub: Option<SubConstraint>,
-    pub value_type: Option<ValueTypeConstraint>,
+    pub value_type: Option<Box<ValueTypeConstraint>>,
     pub abstract_: Option<AbstractConstraint>,
 }
 
@@ -94,7 +94,7 @@ impl TypeStatement {
     }
 
     pub fn constrain_value_type(self, value_type: ValueTypeConstraint) -> TypeStatement {
-        TypeStatement { value_type: Some(value_type), ..self }
+        TypeStatement { value_type: Some(Box::new(value_type)), ..self }
     }
 
     fn is_type_constrained(&self) -> bool {
@@ -125,7 +125,7 @@ impl Validatable for TypeStatement {
                 .chain(self.regex.iter().map(Validatable::validate))
                 .chain(self.relates.iter().map(Validatable::validate))
                 .chain(self.sub.iter().map(Validatable::validate))
-                .chain(self.value_type.iter().map(Validatable::validate))
+                .chain(self.value_type.iter().map(|value_type| value_type.validate()))
                 .chain(self.abstract_.iter().map(Validatable::validate)),
         )
     }
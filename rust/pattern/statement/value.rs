@@ -4,11 +4,11 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use std::{fmt, iter};
+use std::{collections::HashMap, fmt, iter};
 
 use crate::{
-    common::{error::collect_err, validatable::Validatable, Result},
-    pattern::{AssignConstraint, Predicate},
+    common::{error::collect_err, token, validatable::Validatable, Result},
+    pattern::{AssignConstraint, Predicate, ReduceConstraint, Simplifiable},
     variable::{variable::VariableRef, ValueVariable},
 };
 
@@ -17,11 +17,12 @@ pub struct ValueStatement {
     pub variable: ValueVariable,
     pub assign_constraint: Option<AssignConstraint>,
     pub predicate_constraint: Option<Predicate>,
+    pub reduce_constraint: Option<ReduceConstraint>,
 }
 
 impl ValueStatement {
     pub fn new(variable: ValueVariable) -> ValueStatement {
-        ValueStatement { variable, assign_constraint: None, predicate_constraint: None }
+        ValueStatement { variable, assign_constraint: None, predicate_constraint: None, reduce_constraint: None }
     }
 
     pub fn owner(&self) -> VariableRef<'_> {
@@ -32,7 +33,8 @@ impl ValueStatement {
         Box::new(
             iter::once(self.owner())
                 .chain(self.assign_constraint.iter().flat_map(|assign| assign.variables()))
-                .chain(self.predicate_constraint.iter().flat_map(|predicate| predicate.variables())),
+                .chain(self.predicate_constraint.iter().flat_map(|predicate| predicate.variables()))
+                .chain(self.reduce_constraint.iter().flat_map(|reduce| reduce.variables())),
         )
     }
 
@@ -43,6 +45,10 @@ impl ValueStatement {
     pub fn constrain_predicate(self, predicate: Predicate) -> ValueStatement {
         Self { predicate_constraint: Some(predicate), ..self }
     }
+
+    pub fn constrain_reduce(self, reduce: ReduceConstraint) -> ValueStatement {
+        Self { reduce_constraint: Some(reduce), ..self }
+    }
 }
 
 impl Validatable for ValueStatement {
@@ -50,7 +56,8 @@ impl Validatable for ValueStatement {
         collect_err(
             iter::once(self.variable.validate())
                 .chain(self.assign_constraint.iter().map(Validatable::validate))
-                .chain(self.predicate_constraint.iter().map(Validatable::validate)),
+                .chain(self.predicate_constraint.iter().map(Validatable::validate))
+                .chain(self.reduce_constraint.iter().map(Validatable::validate)),
         )
     }
 }
@@ -61,11 +68,31 @@ impl From<ValueVariable> for ValueStatement {
     }
 }
 
+impl ValueStatement {
+    pub(crate) fn simplify_with(&mut self, variable_types: &HashMap<ValueVariable, token::ValueType>) {
+        if let Some(assign) = &mut self.assign_constraint {
+            assign.expression.simplify_with(variable_types);
+        }
+    }
+}
+
+impl Simplifiable for ValueStatement {
+    fn simplify(&mut self) {
+        self.simplify_with(&HashMap::new());
+    }
+}
+
 impl fmt::Display for ValueStatement {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(reduce) = &self.reduce_constraint {
+            return write!(f, "{} {} {reduce}", token::Constraint::Reduce, self.variable);
+        }
         write!(f, "{}", self.variable)?;
         if let Some(assign) = &self.assign_constraint {
             write!(f, " {assign}")?;
+        } else if let Some(predicate) = self.predicate_constraint.as_ref().filter(|predicate| predicate.has_accessor())
+        {
+            write!(f, "{predicate}")?;
         } else if let Some(predicate) = &self.predicate_constraint {
             write!(f, " {} {}", predicate.predicate, predicate.value)?;
         }
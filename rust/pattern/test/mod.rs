@@ -4,11 +4,24 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
+use std::collections::HashMap;
+
+use chrono::{FixedOffset, NaiveDate, TimeZone};
+
 use crate::{
     and,
-    builder::cvar,
-    not, or, parse_query,
-    pattern::{Disjunction, Normalisable, ThingStatementBuilder},
+    builder::{
+        abs, add_duration, ceil, concat, cvar, day, floor, length, month, round, substring, subtract_duration,
+        uppercase, vvar, year,
+    },
+    common::{duration::Duration, token},
+    not, or, parse_definables, parse_query,
+    pattern::{
+        evaluate, Constant, Definable, Disjunction, Expression, ExpressionBuilder, Label, Normalisable, Rule,
+        RuleCycle, RuleDependencyGraph, Simplifiable, ThingStatementBuilder, TypeStatement,
+    },
+    try_,
+    variable::Variable,
 };
 
 #[test]
@@ -65,3 +78,312 @@ get;"#;
         .into()])
     );
 }
+
+#[test]
+fn optional_is_kept_as_conjunct() {
+    let query = r#"match
+$p isa person;
+{
+    $p has name "Alice";
+} or {
+    $p has name "Bob";
+};
+try {
+    $p has email $e;
+};
+get;"#;
+
+    let mut parsed = parse_query(query).unwrap().into_get();
+    let normalised = parsed.match_clause.conjunction.normalise().into_disjunction();
+
+    let optional = try_(Disjunction::new(vec![and!(cvar("p").has(("email", cvar("e")))).into()]));
+    assert_eq!(
+        normalised,
+        or!(
+            and!(cvar("p").has(("name", "Alice")), cvar("p").isa("person"), optional.clone()),
+            and!(cvar("p").has(("name", "Bob")), cvar("p").isa("person"), optional),
+        )
+    );
+}
+
+#[test]
+fn rule_dependency_graph() {
+    let definables = r#"
+friendship sub relation, relates friend;
+close-friendship sub friendship;
+rule transitive-friendship: when {
+    (friend: $x, friend: $y) isa friendship;
+    (friend: $y, friend: $z) isa friendship;
+} then {
+    (friend: $x, friend: $z) isa close-friendship;
+};
+rule popular: when {
+    $x isa person;
+    (friend: $x, friend: $y) isa close-friendship;
+} then {
+    $x has status "popular";
+};
+rule lonely: when {
+    $x isa person;
+    not { $x has status "popular"; };
+} then {
+    $x has status "lonely";
+};"#;
+
+    let (rules, types): (Vec<Definable>, Vec<Definable>) =
+        parse_definables(definables).unwrap().into_iter().partition(|d| matches!(d, Definable::RuleDefinition(_)));
+    let rules: Vec<Rule> = rules.into_iter().map(Definable::into_rule).collect();
+    let types: Vec<TypeStatement> = types.into_iter().map(Definable::into_type_statement).collect();
+    let graph = RuleDependencyGraph::with_types(&rules, &types);
+
+    assert_eq!(
+        graph.to_string(),
+        r#"transitive-friendship -> transitive-friendship [close-friendship]
+transitive-friendship -> popular [close-friendship]
+popular -> lonely [not status]
+lonely -> lonely [not status]
+cycle: transitive-friendship -> transitive-friendship
+cycle through negation: lonely -> lonely"#
+    );
+    assert_eq!(
+        graph.components(),
+        vec![vec![Label::from("transitive-friendship")], vec![Label::from("popular")], vec![Label::from("lonely")],]
+    );
+    assert!(graph.is_recursive(&Label::from("transitive-friendship")));
+    assert!(!graph.is_recursive(&Label::from("popular")));
+    assert_eq!(graph.negated_cycles(), vec![RuleCycle { rules: vec![Label::from("lonely")], is_negated: true }]);
+    assert!(graph.to_dot().contains(r#""lonely" -> "lonely" [label="status", style=dashed];"#));
+}
+
+#[test]
+fn rule_dependency_graph_untyped_premises() {
+    let query = r#"define
+rule marriage-is-partnership: when {
+    (spouse: $x, spouse: $y) isa marriage;
+} then {
+    (partner: $x, partner: $y) isa partnership;
+};
+rule related: when {
+    ($x, $y);
+} then {
+    (relative: $x, relative: $y) isa family;
+};"#;
+
+    let graph = parse_query(query).unwrap().into_define().rule_dependency_graph();
+
+    assert_eq!(
+        graph.to_string(),
+        r#"marriage-is-partnership -> related [partnership]
+related -> related [family]
+cycle: related -> related"#
+    );
+}
+
+#[test]
+fn rule_stratification() {
+    let query = r#"define
+rule transitive-reachability: when {
+    (from: $x, to: $y) isa reachable;
+    (from: $y, to: $z) isa edge;
+} then {
+    (from: $x, to: $z) isa reachable;
+};
+rule direct-reachability: when {
+    (from: $x, to: $y) isa edge;
+} then {
+    (from: $x, to: $y) isa reachable;
+};
+rule unreachable: when {
+    $x isa node;
+    $y isa node;
+    not { (from: $x, to: $y) isa reachable; };
+} then {
+    (from: $x, to: $y) isa unreachable;
+};
+rule isolated: when {
+    $x isa node;
+    not { (from: $x, to: $y) isa reachable; };
+    (from: $x, to: $x) isa unreachable;
+} then {
+    $x has isolated true;
+};"#;
+
+    let stratification = parse_query(query).unwrap().into_define().rule_stratification().unwrap();
+
+    assert_eq!(
+        stratification.to_string(),
+        r#"stratum 0: direct-reachability, transitive-reachability
+stratum 1: unreachable, isolated"#
+    );
+    assert_eq!(stratification.stratum_of(&Label::from("isolated")), Some(1));
+}
+
+#[test]
+fn rule_stratification_rejects_negation_cycle() {
+    let query = r#"define
+rule lonely: when {
+    $x isa person;
+    not { $x has status "popular"; };
+} then {
+    $x has status "lonely";
+};"#;
+
+    let parsed = parse_query(query);
+    assert!(parsed.is_err());
+    assert!(parsed.unwrap_err().to_string().contains(
+        "Rule 'lonely' negates 'status', which is inferred by rule 'lonely' in the same recursive cycle (lonely -> lonely [not status])"
+    ));
+}
+
+#[test]
+fn evaluate_expressions() {
+    let bindings: HashMap<Variable, Constant> = [
+        (cvar("x").into(), Constant::Long(7)),
+        (vvar("y").into(), Constant::Double(2.5)),
+        (vvar("s").into(), Constant::String("seven".to_owned())),
+    ]
+    .into_iter()
+    .collect();
+    let eval = |expression: Expression| evaluate(&expression, &bindings);
+
+    assert_eq!(eval(cvar("x").add(round(vvar("y")).multiply(2)).into()).unwrap(), Constant::Long(13));
+    assert_eq!(eval(cvar("x").divide(2).into()).unwrap(), Constant::Long(3));
+    assert_eq!(eval(cvar("x").modulo(-4).into()).unwrap(), Constant::Long(3));
+    assert_eq!(eval(cvar("x").divide(vvar("y")).into()).unwrap(), Constant::Double(2.8));
+    assert_eq!(eval(cvar("x").power(2).into()).unwrap(), Constant::Long(49));
+    assert_eq!(eval(Expression::from(2).power(-1).into()).unwrap(), Constant::Double(0.5));
+    assert_eq!(eval(abs(Expression::from(1).subtract(cvar("x"))).into()).unwrap(), Constant::Long(6));
+    assert_eq!(eval(ceil(vvar("y")).into()).unwrap(), Constant::Long(3));
+    assert_eq!(eval(floor(vvar("y")).into()).unwrap(), Constant::Long(2));
+    assert_eq!(eval(round(Expression::from(-2.5)).into()).unwrap(), Constant::Long(-2));
+    assert_eq!(eval(round(Expression::from(-2.6)).into()).unwrap(), Constant::Long(-3));
+    assert_eq!(eval(max!(cvar("x"), 3)).unwrap(), Constant::Long(7));
+    assert_eq!(eval(min!(cvar("x"), vvar("y"))).unwrap(), Constant::Double(2.5));
+
+    let error = |expression: Expression| eval(expression).unwrap_err().to_string();
+    assert!(error(cvar("x").divide(0).into()).contains("'$x / 0' divides by zero"));
+    assert!(error(cvar("x").modulo(0.0).into()).contains("divides by zero"));
+    assert!(error(cvar("x").add(vvar("s")).into())
+        .contains("only be applied to numeric operands, but the operand '?s' is not numeric"));
+    assert!(error(Expression::from(i64::MAX).add(1).into()).contains("does not fit in a long value"));
+    assert!(error(cvar("z").add(1).into()).contains("'$z' is not bound to a value"));
+    assert!(error(max!()).contains("'max' cannot be applied to 0 argument(s)"));
+}
+
+#[test]
+fn power_value_type_agrees_with_evaluation() {
+    let variable_types = HashMap::from([(vvar("n"), token::ValueType::Long)]);
+    let power = |exponent: Expression| Expression::from(Expression::from(2).power(exponent));
+    let value_type = |expression: &Expression| expression.value_type(&variable_types).unwrap();
+    let eval = |expression: &Expression, exponent: i64| {
+        evaluate(expression, &HashMap::from([(vvar("n").into(), Constant::Long(exponent))])).unwrap()
+    };
+
+    let variable_exponent = power(vvar("n").into());
+    assert_eq!(value_type(&variable_exponent), None);
+    assert_eq!(eval(&variable_exponent, 3), Constant::Long(8));
+    assert_eq!(eval(&variable_exponent, -1), Constant::Double(0.5));
+    assert_eq!(value_type(&power(3.into())), Some(token::ValueType::Long));
+    assert_eq!(value_type(&power((-1).into())), Some(token::ValueType::Double));
+    assert_eq!(value_type(&power(1.5.into())), Some(token::ValueType::Double));
+}
+
+#[test]
+fn evaluate_string_and_datetime_functions() {
+    let date_time = NaiveDate::from_ymd_opt(2024, 1, 31).unwrap().and_hms_opt(9, 30, 0).unwrap();
+    let bindings: HashMap<Variable, Constant> = [
+        (cvar("name").into(), Constant::String("Gödel".to_owned())),
+        (cvar("date").into(), Constant::DateTime(date_time)),
+        (cvar("day").into(), Constant::Date(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap())),
+        (
+            cvar("zoned").into(),
+            Constant::DateTimeTZ(FixedOffset::east_opt(3600).unwrap().from_local_datetime(&date_time).unwrap()),
+        ),
+    ]
+    .into_iter()
+    .collect();
+    let eval = |expression: Expression| evaluate(&expression, &bindings);
+    let duration = |duration: &str| duration.parse::<Duration>().unwrap();
+
+    assert_eq!(eval(length(cvar("name")).into()).unwrap(), Constant::Long(5));
+    assert_eq!(eval(uppercase(cvar("name")).into()).unwrap(), Constant::String("GÖDEL".to_owned()));
+    assert_eq!(eval(substring(cvar("name"), 1, 3).into()).unwrap(), Constant::String("öde".to_owned()));
+    assert_eq!(eval(substring(cvar("name"), -2, 10).into()).unwrap(), Constant::String("Gödel".to_owned()));
+    assert_eq!(
+        eval(concat::<Expression>([cvar("name").into(), ", ".into(), "Kurt".into()]).into()).unwrap(),
+        Constant::String("Gödel, Kurt".to_owned())
+    );
+    assert_eq!(eval(year(cvar("date")).into()).unwrap(), Constant::Long(2024));
+    assert_eq!(eval(month(cvar("date")).into()).unwrap(), Constant::Long(1));
+    assert_eq!(eval(day(cvar("date")).into()).unwrap(), Constant::Long(31));
+    assert_eq!(eval(month(cvar("day")).into()).unwrap(), Constant::Long(2));
+    assert_eq!(eval(day(cvar("zoned")).into()).unwrap(), Constant::Long(31));
+    assert_eq!(
+        eval(add_duration(cvar("day"), duration("P1DT12H")).into()).unwrap(),
+        Constant::DateTime(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(12, 0, 0).unwrap())
+    );
+    assert_eq!(
+        eval(subtract_duration(cvar("zoned"), duration("PT10H")).into()).unwrap(),
+        Constant::DateTimeTZ(FixedOffset::east_opt(3600).unwrap().with_ymd_and_hms(2024, 1, 30, 23, 30, 0).unwrap())
+    );
+    assert_eq!(
+        eval(add_duration(cvar("date"), duration("P1MT2H30.5S")).into()).unwrap(),
+        Constant::DateTime(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap().and_hms_milli_opt(11, 30, 30, 500).unwrap())
+    );
+    assert_eq!(
+        eval(subtract_duration(cvar("date"), duration("P1Y1W")).into()).unwrap(),
+        Constant::DateTime(NaiveDate::from_ymd_opt(2023, 1, 24).unwrap().and_hms_opt(9, 30, 0).unwrap())
+    );
+
+    let error = |expression: Expression| eval(expression).unwrap_err().to_string();
+    assert!(error(length(cvar("date")).into()).contains("'length' expects an argument of type 'string', got"));
+    assert!(error(year(cvar("name")).into())
+        .contains("'year' expects an argument of type 'date', 'datetime' or 'datetime-tz', got '\"Gödel\"'"));
+    assert!(error(add_duration(cvar("date"), "P1M").into()).contains("expects an argument of type 'duration'"));
+    assert!(error(add_duration(cvar("date"), duration("P999999Y")).into()).contains("is not a valid datetime"));
+    assert!("1 month"
+        .parse::<Duration>()
+        .unwrap_err()
+        .to_string()
+        .contains("'1 month' is not a valid ISO-8601 duration"));
+    assert!("PT".parse::<Duration>().is_err());
+}
+
+#[test]
+fn simplify_value_statements() {
+    let query = r#"match
+$p isa person, has age $a;
+?x = 1 + 2 * 3;
+?y = ($a + 0) * (2 - 1);
+?z = ($a - 1) - (2 + 3);
+?w = $a / (4 - 4);
+?v = max(1.5, 2) + round(?x);
+?u = round(?x) * 1 + 0;
+?s = ?y * 1;
+?r = ?y / 1 + ?x / 1;
+not { ?t = $a ^ 1 * 1.0; ?t > 100; };
+get;"#;
+
+    let mut parsed = parse_query(query).unwrap();
+    parsed.simplify();
+
+    assert_eq!(
+        parsed.to_string(),
+        r#"match
+$p isa person,
+    has age $a;
+?x = 7;
+?y = $a;
+?z = $a - 1 - 5;
+?w = $a / 0;
+?v = 2.0 + round(?x);
+?u = round(?x);
+?s = ?y;
+?r = ?y / 1 + ?x;
+not {
+    ?t = $a ^ 1 * 1.0;
+    ?t > 100;
+};
+get;"#
+    );
+}
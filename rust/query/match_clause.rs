@@ -4,7 +4,10 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use std::{fmt, iter};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, iter, slice,
+};
 
 use crate::{
     common::{
@@ -13,11 +16,15 @@ use crate::{
         validatable::Validatable,
         Error,
     },
-    pattern::{Conjunction, Pattern, VariablesRetrieved},
+    pattern::{
+        assignments, infer_value_types, Conjunction, Pattern, Simplifiable, Statement, ValueStatement,
+        VariablesRetrieved,
+    },
     query::{
-        modifier::Modifiers, typeql_get, Projection, TypeQLDelete, TypeQLFetch, TypeQLGet, TypeQLInsert, Writable,
+        modifier::Modifiers, typeql_get, Projection, TypeQLDelete, TypeQLFetch, TypeQLGet, TypeQLInsert, TypeQLPut,
+        Writable,
     },
-    variable::{variable::VariableRef, Variable},
+    variable::{variable::VariableRef, ValueVariable, Variable},
     Result,
 };
 
@@ -32,21 +39,26 @@ impl MatchClause {
     }
 
     pub fn get(self) -> TypeQLGet {
+        self.get_vars(Vec::default())
+    }
+
+    pub fn get_vars(self, vars: Vec<Variable>) -> TypeQLGet {
         TypeQLGet {
             match_clause: self,
-            filter: typeql_get::Filter { vars: Vec::default() },
+            filter: typeql_get::Filter { vars },
+            selection: None,
             modifiers: Modifiers::default(),
         }
     }
 
-    pub fn get_vars(self, vars: Vec<Variable>) -> TypeQLGet {
-        TypeQLGet { match_clause: self, filter: typeql_get::Filter { vars }, modifiers: Modifiers::default() }
-    }
-
     pub fn get_fixed<const N: usize, T: Into<Variable>>(self, vars: [T; N]) -> TypeQLGet {
         self.get_vars(vars.into_iter().map(|var| var.into()).collect())
     }
 
+    pub fn select(self, selection: impl Into<typeql_get::Selection>) -> TypeQLGet {
+        TypeQLGet { selection: Some(selection.into()), ..self.get() }
+    }
+
     pub fn fetch(self, projections: Vec<Projection>) -> TypeQLFetch {
         TypeQLFetch { match_clause: self, projections, modifiers: Modifiers::default() }
     }
@@ -63,15 +75,77 @@ impl MatchClause {
         TypeQLDelete { match_clause: self, statements: writable.statements(), modifiers: Modifiers::default() }
     }
 
+    pub fn put(self, writable: impl Writable) -> TypeQLPut {
+        TypeQLPut { match_clause: Some(self), statements: writable.statements() }
+    }
+
     fn validate_nested_patterns_are_bounded(&self) -> Result {
         let bounds = self.conjunction.retrieved_variables().collect();
         collect_err(self.conjunction.patterns.iter().map(|p| p.validate_is_bounded_by(&bounds)))
     }
+
+    fn validate_reductions_are_bound(&self) -> Result {
+        let patterns = &self.conjunction.patterns;
+        let unbound: Vec<Variable> = patterns
+            .iter()
+            .enumerate()
+            .filter_map(|(i, pattern)| match pattern {
+                Pattern::Statement(Statement::Value(ValueStatement { reduce_constraint: Some(reduce), .. })) => {
+                    Some((i, reduce))
+                }
+                _ => None,
+            })
+            .flat_map(|(i, reduce)| {
+                let bound: HashSet<VariableRef<'_>> = patterns
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .flat_map(|(_, pattern)| pattern.retrieved_variables())
+                    .collect();
+                reduce
+                    .variables()
+                    .filter(|variable| !bound.contains(variable))
+                    .map(|variable| variable.to_owned())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        collect_err(unbound.into_iter().map(|variable| Err(TypeQLError::ReduceVarNotBound { variable })?))
+    }
+
+    fn validate_assignments_are_well_typed(&self) -> Result {
+        validate_assignments_are_well_typed(&self.conjunction.patterns, &HashMap::new())
+    }
+
+    fn validate_value_variables_are_assigned_once(&self) -> Result {
+        let mut assigned = HashSet::new();
+        let repeated: HashSet<&ValueVariable> = self
+            .conjunction
+            .patterns
+            .iter()
+            .filter_map(|pattern| match pattern {
+                Pattern::Statement(Statement::Value(statement))
+                    if statement.assign_constraint.is_some() || statement.reduce_constraint.is_some() =>
+                {
+                    Some(&statement.variable)
+                }
+                _ => None,
+            })
+            .filter(|variable| !assigned.insert(*variable))
+            .collect();
+        collect_err(repeated.into_iter().map(|variable| {
+            Err(TypeQLError::ValueVarAssignedMoreThanOnce { variable: VariableRef::Value(variable).to_owned() })?
+        }))
+    }
 }
 
 impl VariablesRetrieved for MatchClause {
     fn retrieved_variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
-        self.conjunction.retrieved_variables()
+        Box::new(self.conjunction.retrieved_variables().chain(self.conjunction.patterns.iter().flat_map(|pattern| {
+            match pattern {
+                Pattern::Optional(optional) => optional.retrieved_variables(),
+                _ => Box::new(iter::empty()),
+            }
+        })))
     }
 }
 
@@ -79,10 +153,45 @@ impl Validatable for MatchClause {
     fn validate(&self) -> Result {
         self.validate_nested_patterns_are_bounded()?;
         validate_statements_have_named_variable(self.conjunction.patterns.iter())?;
-        collect_err(self.conjunction.patterns.iter().map(|p| p.validate()))
+        collect_err(self.conjunction.patterns.iter().map(|p| p.validate()).chain([
+            self.validate_assignments_are_well_typed(),
+            self.validate_reductions_are_bound(),
+            self.validate_value_variables_are_assigned_once(),
+        ]))
     }
 }
 
+fn validate_assignments_are_well_typed(
+    patterns: &[Pattern],
+    outer_types: &HashMap<ValueVariable, token::ValueType>,
+) -> Result {
+    let variable_types = infer_value_types(patterns, outer_types);
+    let nested = patterns.iter().map(|pattern| match pattern {
+        Pattern::Conjunction(conjunction) => {
+            validate_assignments_are_well_typed(&conjunction.patterns, &variable_types)
+        }
+        Pattern::Disjunction(disjunction) => collect_err(
+            disjunction
+                .patterns
+                .iter()
+                .map(|branch| validate_assignments_are_well_typed(slice::from_ref(branch), &variable_types)),
+        ),
+        Pattern::Negation(negation) => {
+            validate_assignments_are_well_typed(slice::from_ref(negation.pattern.as_ref()), &variable_types)
+        }
+        Pattern::Optional(optional) => {
+            validate_assignments_are_well_typed(slice::from_ref(optional.pattern.as_ref()), &variable_types)
+        }
+        Pattern::Statement(_) => Ok(()),
+    });
+    collect_err(
+        assignments(patterns)
+            .filter(|(_, expression)| expression.value_type(&HashMap::new()).is_ok())
+            .map(|(_, expression)| expression.value_type(&variable_types).map(|_| ()))
+            .chain(nested),
+    )
+}
+
 fn validate_statements_have_named_variable<'a>(patterns: impl Iterator<Item = &'a Pattern>) -> Result {
     collect_err(patterns.map(|pattern| {
         match pattern {
@@ -94,10 +203,17 @@ fn validate_statements_have_named_variable<'a>(patterns: impl Iterator<Item = &'
             Pattern::Conjunction(c) => validate_statements_have_named_variable(c.patterns.iter()),
             Pattern::Disjunction(d) => validate_statements_have_named_variable(d.patterns.iter()),
             Pattern::Negation(n) => validate_statements_have_named_variable(iter::once(n.pattern.as_ref())),
+            Pattern::Optional(o) => validate_statements_have_named_variable(iter::once(o.pattern.as_ref())),
         }
     }))
 }
 
+impl Simplifiable for MatchClause {
+    fn simplify(&mut self) {
+        self.conjunction.simplify();
+    }
+}
+
 impl fmt::Display for MatchClause {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", token::Clause::Match)?;
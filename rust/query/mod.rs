@@ -7,17 +7,19 @@
 use std::fmt;
 
 pub use match_clause::MatchClause;
-pub use modifier::{sorting, Limit, Offset, Sorting};
+pub use modifier::{sorting, Cursor, Distinct, Limit, Offset, Sorting};
 pub use typeql_define::TypeQLDefine;
 pub use typeql_delete::TypeQLDelete;
 pub use typeql_fetch::{
     Projection, ProjectionAttribute, ProjectionBuilder, ProjectionKeyLabel, ProjectionKeyVar, ProjectionKeyVarBuilder,
     ProjectionSubquery, TypeQLFetch,
 };
-pub use typeql_get::{Filter, TypeQLGet};
-pub use typeql_get_aggregate::{AggregateQueryBuilder, TypeQLGetAggregate, TypeQLGetGroupAggregate};
+pub use typeql_get::{Filter, SelectVariable, Selection, TypeQLGet};
+pub use typeql_get_aggregate::{AggregateQueryBuilder, Aggregation, TypeQLGetAggregate, TypeQLGetGroupAggregate};
 pub use typeql_get_group::TypeQLGetGroup;
 pub use typeql_insert::TypeQLInsert;
+pub use typeql_pipeline::{Stage, TypeQLPipeline};
+pub use typeql_put::TypeQLPut;
 pub use typeql_undefine::TypeQLUndefine;
 pub use typeql_update::TypeQLUpdate;
 pub use writable::Writable;
@@ -25,6 +27,7 @@ pub use writable::Writable;
 use crate::{
     common::{validatable::Validatable, Result},
     enum_getter, enum_wrapper,
+    pattern::Simplifiable,
 };
 
 mod match_clause;
@@ -36,6 +39,8 @@ mod typeql_get;
 mod typeql_get_aggregate;
 mod typeql_get_group;
 mod typeql_insert;
+mod typeql_pipeline;
+mod typeql_put;
 mod typeql_undefine;
 mod typeql_update;
 mod writable;
@@ -45,6 +50,7 @@ pub enum Query {
     Define(TypeQLDefine),
     Undefine(TypeQLUndefine),
     Insert(TypeQLInsert),
+    Put(TypeQLPut),
     Delete(TypeQLDelete),
     Update(TypeQLUpdate),
     GetAggregate(TypeQLGetAggregate),
@@ -52,12 +58,14 @@ pub enum Query {
     GetGroup(TypeQLGetGroup),
     GetGroupAggregate(TypeQLGetGroupAggregate),
     Fetch(TypeQLFetch),
+    Pipeline(TypeQLPipeline),
 }
 
 enum_getter! { Query
     into_define(Define) => TypeQLDefine,
     into_undefine(Undefine) => TypeQLUndefine,
     into_insert(Insert) => TypeQLInsert,
+    into_put(Put) => TypeQLPut,
     into_delete(Delete) => TypeQLDelete,
     into_update(Update) => TypeQLUpdate,
     into_get(Get) => TypeQLGet,
@@ -65,19 +73,22 @@ enum_getter! { Query
     into_get_group(GetGroup) => TypeQLGetGroup,
     into_get_group_aggregate(GetGroupAggregate) => TypeQLGetGroupAggregate,
     into_fetch(Fetch) => TypeQLFetch,
+    into_pipeline(Pipeline) => TypeQLPipeline,
 }
 
 enum_wrapper! { Query
     TypeQLDefine => Define,
     TypeQLUndefine => Undefine,
     TypeQLInsert => Insert,
+    TypeQLPut => Put,
     TypeQLDelete => Delete,
     TypeQLUpdate => Update,
     TypeQLGet => Get,
     TypeQLGetAggregate => GetAggregate,
     TypeQLGetGroup => GetGroup,
     TypeQLGetGroupAggregate => GetGroupAggregate,
-    TypeQLFetch => Fetch
+    TypeQLFetch => Fetch,
+    TypeQLPipeline => Pipeline
 }
 
 impl Validatable for Query {
@@ -86,6 +97,7 @@ impl Validatable for Query {
             Query::Define(query) => query.validate(),
             Query::Undefine(query) => query.validate(),
             Query::Insert(query) => query.validate(),
+            Query::Put(query) => query.validate(),
             Query::Delete(query) => query.validate(),
             Query::Update(query) => query.validate(),
             Query::Get(query) => query.validate(),
@@ -93,6 +105,7 @@ impl Validatable for Query {
             Query::GetGroup(query) => query.validate(),
             Query::GetGroupAggregate(query) => query.validate(),
             Query::Fetch(query) => query.validate(),
+            Query::Pipeline(query) => query.validate(),
         }
     }
 
@@ -101,6 +114,7 @@ impl Validatable for Query {
             Query::Define(query) => query.validated().map(TypeQLDefine::into),
             Query::Undefine(query) => query.validated().map(TypeQLUndefine::into),
             Query::Insert(query) => query.validated().map(TypeQLInsert::into),
+            Query::Put(query) => query.validated().map(TypeQLPut::into),
             Query::Delete(query) => query.validated().map(TypeQLDelete::into),
             Query::Update(query) => query.validated().map(TypeQLUpdate::into),
             Query::Get(query) => query.validated().map(TypeQLGet::into),
@@ -108,6 +122,26 @@ impl Validatable for Query {
             Query::GetGroup(query) => query.validated().map(TypeQLGetGroup::into),
             Query::GetGroupAggregate(query) => query.validated().map(TypeQLGetGroupAggregate::into),
             Query::Fetch(query) => query.validated().map(TypeQLFetch::into),
+            Query::Pipeline(query) => query.validated().map(TypeQLPipeline::into),
+        }
+    }
+}
+
+impl Simplifiable for Query {
+    fn simplify(&mut self) {
+        match self {
+            Query::Define(query) => query.simplify(),
+            Query::Undefine(_) => (),
+            Query::Insert(query) => query.simplify(),
+            Query::Put(query) => query.simplify(),
+            Query::Delete(query) => query.simplify(),
+            Query::Update(query) => query.simplify(),
+            Query::Get(query) => query.simplify(),
+            Query::GetAggregate(query) => query.simplify(),
+            Query::GetGroup(query) => query.simplify(),
+            Query::GetGroupAggregate(query) => query.simplify(),
+            Query::Fetch(query) => query.simplify(),
+            Query::Pipeline(query) => query.simplify(),
         }
     }
 }
@@ -118,6 +152,7 @@ impl fmt::Display for Query {
             Query::Define(query) => write!(f, "{query}"),
             Query::Undefine(query) => write!(f, "{query}"),
             Query::Insert(query) => write!(f, "{query}"),
+            Query::Put(query) => write!(f, "{query}"),
             Query::Delete(query) => write!(f, "{query}"),
             Query::Update(query) => write!(f, "{query}"),
             Query::Get(query) => write!(f, "{query}"),
@@ -125,6 +160,7 @@ impl fmt::Display for Query {
             Query::GetGroup(query) => write!(f, "{query}"),
             Query::GetGroupAggregate(query) => write!(f, "{query}"),
             Query::Fetch(query) => write!(f, "{query}"),
+            Query::Pipeline(query) => write!(f, "{query}"),
         }
     }
 }
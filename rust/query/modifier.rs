@@ -11,26 +11,41 @@ use crate::{
         error::{collect_err, TypeQLError},
         token, Result,
     },
-    variable::variable::VariableRef,
+    pattern::Constant,
+    variable::{variable::VariableRef, Variable},
     write_joined,
 };
 
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub struct Modifiers {
+    pub distinct: Option<Distinct>,
     pub sorting: Option<Sorting>,
+    pub cursor: Option<Cursor>,
     pub limit: Option<Limit>,
     pub offset: Option<Offset>,
 }
 
 impl Modifiers {
     pub fn is_empty(&self) -> bool {
-        self.sorting.is_none() && self.limit.is_none() && self.offset.is_none()
+        self.distinct.is_none()
+            && self.sorting.is_none()
+            && self.cursor.is_none()
+            && self.limit.is_none()
+            && self.offset.is_none()
+    }
+
+    pub fn distinct(self) -> Self {
+        Self { distinct: Some(Distinct), ..self }
     }
 
     pub fn sort(self, sorting: impl Into<Sorting>) -> Self {
         Self { sorting: Some(sorting.into()), ..self }
     }
 
+    pub fn after(self, cursor: Cursor) -> Self {
+        Self { cursor: Some(cursor), ..self }
+    }
+
     pub fn limit(self, limit: usize) -> Self {
         Self { limit: Some(Limit { limit }), ..self }
     }
@@ -40,10 +55,19 @@ impl Modifiers {
     }
 }
 
+impl Modifiers {
+    pub(crate) fn validate(&self, available_variables: &HashSet<VariableRef<'_>>) -> Result {
+        collect_err([
+            self.sorting.as_ref().map_or(Ok(()), |sorting| sorting.validate(available_variables)),
+            self.cursor.as_ref().map_or(Ok(()), |cursor| cursor.validate(self.sorting.as_ref())),
+        ])
+    }
+}
+
 impl fmt::Display for Modifiers {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if !self.is_empty() {
-            write_joined!(f, "; ", self.sorting, self.offset, self.limit)?;
+            write_joined!(f, "; ", self.distinct, self.sorting, self.cursor, self.offset, self.limit)?;
             f.write_str(";")
         } else {
             Ok(())
@@ -63,65 +87,82 @@ pub mod sorting {
     pub struct SortVariable {
         pub variable: Variable,
         pub order: Option<token::Order>,
+        pub nulls: Option<token::NullsOrder>,
+        pub collation: Option<Collation>,
+    }
+
+    pub trait SortVariableBuilder {
+        fn nulls(self, nulls: token::NullsOrder) -> SortVariable;
+        fn collate(self, collation: Collation) -> SortVariable;
+    }
+
+    impl<T: Into<SortVariable>> SortVariableBuilder for T {
+        fn nulls(self, nulls: token::NullsOrder) -> SortVariable {
+            SortVariable { nulls: Some(nulls), ..self.into() }
+        }
+
+        fn collate(self, collation: Collation) -> SortVariable {
+            SortVariable { collation: Some(collation), ..self.into() }
+        }
     }
 
     impl From<Variable> for SortVariable {
         fn from(variable: Variable) -> Self {
-            SortVariable { variable, order: None }
+            SortVariable { variable, order: None, nulls: None, collation: None }
         }
     }
 
     impl From<(Variable, token::Order)> for SortVariable {
         fn from(ordered_var: (Variable, token::Order)) -> Self {
             let (variable, order) = ordered_var;
-            SortVariable { variable, order: Some(order) }
+            SortVariable { variable, order: Some(order), nulls: None, collation: None }
         }
     }
 
     impl From<(Variable, Option<token::Order>)> for SortVariable {
         fn from(ordered_var: (Variable, Option<token::Order>)) -> Self {
             let (variable, order) = ordered_var;
-            SortVariable { variable, order }
+            SortVariable { variable, order, nulls: None, collation: None }
         }
     }
 
     impl From<ConceptVariable> for SortVariable {
         fn from(variable: ConceptVariable) -> Self {
-            SortVariable { variable: variable.into(), order: None }
+            SortVariable { variable: variable.into(), order: None, nulls: None, collation: None }
         }
     }
 
     impl From<(ConceptVariable, token::Order)> for SortVariable {
         fn from(ordered_var: (ConceptVariable, token::Order)) -> Self {
             let (variable, order) = ordered_var;
-            SortVariable { variable: variable.into(), order: Some(order) }
+            SortVariable { variable: variable.into(), order: Some(order), nulls: None, collation: None }
         }
     }
 
     impl From<(ConceptVariable, Option<token::Order>)> for SortVariable {
         fn from(ordered_var: (ConceptVariable, Option<token::Order>)) -> Self {
             let (variable, order) = ordered_var;
-            SortVariable { variable: variable.into(), order }
+            SortVariable { variable: variable.into(), order, nulls: None, collation: None }
         }
     }
 
     impl From<ValueVariable> for SortVariable {
         fn from(variable: ValueVariable) -> Self {
-            SortVariable { variable: variable.into(), order: None }
+            SortVariable { variable: variable.into(), order: None, nulls: None, collation: None }
         }
     }
 
     impl From<(ValueVariable, token::Order)> for SortVariable {
         fn from(ordered_var: (ValueVariable, token::Order)) -> Self {
             let (variable, order) = ordered_var;
-            SortVariable { variable: variable.into(), order: Some(order) }
+            SortVariable { variable: variable.into(), order: Some(order), nulls: None, collation: None }
         }
     }
 
     impl From<(ValueVariable, Option<token::Order>)> for SortVariable {
         fn from(ordered_var: (ValueVariable, Option<token::Order>)) -> Self {
             let (variable, order) = ordered_var;
-            SortVariable { variable: variable.into(), order }
+            SortVariable { variable: variable.into(), order, nulls: None, collation: None }
         }
     }
 
@@ -131,9 +172,28 @@ pub mod sorting {
             if let Some(order) = self.order {
                 write!(f, " {order}")?;
             }
+            if let Some(nulls) = self.nulls {
+                write!(f, " {} {nulls}", token::Modifier::Nulls)?;
+            }
+            if let Some(collation) = &self.collation {
+                write!(f, " {} {collation}", token::Modifier::Collate)?;
+            }
             Ok(())
         }
     }
+
+    #[derive(Clone, Debug, Eq, PartialEq)]
+    pub enum Collation {
+        CaseInsensitive,
+    }
+
+    impl fmt::Display for Collation {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Collation::CaseInsensitive => write!(f, "{}", token::Modifier::NoCase),
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -175,6 +235,65 @@ impl fmt::Display for Sorting {
     }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cursor {
+    pub vars: Vec<Variable>,
+    pub values: Vec<Constant>,
+}
+
+impl Cursor {
+    pub fn new(vars: Vec<Variable>, values: Vec<Constant>) -> Self {
+        Cursor { vars, values }
+    }
+
+    fn validate(&self, sorting: Option<&Sorting>) -> Result {
+        let Some(sorting) = sorting else {
+            return Err(TypeQLError::CursorRequiresSorting.into());
+        };
+        let sort_vars = sorting.vars.iter().map(|sort_var| &sort_var.variable);
+        collect_err(
+            [
+                if sort_vars.eq(&self.vars) {
+                    Ok(())
+                } else {
+                    Err(TypeQLError::CursorVarsNotSorted { cursor: self.to_string(), sorting: sorting.to_string() })?
+                },
+                if self.vars.len() == self.values.len() {
+                    Ok(())
+                } else {
+                    Err(TypeQLError::CursorValuesMismatch { cursor: self.to_string() })?
+                },
+            ]
+            .into_iter()
+            .chain(self.values.iter().map(|value| match value {
+                Constant::List(_) | Constant::Struct(_) => {
+                    Err(TypeQLError::InvalidCursorValue { value: value.clone() })?
+                }
+                _ => Ok(()),
+            })),
+        )
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (", token::Modifier::After)?;
+        write_joined!(f, ", ", self.vars)?;
+        f.write_str(") (")?;
+        write_joined!(f, ", ", self.values)?;
+        f.write_str(")")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+pub struct Distinct;
+
+impl fmt::Display for Distinct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", token::Modifier::Distinct)
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
 pub struct Limit {
     pub limit: usize,
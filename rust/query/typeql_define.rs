@@ -13,7 +13,11 @@ use crate::{
         validatable::Validatable,
         Result,
     },
-    pattern::{Definable, Rule, TypeStatement},
+    pattern::{
+        validate_functions_are_not_recursive, validate_value_annotations, Definable, FunctionLabel, Label, Rule,
+        RuleDependencyGraph, RuleStratification, SchemaFunction, Simplifiable, Struct, TypeStatement, ValueType,
+    },
+    variable::TypeReference,
     write_joined,
 };
 
@@ -21,6 +25,9 @@ use crate::{
 pub struct TypeQLDefine {
     statements: Vec<TypeStatement>,
     rules: Vec<Rule>,
+    functions: Vec<SchemaFunction>,
+    function_declarations: Vec<FunctionLabel>,
+    structs: Vec<Struct>,
 }
 
 impl TypeQLDefine {
@@ -31,9 +38,36 @@ impl TypeQLDefine {
             Definable::RuleDeclaration(r) => {
                 panic!("{}", TypeQLError::InvalidRuleWhenMissingPatterns { rule_label: r.label })
             }
+            Definable::FunctionDefinition(function) => define.add_function(function),
+            Definable::FunctionDeclaration(function) => define.add_function_declaration(function),
+            Definable::Struct(struct_) => define.add_struct(struct_),
         })
     }
 
+    pub fn statements(&self) -> &[TypeStatement] {
+        &self.statements
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn functions(&self) -> &[SchemaFunction] {
+        &self.functions
+    }
+
+    pub fn structs(&self) -> &[Struct] {
+        &self.structs
+    }
+
+    pub fn rule_dependency_graph(&self) -> RuleDependencyGraph {
+        RuleDependencyGraph::with_types(&self.rules, &self.statements)
+    }
+
+    pub fn rule_stratification(&self) -> Result<RuleStratification> {
+        RuleStratification::new(&self.rule_dependency_graph())
+    }
+
     fn add_statement(mut self, statement: TypeStatement) -> Self {
         self.statements.push(statement);
         self
@@ -44,8 +78,66 @@ impl TypeQLDefine {
         self
     }
 
+    fn add_function(mut self, function: SchemaFunction) -> Self {
+        self.functions.push(function);
+        self
+    }
+
+    fn add_function_declaration(mut self, function: FunctionLabel) -> Self {
+        self.function_declarations.push(function);
+        self
+    }
+
+    fn add_struct(mut self, struct_: Struct) -> Self {
+        self.structs.push(struct_);
+        self
+    }
+
+    fn validate_owns_annotations(&self) -> Result {
+        collect_err(self.statements.iter().flat_map(|statement| &statement.owns).filter_map(|owns| {
+            let TypeReference::Label(attribute_type) = &owns.attribute_type else { return None };
+            Some(validate_value_annotations(&owns.annotations, self.value_type(attribute_type)?))
+        }))
+    }
+
+    fn value_type(&self, label: &Label) -> Option<&ValueType> {
+        let mut label = label;
+        for _ in 0..self.statements.len() {
+            let statement = self.statements.iter().find(|statement| {
+                statement.label.as_ref().is_some_and(|constraint| &constraint.label == label)
+                    && (statement.value_type.is_some() || statement.sub.is_some())
+            })?;
+            match (&statement.value_type, statement.sub.as_ref().map(|sub| &sub.type_)) {
+                (Some(value_type), _) => return Some(&value_type.value_type),
+                (None, Some(TypeReference::Label(supertype))) => label = supertype,
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    fn validate_functions_have_bodies(&self) -> Result {
+        collect_err(self.function_declarations.iter().map(|function| {
+            Err(TypeQLError::InvalidFunctionWhenMissingBody { function_label: function.label.clone() })?
+        }))
+    }
+
+    fn validate_structs_have_fields(&self) -> Result {
+        collect_err(
+            self.structs
+                .iter()
+                .filter(|struct_| struct_.fields.is_empty())
+                .map(|struct_| Err(TypeQLError::InvalidStructWhenMissingFields { struct_name: struct_.name.clone() })?),
+        )
+    }
+
     fn validate_non_empty(&self) -> Result {
-        if self.statements.is_empty() && self.rules.is_empty() {
+        if self.statements.is_empty()
+            && self.rules.is_empty()
+            && self.functions.is_empty()
+            && self.function_declarations.is_empty()
+            && self.structs.is_empty()
+        {
             Err(TypeQLError::MissingDefinables)?
         }
         Ok(())
@@ -58,15 +150,29 @@ impl Validatable for TypeQLDefine {
             iter::once(self.validate_non_empty())
                 .chain(self.statements.iter().map(Validatable::validate))
                 .chain(self.statements.iter().map(TypeStatement::validate_definable))
-                .chain(self.rules.iter().map(Validatable::validate)),
+                .chain(iter::once(self.validate_owns_annotations()))
+                .chain(self.rules.iter().map(Validatable::validate))
+                .chain(self.functions.iter().map(Validatable::validate))
+                .chain(iter::once(validate_functions_are_not_recursive(&self.functions)))
+                .chain(iter::once(self.validate_functions_have_bodies()))
+                .chain(self.structs.iter().map(Validatable::validate))
+                .chain(iter::once(self.validate_structs_have_fields()))
+                .chain(iter::once(self.rule_stratification().map(|_| ()))),
         )
     }
 }
 
+impl Simplifiable for TypeQLDefine {
+    fn simplify(&mut self) {
+        self.rules.iter_mut().for_each(Rule::simplify);
+        self.functions.iter_mut().for_each(SchemaFunction::simplify);
+    }
+}
+
 impl fmt::Display for TypeQLDefine {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{}", token::Clause::Define)?;
-        write_joined!(f, ";\n", self.statements, self.rules)?;
+        write_joined!(f, ";\n", self.structs, self.statements, self.rules, self.functions, self.function_declarations)?;
         f.write_str(";")
     }
 }
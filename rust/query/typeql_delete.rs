@@ -13,7 +13,7 @@ use crate::{
         validatable::Validatable,
         Result,
     },
-    pattern::{ThingStatement, VariablesRetrieved},
+    pattern::{Simplifiable, ThingStatement, VariablesRetrieved},
     query::{modifier::Modifiers, writable::validate_non_empty, MatchClause, Sorting, TypeQLUpdate, Writable},
     variable::variable::VariableRef,
     write_joined,
@@ -52,7 +52,7 @@ impl Validatable for TypeQLDelete {
                 self.match_clause.validate(),
                 validate_delete_in_scope(&match_variables, &self.statements),
                 validate_non_empty(&self.statements),
-                self.modifiers.sorting.as_ref().map(|s| s.validate(&match_variables)).unwrap_or(Ok(())),
+                self.modifiers.validate(&match_variables),
             ]
             .into_iter())
             .chain(self.statements.iter().map(Validatable::validate)),
@@ -60,7 +60,10 @@ impl Validatable for TypeQLDelete {
     }
 }
 
-fn validate_delete_in_scope(scope_variables: &HashSet<VariableRef<'_>>, statements: &[ThingStatement]) -> Result {
+pub(crate) fn validate_delete_in_scope(
+    scope_variables: &HashSet<VariableRef<'_>>,
+    statements: &[ThingStatement],
+) -> Result {
     collect_err(statements.iter().flat_map(|v| v.variables()).filter(|r| r.is_name()).map(|r| -> Result {
         if scope_variables.contains(&r) {
             Ok(())
@@ -70,6 +73,12 @@ fn validate_delete_in_scope(scope_variables: &HashSet<VariableRef<'_>>, statemen
     }))
 }
 
+impl Simplifiable for TypeQLDelete {
+    fn simplify(&mut self) {
+        self.match_clause.simplify();
+    }
+}
+
 impl fmt::Display for TypeQLDelete {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{}", self.match_clause)?;
@@ -17,7 +17,7 @@ use crate::{
         validatable::Validatable,
         Result,
     },
-    pattern::{Label, VariablesRetrieved},
+    pattern::{Label, Simplifiable, VariablesRetrieved},
     query::{modifier::Modifiers, MatchClause, TypeQLGetAggregate},
     variable::{variable::VariableRef, Variable},
     write_joined,
@@ -56,7 +56,7 @@ impl Validatable for TypeQLFetch {
         let match_variables = self.match_clause.retrieved_variables().collect();
         collect_err([
             self.match_clause.validate(),
-            self.modifiers.sorting.as_ref().map_or(Ok(()), |s| s.validate(&match_variables)),
+            self.modifiers.validate(&match_variables),
             self.validate_names_are_unique(),
         ])
     }
@@ -74,20 +74,23 @@ impl VariablesRetrieved for TypeQLFetch {
 pub enum Projection {
     Variable(ProjectionKeyVar),
     Attribute(ProjectionKeyVar, Vec<ProjectionAttribute>),
+    Field(ProjectionKeyVar, Vec<String>),
     Subquery(ProjectionKeyLabel, ProjectionSubquery),
 }
 
 impl Projection {
     pub fn key_variable(&self) -> Option<VariableRef<'_>> {
         match self {
-            Projection::Variable(key) | Projection::Attribute(key, _) => Some(key.variable.as_ref()),
+            Projection::Variable(key) | Projection::Attribute(key, _) | Projection::Field(key, _) => {
+                Some(key.variable.as_ref())
+            }
             Projection::Subquery(_, _) => None,
         }
     }
 
     pub fn value_variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
         match self {
-            Projection::Variable(_) | Projection::Attribute(_, _) => Box::new(iter::empty()),
+            Projection::Variable(_) | Projection::Attribute(_, _) | Projection::Field(_, _) => Box::new(iter::empty()),
             Projection::Subquery(_, subquery) => subquery.variables(),
         }
     }
@@ -141,7 +144,7 @@ pub struct ProjectionKeyLabel {
 
 impl ProjectionKeyLabel {
     pub fn map_subquery_get_aggregate(self, subquery: TypeQLGetAggregate) -> Projection {
-        Projection::Subquery(self, ProjectionSubquery::GetAggregate(subquery))
+        Projection::Subquery(self, ProjectionSubquery::GetAggregate(Box::new(subquery)))
     }
 
     pub fn map_subquery_fetch(self, subquery: TypeQLFetch) -> Projection {
@@ -197,7 +200,7 @@ impl<T: Into<Label>, U: Into<ProjectionKeyLabel>> From<(T, U)> for ProjectionAtt
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum ProjectionSubquery {
-    GetAggregate(TypeQLGetAggregate),
+    GetAggregate(Box<TypeQLGetAggregate>),
     Fetch(Box<TypeQLFetch>),
 }
 
@@ -213,6 +216,7 @@ impl ProjectionSubquery {
 pub trait ProjectionBuilder {
     fn map_attribute(self, attribute: impl Into<ProjectionAttribute>) -> Projection;
     fn map_attributes(self, attribute: Vec<ProjectionAttribute>) -> Projection;
+    fn map_field(self, field: impl Into<String>) -> Projection;
 }
 
 impl<T: Into<ProjectionKeyVar>> ProjectionBuilder for T {
@@ -223,6 +227,23 @@ impl<T: Into<ProjectionKeyVar>> ProjectionBuilder for T {
     fn map_attributes(self, attributes: Vec<ProjectionAttribute>) -> Projection {
         Projection::Attribute(self.into(), attributes)
     }
+
+    fn map_field(self, field: impl Into<String>) -> Projection {
+        Projection::Field(self.into(), vec![field.into()])
+    }
+}
+
+impl Simplifiable for TypeQLFetch {
+    fn simplify(&mut self) {
+        self.match_clause.simplify();
+        for projection in &mut self.projections {
+            match projection {
+                Projection::Subquery(_, ProjectionSubquery::Fetch(subquery)) => subquery.simplify(),
+                Projection::Subquery(_, ProjectionSubquery::GetAggregate(subquery)) => subquery.simplify(),
+                Projection::Variable(_) | Projection::Attribute(_, _) | Projection::Field(_, _) => (),
+            }
+        }
+    }
 }
 
 impl fmt::Display for TypeQLFetch {
@@ -249,6 +270,13 @@ impl fmt::Display for Projection {
                 write_joined!(f, ", ", attrs)?;
                 write!(f, ";")
             }
+            Projection::Field(key, fields) => {
+                write!(f, "{}.{}", key.variable, fields.join("."))?;
+                if let Some(label) = &key.label {
+                    write!(f, " {} {}", token::Projection::As, label)?;
+                }
+                write!(f, ";")
+            }
             Projection::Subquery(label, subquery) => {
                 write!(f, "{}: {{\n{}\n}};", label, indent(subquery.to_string().as_ref()))
             }
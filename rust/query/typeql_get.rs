@@ -15,12 +15,12 @@ use crate::{
         validatable::Validatable,
         Result,
     },
-    pattern::{Conjunction, VariablesRetrieved},
+    pattern::{Conjunction, Constant, Simplifiable, VariablesRetrieved},
     query::{
-        modifier::{Modifiers, Sorting},
+        modifier::{Cursor, Modifiers, Sorting},
         AggregateQueryBuilder, MatchClause, TypeQLGetGroup,
     },
-    variable::{variable::VariableRef, Variable},
+    variable::{variable::VariableRef, ConceptVariable, ValueVariable, Variable},
     write_joined,
 };
 
@@ -28,20 +28,39 @@ use crate::{
 pub struct TypeQLGet {
     pub match_clause: MatchClause,
     pub filter: Filter,
+    pub selection: Option<Selection>,
     pub modifiers: Modifiers,
 }
 
-impl AggregateQueryBuilder for TypeQLGet {}
+impl AggregateQueryBuilder for TypeQLGet {
+    fn is_sorted(&self) -> bool {
+        self.modifiers.sorting.is_some()
+    }
+}
 
 impl TypeQLGet {
     pub fn new(match_clause: MatchClause) -> Self {
-        TypeQLGet { match_clause, filter: Filter::default(), modifiers: Modifiers::default() }
+        TypeQLGet { match_clause, filter: Filter::default(), selection: None, modifiers: Modifiers::default() }
+    }
+
+    pub fn distinct(self) -> Self {
+        TypeQLGet { modifiers: self.modifiers.distinct(), ..self }
     }
 
     pub fn sort(self, sorting: impl Into<Sorting>) -> Self {
         TypeQLGet { modifiers: self.modifiers.sort(sorting), ..self }
     }
 
+    pub fn after(
+        self,
+        vars: impl IntoIterator<Item = impl Into<Variable>>,
+        values: impl IntoIterator<Item = impl Into<Constant>>,
+    ) -> Self {
+        let cursor =
+            Cursor::new(vars.into_iter().map(Into::into).collect(), values.into_iter().map(Into::into).collect());
+        TypeQLGet { modifiers: self.modifiers.after(cursor), ..self }
+    }
+
     pub fn limit(self, limit: usize) -> Self {
         TypeQLGet { modifiers: self.modifiers.limit(limit), ..self }
     }
@@ -51,19 +70,23 @@ impl TypeQLGet {
     }
 
     pub fn group(self, var: impl Into<Variable>) -> TypeQLGetGroup {
-        TypeQLGetGroup { query: self, group_var: var.into() }
+        self.group_by([var])
+    }
+
+    pub fn group_by(self, vars: impl IntoIterator<Item = impl Into<Variable>>) -> TypeQLGetGroup {
+        TypeQLGetGroup { query: self, group_vars: vars.into_iter().map(Into::into).collect() }
     }
 }
 
 impl Validatable for TypeQLGet {
     fn validate(&self) -> Result {
         let match_variables = self.match_clause.retrieved_variables().collect();
-        let filter_vars = HashSet::from_iter(self.filter.vars.iter().map(Variable::as_ref));
-        let retrieved_variables = if self.filter.vars.is_empty() { &match_variables } else { &filter_vars };
+        let retrieved_variables = self.retrieved_variables().collect();
         collect_err([
             self.match_clause.validate(),
             validate_filters_are_in_scope(&match_variables, &self.filter),
-            self.modifiers.sorting.as_ref().map(|s| s.validate(retrieved_variables)).unwrap_or(Ok(())),
+            self.selection.as_ref().map(|s| validate_selection_is_in_scope(&match_variables, s)).unwrap_or(Ok(())),
+            self.modifiers.validate(&retrieved_variables),
             validate_variable_names_are_unique(&self.match_clause.conjunction),
         ])
     }
@@ -71,7 +94,9 @@ impl Validatable for TypeQLGet {
 
 impl VariablesRetrieved for TypeQLGet {
     fn retrieved_variables(&self) -> Box<dyn Iterator<Item = VariableRef<'_>> + '_> {
-        if !self.filter.vars.is_empty() {
+        if let Some(selection) = &self.selection {
+            Box::new(selection.vars.iter().map(SelectVariable::output))
+        } else if !self.filter.vars.is_empty() {
             Box::new(self.filter.vars.iter().map(Variable::as_ref))
         } else {
             self.match_clause.retrieved_variables()
@@ -79,7 +104,7 @@ impl VariablesRetrieved for TypeQLGet {
     }
 }
 
-fn validate_filters_are_in_scope(match_variables: &HashSet<VariableRef<'_>>, filter: &Filter) -> Result {
+pub(crate) fn validate_filters_are_in_scope(match_variables: &HashSet<VariableRef<'_>>, filter: &Filter) -> Result {
     let mut seen = HashSet::new();
     collect_err(filter.vars.iter().map(|variable| {
         if !variable.is_named() {
@@ -95,6 +120,26 @@ fn validate_filters_are_in_scope(match_variables: &HashSet<VariableRef<'_>>, fil
     }))
 }
 
+pub(crate) fn validate_selection_is_in_scope(
+    match_variables: &HashSet<VariableRef<'_>>,
+    selection: &Selection,
+) -> Result {
+    if selection.vars.is_empty() {
+        Err(TypeQLError::MissingSelectVariable)?
+    }
+    let mut seen = HashSet::new();
+    collect_err(selection.vars.iter().map(|select_var| {
+        if !select_var.variable.is_named() {
+            Err(TypeQLError::VariableNotNamed)?
+        } else if !match_variables.contains(&select_var.variable.as_ref()) {
+            Err(TypeQLError::SelectVarNotBound { variable: select_var.variable.clone() })?
+        } else if !seen.insert(select_var.output()) {
+            Err(TypeQLError::SelectVarRepeating { variable: select_var.output().to_owned() })?
+        }
+        select_var.alias.as_ref().map(Validatable::validate).unwrap_or(Ok(()))
+    }))
+}
+
 fn validate_variable_names_are_unique(conjunction: &Conjunction) -> Result {
     let all_refs = conjunction.variables_recursive();
     let (concept_refs, value_refs) = all_refs.partition::<HashSet<_>, _>(VariableRef::is_concept);
@@ -108,10 +153,19 @@ fn validate_variable_names_are_unique(conjunction: &Conjunction) -> Result {
     Ok(())
 }
 
+impl Simplifiable for TypeQLGet {
+    fn simplify(&mut self) {
+        self.match_clause.simplify();
+    }
+}
+
 impl fmt::Display for TypeQLGet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.match_clause)?;
-        write!(f, "\n{}", self.filter)?;
+        match &self.selection {
+            Some(selection) => write!(f, "\n{selection}")?,
+            None => write!(f, "\n{}", self.filter)?,
+        }
         if !self.modifiers.is_empty() {
             write!(f, "\n{}", self.modifiers)
         } else {
@@ -135,3 +189,77 @@ impl fmt::Display for Filter {
         write!(f, ";")
     }
 }
+
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct Selection {
+    pub vars: Vec<SelectVariable>,
+}
+
+impl Selection {
+    pub fn new(vars: Vec<SelectVariable>) -> Self {
+        Selection { vars }
+    }
+}
+
+impl<const N: usize, T: Into<SelectVariable>> From<[T; N]> for Selection {
+    fn from(vars: [T; N]) -> Self {
+        Self::new(vars.map(Into::into).to_vec())
+    }
+}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", token::Clause::Select)?;
+        write_joined!(f, ", ", self.vars)?;
+        write!(f, ";")
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SelectVariable {
+    pub variable: Variable,
+    pub alias: Option<ValueVariable>,
+}
+
+impl SelectVariable {
+    pub fn output(&self) -> VariableRef<'_> {
+        match &self.alias {
+            Some(alias) => VariableRef::Value(alias),
+            None => self.variable.as_ref(),
+        }
+    }
+}
+
+impl From<Variable> for SelectVariable {
+    fn from(variable: Variable) -> Self {
+        SelectVariable { variable, alias: None }
+    }
+}
+
+impl From<ConceptVariable> for SelectVariable {
+    fn from(variable: ConceptVariable) -> Self {
+        SelectVariable { variable: variable.into(), alias: None }
+    }
+}
+
+impl From<ValueVariable> for SelectVariable {
+    fn from(variable: ValueVariable) -> Self {
+        SelectVariable { variable: variable.into(), alias: None }
+    }
+}
+
+impl From<(ValueVariable, ValueVariable)> for SelectVariable {
+    fn from((variable, alias): (ValueVariable, ValueVariable)) -> Self {
+        SelectVariable { variable: variable.into(), alias: Some(alias) }
+    }
+}
+
+impl fmt::Display for SelectVariable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.variable)?;
+        if let Some(alias) = &self.alias {
+            write!(f, " {} {alias}", token::Projection::As)?;
+        }
+        Ok(())
+    }
+}
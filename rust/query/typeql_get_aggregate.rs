@@ -13,7 +13,7 @@ use crate::{
         validatable::Validatable,
         Result,
     },
-    pattern::VariablesRetrieved,
+    pattern::{Simplifiable, VariablesRetrieved},
     query::{TypeQLGet, TypeQLGetGroup},
     variable::{variable::VariableRef, Variable},
 };
@@ -24,20 +24,61 @@ where
     T: AggregateQueryBuilder,
 {
     pub query: T,
-    pub method: token::Aggregate,
-    pub var: Option<Variable>,
+    pub aggregations: Vec<Aggregation>,
 }
 
 pub type TypeQLGetAggregate = AggregateQuery<TypeQLGet>;
 pub type TypeQLGetGroupAggregate = AggregateQuery<TypeQLGetGroup>;
 
-impl<T: AggregateQueryBuilder> AggregateQuery<T> {
-    fn new_count(query: T) -> Self {
-        Self { query, method: token::Aggregate::Count, var: None }
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Aggregation {
+    pub method: token::Aggregate,
+    pub var: Option<Variable>,
+    pub percentile: Option<i64>,
+}
+
+impl Aggregation {
+    pub fn count() -> Self {
+        Self { method: token::Aggregate::Count, var: None, percentile: None }
+    }
+
+    pub fn new(method: token::Aggregate, var: impl Into<Variable>) -> Self {
+        Self { method, var: Some(var.into()), percentile: None }
+    }
+
+    pub fn percentile(percentile: i64, var: impl Into<Variable>) -> Self {
+        Self { method: token::Aggregate::Percentile, var: Some(var.into()), percentile: Some(percentile) }
+    }
+
+    fn validate(&self, scope_variables: &HashSet<VariableRef<'_>>, is_sorted: bool) -> Result {
+        collect_err(
+            [
+                validate_method_variable_compatible(&self.method, &self.var),
+                validate_method_sorting_compatible(&self.method, is_sorted),
+                validate_percentile(&self.method, self.percentile),
+            ]
+            .into_iter()
+            .chain(self.var.iter().map(|v| validate_variable_in_scope(v, scope_variables))),
+        )
     }
+}
+
+impl fmt::Display for Aggregation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.method)?;
+        if let Some(percentile) = self.percentile {
+            write!(f, " {percentile}")?;
+        }
+        if let Some(var) = &self.var {
+            write!(f, " {var}")?;
+        }
+        f.write_str(";")
+    }
+}
 
-    fn new(query: T, method: token::Aggregate, var: impl Into<Variable>) -> Self {
-        Self { query, method, var: Some(var.into()) }
+impl<T: AggregateQueryBuilder> AggregateQuery<T> {
+    fn new(query: T, aggregations: Vec<Aggregation>) -> Self {
+        Self { query, aggregations }
     }
 }
 
@@ -45,20 +86,50 @@ impl<T: AggregateQueryBuilder> Validatable for AggregateQuery<T> {
     fn validate(&self) -> Result {
         let retrieved_variables = self.query.retrieved_variables().collect();
         collect_err(
-            [self.query.validate(), validate_method_variable_compatible(&self.method, &self.var)]
-                .into_iter()
-                .chain(self.var.iter().map(|v| validate_variable_in_scope(v, &retrieved_variables))),
+            [self.query.validate(), validate_non_empty(&self.aggregations)].into_iter().chain(
+                self.aggregations
+                    .iter()
+                    .map(|aggregation| aggregation.validate(&retrieved_variables, self.query.is_sorted())),
+            ),
         )
     }
 }
 
+fn validate_non_empty(aggregations: &[Aggregation]) -> Result {
+    if aggregations.is_empty() {
+        Err(TypeQLError::MissingAggregation)?
+    }
+    Ok(())
+}
+
 fn validate_method_variable_compatible(method: &token::Aggregate, var: &Option<Variable>) -> Result {
-    if *method == token::Aggregate::Count && var.is_some() {
-        Err(TypeQLError::InvalidCountVariableArgument)?
+    match (method, var) {
+        (token::Aggregate::Count, Some(_)) => Err(TypeQLError::InvalidCountVariableArgument)?,
+        (token::Aggregate::Count, None) | (_, Some(_)) => Ok(()),
+        (method, None) => Err(TypeQLError::MissingAggregateVariable { method: *method })?,
+    }
+}
+
+fn validate_method_sorting_compatible(method: &token::Aggregate, is_sorted: bool) -> Result {
+    if matches!(method, token::Aggregate::First | token::Aggregate::Last) && !is_sorted {
+        Err(TypeQLError::AggregateRequiresSorting { method: *method })?
     }
     Ok(())
 }
 
+fn validate_percentile(method: &token::Aggregate, percentile: Option<i64>) -> Result {
+    match (method, percentile) {
+        (token::Aggregate::Percentile, Some(percentile)) if !(0..=100).contains(&percentile) => {
+            Err(TypeQLError::InvalidPercentile { percentile })?
+        }
+        (token::Aggregate::Percentile, Some(_)) => Ok(()),
+        (token::Aggregate::Percentile, None) | (_, Some(_)) => {
+            Err(TypeQLError::InvalidAggregatePercentile { method: *method })?
+        }
+        (_, None) => Ok(()),
+    }
+}
+
 fn validate_variable_in_scope(var: &Variable, scope_variables: &HashSet<VariableRef<'_>>) -> Result {
     if !scope_variables.contains(&var.as_ref()) {
         Err(TypeQLError::AggregateVarNotBound { variable: var.clone() })?;
@@ -66,35 +137,41 @@ fn validate_variable_in_scope(var: &Variable, scope_variables: &HashSet<Variable
     Ok(())
 }
 
+impl<T: AggregateQueryBuilder + Simplifiable> Simplifiable for AggregateQuery<T> {
+    fn simplify(&mut self) {
+        self.query.simplify();
+    }
+}
+
 impl fmt::Display for TypeQLGetAggregate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}\n{}", self.query, self.method)?;
-        if let Some(var) = &self.var {
-            write!(f, " {var}")?;
-        }
-        f.write_str(";")
+        write!(f, "{}", self.query)?;
+        self.aggregations.iter().try_for_each(|aggregation| write!(f, "\n{aggregation}"))
     }
 }
 
 impl fmt::Display for TypeQLGetGroupAggregate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} {}", self.query, self.method)?;
-        if let Some(var) = &self.var {
-            write!(f, " {var}")?;
-        }
-        f.write_str(";")
+        write!(f, "{}", self.query)?;
+        self.aggregations.iter().try_for_each(|aggregation| write!(f, " {aggregation}"))
     }
 }
 
 pub trait AggregateQueryBuilder:
     Sized + Clone + fmt::Display + fmt::Debug + Eq + PartialEq + VariablesRetrieved + Validatable
 {
+    fn is_sorted(&self) -> bool;
+
+    fn aggregations(self, aggregations: impl IntoIterator<Item = Aggregation>) -> AggregateQuery<Self> {
+        AggregateQuery::<Self>::new(self, aggregations.into_iter().collect())
+    }
+
     fn count(self) -> AggregateQuery<Self> {
-        AggregateQuery::<Self>::new_count(self)
+        self.aggregations([Aggregation::count()])
     }
 
     fn aggregate(self, method: token::Aggregate, var: impl Into<Variable>) -> AggregateQuery<Self> {
-        AggregateQuery::<Self>::new(self, method, var)
+        self.aggregations([Aggregation::new(method, var)])
     }
 
     fn max(self, var: impl Into<Variable>) -> AggregateQuery<Self> {
@@ -120,4 +197,24 @@ pub trait AggregateQueryBuilder:
     fn sum(self, var: impl Into<Variable>) -> AggregateQuery<Self> {
         self.aggregate(token::Aggregate::Sum, var.into())
     }
+
+    fn count_distinct(self, var: impl Into<Variable>) -> AggregateQuery<Self> {
+        self.aggregate(token::Aggregate::CountDistinct, var.into())
+    }
+
+    fn list(self, var: impl Into<Variable>) -> AggregateQuery<Self> {
+        self.aggregate(token::Aggregate::List, var.into())
+    }
+
+    fn first(self, var: impl Into<Variable>) -> AggregateQuery<Self> {
+        self.aggregate(token::Aggregate::First, var.into())
+    }
+
+    fn last(self, var: impl Into<Variable>) -> AggregateQuery<Self> {
+        self.aggregate(token::Aggregate::Last, var.into())
+    }
+
+    fn percentile(self, percentile: i64, var: impl Into<Variable>) -> AggregateQuery<Self> {
+        self.aggregations([Aggregation::percentile(percentile, var)])
+    }
 }
@@ -4,7 +4,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/.
  */
 
-use std::{collections::HashSet, fmt, iter};
+use std::{collections::HashSet, fmt};
 
 use crate::{
     common::{
@@ -13,26 +13,32 @@ use crate::{
         validatable::Validatable,
         Result,
     },
-    pattern::VariablesRetrieved,
+    pattern::{Simplifiable, VariablesRetrieved},
     query::{AggregateQueryBuilder, TypeQLGet},
     variable::{variable::VariableRef, Variable},
+    write_joined,
 };
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TypeQLGetGroup {
     pub query: TypeQLGet,
-    pub group_var: Variable,
+    pub group_vars: Vec<Variable>,
 }
 
-impl AggregateQueryBuilder for TypeQLGetGroup {}
+impl AggregateQueryBuilder for TypeQLGetGroup {
+    fn is_sorted(&self) -> bool {
+        self.query.is_sorted()
+    }
+}
 
 impl Validatable for TypeQLGetGroup {
     fn validate(&self) -> Result {
         let retrieved_variables = self.query.retrieved_variables().collect();
         collect_err(
-            [self.query.validate(), self.group_var.validate()]
+            [self.query.validate(), validate_non_empty(&self.group_vars)]
                 .into_iter()
-                .chain(iter::once(&self.group_var).map(|v| validate_variable_in_scope(v, &retrieved_variables))),
+                .chain(self.group_vars.iter().map(Validatable::validate))
+                .chain(self.group_vars.iter().map(|v| validate_variable_in_scope(v, &retrieved_variables))),
         )
     }
 }
@@ -43,6 +49,13 @@ impl VariablesRetrieved for TypeQLGetGroup {
     }
 }
 
+fn validate_non_empty(group_vars: &[Variable]) -> Result {
+    if group_vars.is_empty() {
+        Err(TypeQLError::MissingGroupVariable)?
+    }
+    Ok(())
+}
+
 fn validate_variable_in_scope(var: &Variable, scope_variables: &HashSet<VariableRef<'_>>) -> Result {
     if !scope_variables.contains(&var.as_ref()) {
         Err(TypeQLError::GroupVarNotBound { variable: var.clone() })?;
@@ -50,8 +63,16 @@ fn validate_variable_in_scope(var: &Variable, scope_variables: &HashSet<Variable
     Ok(())
 }
 
+impl Simplifiable for TypeQLGetGroup {
+    fn simplify(&mut self) {
+        self.query.simplify();
+    }
+}
+
 impl fmt::Display for TypeQLGetGroup {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}\n{} {};", self.query, token::Clause::Group, self.group_var)
+        write!(f, "{}\n{} ", self.query, token::Clause::Group)?;
+        write_joined!(f, ", ", self.group_vars)?;
+        f.write_str(";")
     }
 }
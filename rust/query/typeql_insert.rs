@@ -13,7 +13,7 @@ use crate::{
         validatable::Validatable,
         Result,
     },
-    pattern::{ThingStatement, VariablesRetrieved},
+    pattern::{Simplifiable, ThingStatement, VariablesRetrieved},
     query::{modifier::Modifiers, writable::validate_non_empty, MatchClause, Sorting},
     variable::variable::VariableRef,
     write_joined,
@@ -63,7 +63,10 @@ impl Validatable for TypeQLInsert {
                     .map(|m| {
                         m.validate()?;
                         let match_variables = m.retrieved_variables().collect();
-                        validate_insert_in_scope_of_match(&match_variables, &self.statements)
+                        collect_err([
+                            validate_insert_in_scope_of_match(&match_variables, &self.statements),
+                            self.modifiers.validate(&match_variables),
+                        ])
                     })
                     .unwrap_or_else(|| Ok(())),
             ]
@@ -86,6 +89,14 @@ fn validate_insert_in_scope_of_match(
     }
 }
 
+impl Simplifiable for TypeQLInsert {
+    fn simplify(&mut self) {
+        if let Some(match_clause) = &mut self.match_clause {
+            match_clause.simplify();
+        }
+    }
+}
+
 impl fmt::Display for TypeQLInsert {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(match_clause) = &self.match_clause {
@@ -0,0 +1,224 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{collections::HashSet, fmt, iter};
+
+use crate::{
+    common::{
+        error::{collect_err, TypeQLError},
+        token,
+        validatable::Validatable,
+        Result,
+    },
+    pattern::{Simplifiable, ThingStatement, VariablesRetrieved},
+    query::{
+        typeql_delete::validate_delete_in_scope,
+        typeql_get::{validate_filters_are_in_scope, validate_selection_is_in_scope},
+        writable::validate_non_empty,
+        Distinct, Filter, Limit, MatchClause, Offset, Projection, SelectVariable, Selection, Sorting, Writable,
+    },
+    variable::{variable::VariableRef, Variable},
+    write_joined,
+};
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct TypeQLPipeline {
+    pub stages: Vec<Stage>,
+}
+
+impl TypeQLPipeline {
+    pub fn new(stages: Vec<Stage>) -> Self {
+        TypeQLPipeline { stages }
+    }
+
+    pub fn match_(self, match_clause: MatchClause) -> Self {
+        self.then(Stage::Match(match_clause))
+    }
+
+    pub fn insert(self, writable: impl Writable) -> Self {
+        self.then(Stage::Insert(writable.statements()))
+    }
+
+    pub fn delete(self, writable: impl Writable) -> Self {
+        self.then(Stage::Delete(writable.statements()))
+    }
+
+    pub fn put(self, writable: impl Writable) -> Self {
+        self.then(Stage::Put(writable.statements()))
+    }
+
+    pub fn get_vars(self, vars: Vec<Variable>) -> Self {
+        self.then(Stage::Get(Filter { vars }))
+    }
+
+    pub fn get_fixed<const N: usize, T: Into<Variable>>(self, vars: [T; N]) -> Self {
+        self.get_vars(vars.into_iter().map(|var| var.into()).collect())
+    }
+
+    pub fn select(self, selection: impl Into<Selection>) -> Self {
+        self.then(Stage::Select(selection.into()))
+    }
+
+    pub fn distinct(self) -> Self {
+        self.then(Stage::Distinct(Distinct))
+    }
+
+    pub fn sort(self, sorting: impl Into<Sorting>) -> Self {
+        self.then(Stage::Sort(sorting.into()))
+    }
+
+    pub fn offset(self, offset: usize) -> Self {
+        self.then(Stage::Offset(Offset { offset }))
+    }
+
+    pub fn limit(self, limit: usize) -> Self {
+        self.then(Stage::Limit(Limit { limit }))
+    }
+
+    pub fn fetch(self, projections: Vec<Projection>) -> Self {
+        self.then(Stage::Fetch(projections))
+    }
+
+    pub fn fetch_fixed<const N: usize>(self, projections: [Projection; N]) -> Self {
+        self.fetch(projections.into())
+    }
+
+    fn then(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    fn validate_non_empty(&self) -> Result {
+        if self.stages.is_empty() {
+            Err(TypeQLError::MissingPipelineStages)?
+        }
+        Ok(())
+    }
+
+    fn validate_fetch_is_last(&self) -> Result {
+        let after_fetch = self.stages.iter().skip_while(|stage| !matches!(stage, Stage::Fetch(_))).skip(1);
+        collect_err(after_fetch.map(|stage| Err(TypeQLError::PipelineStageAfterFetch { stage: stage.to_string() })?))
+    }
+
+    fn validate_stages_are_in_scope(&self) -> Result {
+        let mut available = HashSet::new();
+        collect_err(self.stages.iter().map(|stage| stage.validate_in_scope(&mut available)).collect::<Vec<_>>())
+    }
+}
+
+impl Validatable for TypeQLPipeline {
+    fn validate(&self) -> Result {
+        collect_err([self.validate_non_empty(), self.validate_fetch_is_last(), self.validate_stages_are_in_scope()])
+    }
+}
+
+impl From<MatchClause> for TypeQLPipeline {
+    fn from(match_clause: MatchClause) -> Self {
+        TypeQLPipeline::new(vec![Stage::Match(match_clause)])
+    }
+}
+
+impl Simplifiable for TypeQLPipeline {
+    fn simplify(&mut self) {
+        self.stages.iter_mut().for_each(|stage| {
+            if let Stage::Match(match_clause) = stage {
+                match_clause.simplify()
+            }
+        });
+    }
+}
+
+impl fmt::Display for TypeQLPipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_joined!(f, "\n", self.stages)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Stage {
+    Match(MatchClause),
+    Insert(Vec<ThingStatement>),
+    Delete(Vec<ThingStatement>),
+    Put(Vec<ThingStatement>),
+    Get(Filter),
+    Select(Selection),
+    Distinct(Distinct),
+    Sort(Sorting),
+    Offset(Offset),
+    Limit(Limit),
+    Fetch(Vec<Projection>),
+}
+
+impl Stage {
+    fn validate_in_scope<'a>(&'a self, available: &mut HashSet<VariableRef<'a>>) -> Result {
+        match self {
+            Stage::Match(match_clause) => {
+                available.extend(match_clause.retrieved_variables());
+                match_clause.validate()
+            }
+            Stage::Insert(statements) | Stage::Put(statements) => {
+                available.extend(statements.iter().flat_map(ThingStatement::variables).filter(VariableRef::is_name));
+                collect_err(
+                    iter::once(validate_non_empty(statements)).chain(statements.iter().map(Validatable::validate)),
+                )
+            }
+            Stage::Delete(statements) => collect_err(
+                [validate_non_empty(statements), validate_delete_in_scope(available, statements)]
+                    .into_iter()
+                    .chain(statements.iter().map(Validatable::validate)),
+            ),
+            Stage::Get(filter) => {
+                let result = validate_filters_are_in_scope(available, filter);
+                if !filter.vars.is_empty() {
+                    *available = filter.vars.iter().map(Variable::as_ref).collect();
+                }
+                result
+            }
+            Stage::Select(selection) => {
+                let result = validate_selection_is_in_scope(available, selection);
+                *available = selection.vars.iter().map(SelectVariable::output).collect();
+                result
+            }
+            Stage::Sort(sorting) => sorting.validate(available),
+            Stage::Distinct(_) | Stage::Offset(_) | Stage::Limit(_) => Ok(()),
+            Stage::Fetch(projections) => {
+                collect_err(projections.iter().filter_map(Projection::key_variable).map(|variable| {
+                    match available.contains(&variable) {
+                        true => Ok(()),
+                        false => Err(TypeQLError::FetchVarNotBound { variable: variable.to_owned() })?,
+                    }
+                }))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Match(match_clause) => write!(f, "{match_clause}"),
+            Stage::Insert(statements) => write_statements(f, token::Clause::Insert, statements),
+            Stage::Delete(statements) => write_statements(f, token::Clause::Delete, statements),
+            Stage::Put(statements) => write_statements(f, token::Clause::Put, statements),
+            Stage::Get(filter) => write!(f, "{filter}"),
+            Stage::Select(selection) => write!(f, "{selection}"),
+            Stage::Distinct(distinct) => write!(f, "{distinct};"),
+            Stage::Sort(sorting) => write!(f, "{sorting};"),
+            Stage::Offset(offset) => write!(f, "{offset};"),
+            Stage::Limit(limit) => write!(f, "{limit};"),
+            Stage::Fetch(projections) => {
+                writeln!(f, "{}", token::Clause::Fetch)?;
+                write_joined!(f, "\n", projections)
+            }
+        }
+    }
+}
+
+fn write_statements(f: &mut fmt::Formatter<'_>, clause: token::Clause, statements: &[ThingStatement]) -> fmt::Result {
+    writeln!(f, "{clause}")?;
+    write_joined!(f, ";\n", statements)?;
+    f.write_str(";")
+}
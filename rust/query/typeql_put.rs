@@ -0,0 +1,82 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/.
+ */
+
+use std::{collections::HashSet, fmt};
+
+use crate::{
+    common::{
+        error::{collect_err, TypeQLError},
+        token,
+        validatable::Validatable,
+        Result,
+    },
+    pattern::{Simplifiable, ThingStatement, VariablesRetrieved},
+    query::{writable::validate_non_empty, MatchClause},
+    variable::variable::VariableRef,
+    write_joined,
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TypeQLPut {
+    pub match_clause: Option<MatchClause>,
+    pub statements: Vec<ThingStatement>,
+}
+
+impl TypeQLPut {
+    pub fn new(statements: Vec<ThingStatement>) -> Self {
+        TypeQLPut { match_clause: None, statements }
+    }
+}
+
+impl Validatable for TypeQLPut {
+    fn validate(&self) -> Result {
+        collect_err(
+            [
+                validate_non_empty(&self.statements),
+                self.match_clause
+                    .as_ref()
+                    .map(|m| {
+                        m.validate()?;
+                        let match_variables = m.retrieved_variables().collect();
+                        validate_put_in_scope_of_match(&match_variables, &self.statements)
+                    })
+                    .unwrap_or_else(|| Ok(())),
+            ]
+            .into_iter()
+            .chain(self.statements.iter().map(Validatable::validate)),
+        )
+    }
+}
+
+fn validate_put_in_scope_of_match(match_variables: &HashSet<VariableRef<'_>>, statements: &[ThingStatement]) -> Result {
+    if statements.iter().flat_map(|s| s.variables()).any(|v| match_variables.contains(&v)) {
+        Ok(())
+    } else {
+        let stmts_str = statements.iter().map(ThingStatement::to_string).collect::<Vec<String>>().join(", ");
+        let bounds_str = match_variables.iter().map(VariableRef::to_string).collect::<Vec<String>>().join(", ");
+        Err(TypeQLError::PutClauseNotBound { put_statements: stmts_str, bounds: bounds_str })?
+    }
+}
+
+impl Simplifiable for TypeQLPut {
+    fn simplify(&mut self) {
+        if let Some(match_clause) = &mut self.match_clause {
+            match_clause.simplify();
+        }
+    }
+}
+
+impl fmt::Display for TypeQLPut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(match_clause) = &self.match_clause {
+            writeln!(f, "{match_clause}")?;
+        }
+
+        writeln!(f, "{}", token::Clause::Put)?;
+        write_joined!(f, ";\n", self.statements)?;
+        f.write_str(";")
+    }
+}
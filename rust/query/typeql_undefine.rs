@@ -13,7 +13,7 @@ use crate::{
         validatable::Validatable,
         Result,
     },
-    pattern::{Definable, RuleLabel, TypeStatement},
+    pattern::{Definable, FunctionLabel, RuleLabel, Struct, TypeStatement},
     write_joined,
 };
 
@@ -21,6 +21,8 @@ use crate::{
 pub struct TypeQLUndefine {
     statements: Vec<TypeStatement>,
     rules: Vec<RuleLabel>,
+    functions: Vec<FunctionLabel>,
+    structs: Vec<Struct>,
 }
 
 impl TypeQLUndefine {
@@ -31,6 +33,14 @@ impl TypeQLUndefine {
             Definable::RuleDefinition(rule) => {
                 panic!("{}", TypeQLError::InvalidUndefineQueryRule { rule_label: rule.label })
             }
+            Definable::FunctionDeclaration(function) => undefine.add_function(function),
+            Definable::FunctionDefinition(function) => {
+                panic!("{}", TypeQLError::InvalidUndefineQueryFunction { function_label: function.label })
+            }
+            Definable::Struct(struct_) if !struct_.fields.is_empty() => {
+                panic!("{}", TypeQLError::InvalidUndefineQueryStruct { struct_name: struct_.name })
+            }
+            Definable::Struct(struct_) => undefine.add_struct(struct_),
         })
     }
 
@@ -44,8 +54,18 @@ impl TypeQLUndefine {
         self
     }
 
+    fn add_function(mut self, function: FunctionLabel) -> Self {
+        self.functions.push(function);
+        self
+    }
+
+    fn add_struct(mut self, struct_: Struct) -> Self {
+        self.structs.push(struct_);
+        self
+    }
+
     fn validate_non_empty(&self) -> Result {
-        if self.statements.is_empty() && self.rules.is_empty() {
+        if self.statements.is_empty() && self.rules.is_empty() && self.functions.is_empty() && self.structs.is_empty() {
             Err(TypeQLError::MissingDefinables)?
         }
         Ok(())
@@ -58,7 +78,8 @@ impl Validatable for TypeQLUndefine {
             &mut iter::once(self.validate_non_empty())
                 .chain(self.statements.iter().map(Validatable::validate))
                 .chain(self.statements.iter().map(TypeStatement::validate_definable))
-                .chain(self.rules.iter().map(Validatable::validate)),
+                .chain(self.rules.iter().map(Validatable::validate))
+                .chain(self.functions.iter().map(Validatable::validate)),
         )
     }
 }
@@ -66,7 +87,7 @@ impl Validatable for TypeQLUndefine {
 impl fmt::Display for TypeQLUndefine {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{}", token::Clause::Undefine)?;
-        write_joined!(f, ";\n", self.statements, self.rules)?;
+        write_joined!(f, ";\n", self.structs, self.statements, self.rules, self.functions)?;
         f.write_str(";")
     }
 }
@@ -8,7 +8,7 @@ use std::fmt;
 
 use crate::{
     common::{error::collect_err, token, validatable::Validatable, Result},
-    pattern::{ThingStatement, VariablesRetrieved},
+    pattern::{Simplifiable, ThingStatement, VariablesRetrieved},
     query::{modifier::Modifiers, writable::validate_non_empty, Sorting, TypeQLDelete},
     write_joined,
 };
@@ -41,7 +41,7 @@ impl Validatable for TypeQLUpdate {
             [
                 validate_non_empty(&self.insert_statements),
                 self.query_delete.validate(),
-                self.modifiers.sorting.as_ref().map(|s| s.validate(&match_variables)).unwrap_or(Ok(())),
+                self.modifiers.validate(&match_variables),
             ]
             .into_iter()
             .chain(self.insert_statements.iter().map(Validatable::validate)),
@@ -49,6 +49,12 @@ impl Validatable for TypeQLUpdate {
     }
 }
 
+impl Simplifiable for TypeQLUpdate {
+    fn simplify(&mut self) {
+        self.query_delete.simplify();
+    }
+}
+
 impl fmt::Display for TypeQLUpdate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{}", self.query_delete)?;
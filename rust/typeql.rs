@@ -10,6 +10,8 @@
 #[macro_use]
 mod builder;
 pub mod common;
+#[cfg(feature = "engine")]
+pub mod engine;
 pub mod parser;
 pub mod pattern;
 pub mod query;
@@ -18,19 +20,24 @@ pub mod variable;
 mod util;
 
 pub use builder::{
-    abs, ceil, constant, contains, cvar, eq, floor, gt, gte, label, like, lt, lte, neq, not, rel, round, rule, type_,
-    vvar,
+    abs, add_duration, call, ceil, concat, constant, contains, cvar, day, eq, floor, fun, gt, gte, label, length, like,
+    lowercase, lt, lte, month, neq, not, rel, round, rule, struct_, substring, subtract_duration, try_, type_,
+    uppercase, vvar, year,
 };
 use common::Result;
 use parser::{
     visit_eof_definables, visit_eof_label, visit_eof_pattern, visit_eof_patterns, visit_eof_queries, visit_eof_query,
-    visit_eof_schema_rule, visit_eof_statement,
+    visit_eof_schema_rule, visit_eof_statement, ParseOptions,
 };
 use pattern::{Definable, Label, Pattern, Rule, Statement};
 use query::Query;
 
 pub fn parse_query(typeql_query: &str) -> Result<Query> {
-    visit_eof_query(typeql_query.trim_end())
+    parse_query_with(typeql_query, &ParseOptions::default())
+}
+
+pub fn parse_query_with(typeql_query: &str, options: &ParseOptions) -> Result<Query> {
+    visit_eof_query(typeql_query.trim_end(), options)
 }
 
 pub fn parse_queries(typeql_queries: &str) -> Result<impl Iterator<Item = Result<Query>> + '_> {